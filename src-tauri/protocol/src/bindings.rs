@@ -0,0 +1,383 @@
+use crate::events::*;
+use crate::models::actions::{CustomAction, DirectionVector, DirectionVectorStats};
+use crate::models::alarm::{
+  Alarm, AlarmCondition, AlarmField, AlarmRule, AlarmSeverity, BatteryForecast, HealthFlag,
+};
+use crate::models::battery::BatteryEstimate;
+use crate::models::capture::{CapturedFrame, FrameDirection, FrameKind};
+use crate::models::config::{
+  AttitudeIndicator, Config, ControlSource, GamepadBindings, KeyboardBindings, RovProfile,
+};
+use crate::models::connection::{ConnectionPhase, ConnectionState, ConnectionStatus};
+use crate::models::discovery::DiscoveredRov;
+use crate::models::dive::{
+  DiveRecord, DiveRecording, DiveRecordingFormat, DiveReplayState, DiveSample, DiveSession,
+  DiveSummary,
+};
+use crate::models::gamepad::{GamepadData, GamepadEventType};
+use crate::models::handshake::{Capability, Handshake};
+use crate::models::link_quality::{LinkQuality, RttBucket};
+use crate::models::log::{LogEntry, LogLevel, LogOrigin};
+use crate::models::outbound::{DeliveryPolicy, OutboundPriority, QueuedMessage};
+use crate::models::rov_config::{
+  DirectionCoefficients, FirmwareVersion, FluidType, MicrocontrollerFirmwareVariant, Pid, Power,
+  Regulator, RegulatorSuggestions, RovConfig, ThrusterAllocation, ThrusterPinSetup, ThrusterTest,
+};
+use crate::models::rov_status::{RovStatus, SystemHealth};
+use crate::models::rov_telemetry::RovTelemetry;
+use crate::models::telemetry_history::{TelemetryBucket, TelemetryField, TelemetrySeries};
+use crate::models::telemetry_ui::{TelemetryUiMode, TelemetryUiStats};
+use crate::models::toast::{Toast, ToastCancel, ToastType};
+use crate::models::unknown::UnknownMessage;
+use crate::models::vehicle::{VehicleEvent, VehicleId, VehicleInfo};
+use ts_rs::TS;
+
+// Where the frontend imports the bindings from, relative to this crate.
+pub const BINDINGS_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../../src/lib/bindings.ts");
+
+const HEADER: &str = "\
+// Generated from the Rust models by `bun run bindings`, do not edit.
+import { invoke } from '@tauri-apps/api/core';
+import { type EventCallback, listen } from '@tauri-apps/api/event';
+";
+
+// A command as the frontend invokes it. Arguments are camelCase in JavaScript
+// and may be left out when they are optional in Rust.
+struct Command {
+  name: &'static str,
+  args: Vec<(&'static str, String)>,
+  returns: String,
+  signature: CommandSignature,
+}
+
+// A command as declared in Rust, with its argument and result types as
+// written there and spaces left out. State the command takes isn't an
+// argument, and a `Result` stands for what it returns on success.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CommandSignature {
+  pub name: String,
+  pub args: Vec<(String, String)>,
+  pub returns: String,
+}
+
+macro_rules! command {
+  ($name:ident($($arg:ident: $ty:ty),*) -> $returns:ty) => {
+    Command {
+      name: stringify!($name),
+      args: vec![$((stringify!($arg), type_name::<$ty>())),*],
+      returns: type_name::<$returns>(),
+      signature: CommandSignature {
+        name: stringify!($name).to_string(),
+        args: vec![$((stringify!($arg).to_string(), without_spaces(stringify!($ty)))),*],
+        returns: without_spaces(stringify!($returns)),
+      },
+    }
+  };
+}
+
+// Everything the frontend shares with the backend, as one TypeScript module.
+pub fn typescript() -> String {
+  let mut out = String::from(HEADER);
+  for (name, alias) in aliases() {
+    out.push_str(&format!("\nexport type {} = {};\n", name, alias));
+  }
+  for declaration in declarations() {
+    out.push_str(&format!("\nexport {}\n", declaration));
+  }
+
+  out.push_str("\nexport type EventPayloads = {\n");
+  for (event, payload) in events() {
+    out.push_str(&format!("  {}: {};\n", event, payload));
+  }
+  out.push_str(
+    "};
+
+export function listenEvent<E extends keyof EventPayloads>(
+  event: E,
+  handler: EventCallback<EventPayloads[E]>,
+) {
+  return listen<EventPayloads[E]>(event, handler);
+}
+
+export const commands = {
+",
+  );
+  for command in commands() {
+    out.push_str(&command.typescript());
+  }
+  out.push_str("};\n");
+  out
+}
+
+fn aliases() -> Vec<(&'static str, String)> {
+  vec![
+    ("VehicleId", VehicleId::inline()),
+    ("DirectionVector", DirectionVector::inline()),
+    ("CustomAction", CustomAction::inline()),
+    ("ThrusterAllocation", ThrusterAllocation::inline()),
+    ("ThrusterTest", ThrusterTest::inline()),
+    ("FirmwareVersion", FirmwareVersion::inline()),
+  ]
+}
+
+fn declarations() -> Vec<String> {
+  vec![
+    VehicleEvent::<()>::decl(),
+    VehicleInfo::decl(),
+    ConnectionPhase::decl(),
+    ConnectionState::decl(),
+    ConnectionStatus::decl(),
+    Capability::decl(),
+    Handshake::decl(),
+    RttBucket::decl(),
+    LinkQuality::decl(),
+    OutboundPriority::decl(),
+    DeliveryPolicy::decl(),
+    QueuedMessage::decl(),
+    DirectionVectorStats::decl(),
+    MicrocontrollerFirmwareVariant::decl(),
+    FluidType::decl(),
+    ThrusterPinSetup::decl(),
+    Pid::decl(),
+    Regulator::decl(),
+    RegulatorSuggestions::decl(),
+    DirectionCoefficients::decl(),
+    Power::decl(),
+    RovConfig::decl(),
+    SystemHealth::decl(),
+    RovStatus::decl(),
+    RovTelemetry::decl(),
+    TelemetryUiStats::decl(),
+    TelemetryField::decl(),
+    TelemetryBucket::decl(),
+    TelemetrySeries::decl(),
+    HealthFlag::decl(),
+    BatteryForecast::decl(),
+    AlarmField::decl(),
+    AlarmCondition::decl(),
+    AlarmSeverity::decl(),
+    AlarmRule::decl(),
+    Alarm::decl(),
+    BatteryEstimate::decl(),
+    LogLevel::decl(),
+    LogOrigin::decl(),
+    LogEntry::decl(),
+    ToastType::decl(),
+    ToastCancel::decl(),
+    Toast::decl(),
+    UnknownMessage::decl(),
+    DiscoveredRov::decl(),
+    GamepadEventType::decl(),
+    GamepadData::decl(),
+    KeyboardBindings::decl(),
+    ControlSource::decl(),
+    AttitudeIndicator::decl(),
+    GamepadBindings::decl(),
+    RovProfile::decl(),
+    TelemetryUiMode::decl(),
+    Config::decl(),
+    FrameDirection::decl(),
+    FrameKind::decl(),
+    CapturedFrame::decl(),
+    DiveRecordingFormat::decl(),
+    DiveRecording::decl(),
+    DiveSample::decl(),
+    DiveRecord::decl(),
+    DiveSummary::decl(),
+    DiveSession::decl(),
+    DiveReplayState::decl(),
+  ]
+}
+
+fn events() -> Vec<(&'static str, String)> {
+  vec![
+    (ROV_TELEMETRY, VehicleEvent::<RovTelemetry>::name()),
+    (
+      ROV_TELEMETRY_BATCH,
+      VehicleEvent::<Vec<RovTelemetry>>::name(),
+    ),
+    (ROV_STATUS_UPDATE, VehicleEvent::<RovStatus>::name()),
+    (ROV_CONFIG_RECEIVED, VehicleEvent::<RovConfig>::name()),
+    (
+      REGULATOR_SUGGESTIONS_RECEIVED,
+      VehicleEvent::<RegulatorSuggestions>::name(),
+    ),
+    (
+      FIRMWARE_VERSION_RECEIVED,
+      VehicleEvent::<FirmwareVersion>::name(),
+    ),
+    (ROV_UNKNOWN_MESSAGE, VehicleEvent::<UnknownMessage>::name()),
+    (ROV_HANDSHAKE_COMPLETED, VehicleEvent::<Handshake>::name()),
+    (
+      ROV_CONNECTION_STATE_CHANGED,
+      VehicleEvent::<ConnectionState>::name(),
+    ),
+    (
+      ROV_CONNECTION_STATUS_UPDATED,
+      VehicleEvent::<ConnectionStatus>::name(),
+    ),
+    (LINK_QUALITY, VehicleEvent::<LinkQuality>::name()),
+    (LOG_MESSAGE, VehicleEvent::<LogEntry>::name()),
+    (DIVE_REPLAY_STATE, VehicleEvent::<DiveReplayState>::name()),
+    (ALARM_CHANGED, VehicleEvent::<Alarm>::name()),
+    (BATTERY_ESTIMATE, VehicleEvent::<BatteryEstimate>::name()),
+    (SHOW_TOAST, VehicleEvent::<Toast>::name()),
+    (DISCOVERED_ROVS, Vec::<DiscoveredRov>::name()),
+    (GAMEPAD_EVENT, GamepadData::name()),
+  ]
+}
+
+// The commands registered in `run`, with the arguments the frontend passes.
+// Types are written as the commands declare them, tests/bindings.rs checks
+// them against the command signatures.
+fn commands() -> Vec<Command> {
+  vec![
+    command!(get_config() -> Config),
+    command!(set_config(payload: Config) -> ()),
+    command!(create_rov_profile(payload: RovProfile) -> Config),
+    command!(update_rov_profile(name: String, payload: RovProfile) -> Config),
+    command!(delete_rov_profile(name: String) -> Config),
+    command!(switch_rov_profile(name: String) -> Config),
+    command!(list_vehicles() -> Vec<VehicleInfo>),
+    command!(open_vehicle(profile: String) -> Vec<VehicleInfo>),
+    command!(close_vehicle(vehicle: VehicleId) -> Vec<VehicleInfo>),
+    command!(set_pilot_vehicle(vehicle: VehicleId) -> Vec<VehicleInfo>),
+    command!(discover_rovs() -> Vec<DiscoveredRov>),
+    command!(start_gamepad_stream() -> ()),
+    command!(send_direction_vector(payload: DirectionVector) -> ()),
+    command!(get_direction_vector_stats(vehicle: Option<VehicleId>) -> DirectionVectorStats),
+    command!(send_custom_action(vehicle: Option<VehicleId>, payload: CustomAction) -> ()),
+    command!(toggle_pitch_stabilization(vehicle: Option<VehicleId>) -> ()),
+    command!(toggle_roll_stabilization(vehicle: Option<VehicleId>) -> ()),
+    command!(toggle_depth_hold(vehicle: Option<VehicleId>) -> ()),
+    command!(save_recording(temp_path: String) -> ()),
+    command!(append_recording_chunk(temp_path: String, chunk: Vec<u8>) -> ()),
+    command!(request_rov_config(vehicle: Option<VehicleId>) -> RovConfig),
+    command!(set_rov_config(vehicle: Option<VehicleId>, payload: RovConfig) -> RovConfig),
+    command!(start_thruster_test(vehicle: Option<VehicleId>, payload: ThrusterTest) -> ()),
+    command!(cancel_thruster_test(vehicle: Option<VehicleId>, payload: ThrusterTest) -> ()),
+    command!(start_regulator_auto_tuning(vehicle: Option<VehicleId>) -> ()),
+    command!(cancel_regulator_auto_tuning(vehicle: Option<VehicleId>) -> ()),
+    command!(
+      flash_microcontroller_firmware(
+        vehicle: Option<VehicleId>,
+        payload: MicrocontrollerFirmwareVariant
+      ) -> ()
+    ),
+    command!(get_rov_handshake(vehicle: Option<VehicleId>) -> Option<Handshake>),
+    command!(get_connection_state(vehicle: Option<VehicleId>) -> ConnectionState),
+    command!(connect(vehicle: Option<VehicleId>) -> ()),
+    command!(disconnect(vehicle: Option<VehicleId>) -> ()),
+    command!(forget_rov_certificate(vehicle: Option<VehicleId>) -> Config),
+    command!(get_link_quality(vehicle: Option<VehicleId>) -> LinkQuality),
+    command!(get_telemetry_ui_stats(vehicle: Option<VehicleId>) -> TelemetryUiStats),
+    command!(
+      query_telemetry(
+        vehicle: Option<VehicleId>,
+        fields: Vec<TelemetryField>,
+        from: Option<u64>,
+        to: Option<u64>,
+        max_points: u32
+      ) -> Vec<TelemetrySeries>
+    ),
+    command!(list_alarms() -> Vec<Alarm>),
+    command!(acknowledge_alarm(id: u64) -> Alarm),
+    command!(get_battery_estimate(vehicle: Option<VehicleId>) -> Option<BatteryEstimate>),
+    command!(
+      start_dive_recording(
+        vehicle: Option<VehicleId>,
+        format: Option<DiveRecordingFormat>
+      ) -> DiveRecording
+    ),
+    command!(stop_dive_recording(vehicle: Option<VehicleId>) -> DiveRecording),
+    command!(list_dive_recordings() -> Vec<DiveRecording>),
+    command!(
+      start_dive_session(
+        vehicle: Option<VehicleId>,
+        operator: Option<String>,
+        site: Option<String>
+      ) -> DiveSession
+    ),
+    command!(stop_dive_session(vehicle: Option<VehicleId>) -> DiveSession),
+    command!(list_dive_sessions() -> Vec<DiveSession>),
+    command!(
+      start_dive_replay(
+        path: String,
+        speed: Option<f64>,
+        vehicle: Option<VehicleId>
+      ) -> DiveReplayState
+    ),
+    command!(pause_dive_replay() -> DiveReplayState),
+    command!(resume_dive_replay() -> DiveReplayState),
+    command!(seek_dive_replay(position_ms: u64) -> DiveReplayState),
+    command!(set_dive_replay_speed(speed: f64) -> DiveReplayState),
+    command!(stop_dive_replay() -> DiveReplayState),
+    command!(get_dive_replay_state() -> Option<DiveReplayState>),
+    command!(get_outbound_queue(vehicle: Option<VehicleId>) -> Vec<QueuedMessage>),
+    command!(cancel_outbound_message(vehicle: Option<VehicleId>, id: u64) -> ()),
+    command!(
+      replay_capture(paths: Vec<String>, speed: Option<f64>, vehicle: Option<VehicleId>) -> ()
+    ),
+    command!(stop_capture_replay() -> bool),
+  ]
+}
+
+// The commands the bindings are written for, to check against those the app
+// registers.
+pub fn command_signatures() -> Vec<CommandSignature> {
+  commands()
+    .into_iter()
+    .map(|command| command.signature)
+    .collect()
+}
+
+impl Command {
+  fn typescript(&self) -> String {
+    let invoke = format!("invoke<{}>('{}'", self.returns, self.name);
+    let name = camel_case(self.name);
+    if self.args.is_empty() {
+      return format!("  {}: () => {}),\n", name, invoke);
+    }
+
+    let args: Vec<String> = self
+      .args
+      .iter()
+      .map(|(arg, ty)| match ty.strip_suffix(" | null") {
+        Some(_) => format!("{}?: {}", camel_case(arg), ty),
+        None => format!("{}: {}", camel_case(arg), ty),
+      })
+      .collect();
+    let optional = args.iter().all(|arg| arg.contains("?:"));
+    format!(
+      "  {}: (args: {{ {} }}{}) =>\n    {}, args),\n",
+      name,
+      args.join("; "),
+      if optional { " = {}" } else { "" },
+      invoke
+    )
+  }
+}
+
+// Command arguments and results cross as JSON, where 64 bit integers are plain
+// numbers rather than the `bigint` ts-rs assumes.
+fn type_name<T: TS>() -> String {
+  T::name().replace("bigint", "number")
+}
+
+// Rust types as `stringify!` and the source write them alike.
+pub fn without_spaces(text: &str) -> String {
+  text.chars().filter(|c| !c.is_whitespace()).collect()
+}
+
+fn camel_case(name: &str) -> String {
+  let mut words = name.split('_');
+  let mut out = words.next().unwrap_or_default().to_string();
+  for word in words {
+    let mut chars = word.chars();
+    if let Some(first) = chars.next() {
+      out.extend(first.to_uppercase());
+      out.push_str(chars.as_str());
+    }
+  }
+  out
+}
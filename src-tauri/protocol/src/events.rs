@@ -0,0 +1,21 @@
+// Names of the events emitted to the frontend. Their payloads are listed in
+// `bindings::events`, which the frontend listens through.
+pub const ROV_TELEMETRY: &str = "rov_telemetry";
+pub const ROV_TELEMETRY_BATCH: &str = "rov_telemetry_batch";
+pub const ROV_STATUS_UPDATE: &str = "rov_status_update";
+pub const ROV_CONFIG_RECEIVED: &str = "rov_config_received";
+pub const REGULATOR_SUGGESTIONS_RECEIVED: &str = "regulator_suggestions_received";
+// The misspelling is kept so existing listeners keep working.
+pub const FIRMWARE_VERSION_RECEIVED: &str = "firmware_version_recieved";
+pub const ROV_UNKNOWN_MESSAGE: &str = "rov_unknown_message";
+pub const ROV_HANDSHAKE_COMPLETED: &str = "rov_handshake_completed";
+pub const ROV_CONNECTION_STATE_CHANGED: &str = "rov_connection_state_changed";
+pub const ROV_CONNECTION_STATUS_UPDATED: &str = "rov_connection_status_updated";
+pub const LINK_QUALITY: &str = "link_quality";
+pub const LOG_MESSAGE: &str = "log_message";
+pub const DIVE_REPLAY_STATE: &str = "dive_replay_state";
+pub const ALARM_CHANGED: &str = "alarm_changed";
+pub const BATTERY_ESTIMATE: &str = "battery_estimate";
+pub const SHOW_TOAST: &str = "show_toast";
+pub const DISCOVERED_ROVS: &str = "discovered_rovs";
+pub const GAMEPAD_EVENT: &str = "gamepad_event";
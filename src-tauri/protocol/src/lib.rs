@@ -0,0 +1,28 @@
+pub mod models {
+  pub mod actions;
+  pub mod alarm;
+  pub mod battery;
+  pub mod capture;
+  pub mod config;
+  pub mod connection;
+  pub mod discovery;
+  pub mod dive;
+  pub mod gamepad;
+  pub mod handshake;
+  pub mod link_quality;
+  pub mod log;
+  pub mod outbound;
+  pub mod rov_config;
+  pub mod rov_status;
+  pub mod rov_telemetry;
+  pub mod telemetry_history;
+  pub mod telemetry_ui;
+  pub mod toast;
+  pub mod unknown;
+  pub mod vehicle;
+}
+
+pub mod bindings;
+pub mod events;
+pub mod message;
+pub mod schema;
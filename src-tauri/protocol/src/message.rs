@@ -0,0 +1,128 @@
+use crate::models::{
+  actions::{CustomAction, DirectionVector},
+  handshake::Hello,
+  log::LogEntry,
+  rov_config::{
+    FirmwareVersion, MicrocontrollerFirmwareVariant, RegulatorSuggestions, RovConfig, ThrusterTest,
+  },
+  rov_status::RovStatus,
+  rov_telemetry::RovTelemetry,
+  toast::Toast,
+  unknown::UnknownMessage,
+};
+use once_cell::sync::Lazy;
+use schemars::JsonSchema;
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
+use std::collections::HashSet;
+
+#[derive(Serialize, Deserialize, Debug, Clone, JsonSchema)]
+#[serde(tag = "type", content = "payload", rename_all = "camelCase")]
+pub enum WebsocketMessage {
+  DirectionVector(DirectionVector),
+  GetConfig,
+  SetConfig(RovConfig),
+  Config(RovConfig),
+  StartThrusterTest(ThrusterTest),
+  CancelThrusterTest(ThrusterTest),
+  StartRegulatorAutoTuning,
+  CancelRegulatorAutoTuning,
+  RegulatorSuggestions(RegulatorSuggestions),
+  ShowToast(Toast),
+  LogMessage(LogEntry),
+  StatusUpdate(RovStatus),
+  Telemetry(RovTelemetry),
+  FirmwareVersion(FirmwareVersion),
+  CustomAction(CustomAction),
+  TogglePitchStabilization,
+  ToggleRollStabilization,
+  ToggleDepthHold,
+  FlashMicrocontrollerFirmware(MicrocontrollerFirmwareVariant),
+  Ack,
+  Nack(String),
+  Hello(Hello),
+  // Only ever deserialized from, and serialized back to, its raw JSON by
+  // `WebsocketEnvelope`.
+  #[serde(skip)]
+  #[schemars(skip)]
+  Unknown(UnknownMessage),
+}
+
+// The `type` of every message this app knows, read off the schema so it can't
+// fall behind the enum.
+static MESSAGE_TYPES: Lazy<HashSet<String>> = Lazy::new(|| {
+  let schema = schemars::schema_for!(WebsocketMessage).to_value();
+  schema["oneOf"]
+    .as_array()
+    .into_iter()
+    .flatten()
+    .filter_map(|variant| variant["properties"]["type"]["const"].as_str())
+    .map(str::to_string)
+    .collect()
+});
+
+// Requests carry an `id` that the ROV copies onto its reply, either the
+// matching message (e.g. `config` for `getConfig`) or an `ack`/`nack`.
+// Messages of a type newer firmware added become `WebsocketMessage::Unknown`
+// instead of failing, while a known type with a broken payload still fails.
+#[derive(Debug, Clone, JsonSchema)]
+pub struct WebsocketEnvelope {
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub id: Option<u64>,
+  #[serde(flatten)]
+  pub message: WebsocketMessage,
+}
+
+#[derive(Serialize, Deserialize)]
+struct KnownEnvelope<M> {
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  id: Option<u64>,
+  #[serde(flatten)]
+  message: M,
+}
+
+impl Serialize for WebsocketEnvelope {
+  fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    match &self.message {
+      WebsocketMessage::Unknown(unknown) => unknown.raw.serialize(serializer),
+      message => KnownEnvelope {
+        id: self.id,
+        message,
+      }
+      .serialize(serializer),
+    }
+  }
+}
+
+impl<'de> Deserialize<'de> for WebsocketEnvelope {
+  fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+    let raw = Value::deserialize(deserializer)?;
+    let message_type = match raw.get("type") {
+      Some(Value::String(message_type)) => message_type,
+      Some(_) => return Err(D::Error::custom("message type is not a string")),
+      None => return Err(D::Error::missing_field("type")),
+    };
+
+    if !MESSAGE_TYPES.contains(message_type) {
+      return Ok(WebsocketEnvelope {
+        id: raw.get("id").and_then(Value::as_u64),
+        message: WebsocketMessage::Unknown(UnknownMessage {
+          message_type: message_type.clone(),
+          raw,
+        }),
+      });
+    }
+    let envelope = KnownEnvelope::<WebsocketMessage>::deserialize(raw).map_err(D::Error::custom)?;
+    Ok(WebsocketEnvelope {
+      id: envelope.id,
+      message: envelope.message,
+    })
+  }
+}
+
+impl From<WebsocketMessage> for WebsocketEnvelope {
+  fn from(message: WebsocketMessage) -> Self {
+    WebsocketEnvelope { id: None, message }
+  }
+}
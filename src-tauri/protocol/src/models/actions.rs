@@ -0,0 +1,17 @@
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+pub type DirectionVector = [f32; 8];
+
+pub type CustomAction = String;
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct DirectionVectorStats {
+  #[ts(type = "number")]
+  pub received: u64,
+  #[ts(type = "number")]
+  pub sent: u64,
+  #[ts(type = "number")]
+  pub coalesced: u64,
+}
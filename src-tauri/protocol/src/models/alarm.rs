@@ -0,0 +1,118 @@
+use super::telemetry_history::TelemetryField;
+use super::vehicle::VehicleId;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, TS)]
+#[serde(rename_all = "camelCase")]
+pub enum HealthFlag {
+  ImuOk,
+  PressureSensorOk,
+  MicrocontrollerOk,
+}
+
+// In minutes, from the battery estimate.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, TS)]
+#[serde(rename_all = "camelCase")]
+pub enum BatteryForecast {
+  // Negative once it is past time to head back up.
+  RemainingDiveMinutes,
+  MinimumVoltageMinutes,
+}
+
+// What an alarm rule watches. Health flags read as 1 while ok and 0 once not.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, TS)]
+#[serde(untagged)]
+pub enum AlarmField {
+  Reading(TelemetryField),
+  Health(HealthFlag),
+  Battery(BatteryForecast),
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, TS)]
+#[serde(rename_all = "camelCase")]
+pub enum AlarmCondition {
+  Above,
+  Below,
+  // The change per minute over `rate_window_ms`, for slowly developing
+  // problems such as a rising temperature.
+  RisingFaster,
+  FallingFaster,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, TS)]
+#[serde(rename_all = "camelCase")]
+pub enum AlarmSeverity {
+  Info,
+  Warning,
+  Critical,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct AlarmRule {
+  // Names the alarms of the rule, unique among the rules.
+  pub name: String,
+  pub field: AlarmField,
+  pub condition: AlarmCondition,
+  pub threshold: f32,
+  // How long the condition has to hold before the alarm is raised.
+  #[serde(default)]
+  #[ts(type = "number")]
+  pub duration_ms: u64,
+  // How far back past the threshold the reading has to go for the alarm to
+  // clear, so a reading hovering around it doesn't raise it over and over.
+  #[serde(default)]
+  pub hysteresis: f32,
+  #[serde(default = "default_rate_window_ms")]
+  #[ts(type = "number")]
+  pub rate_window_ms: u64,
+  pub severity: AlarmSeverity,
+}
+
+fn default_rate_window_ms() -> u64 {
+  60_000
+}
+
+// An alarm is listed until it is both cleared and acknowledged. Raised again
+// before that, it is the same alarm.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct Alarm {
+  #[ts(type = "number")]
+  pub id: u64,
+  pub vehicle_id: VehicleId,
+  pub rule: String,
+  pub severity: AlarmSeverity,
+  pub message: String,
+  // The reading, or its change per minute, that raised the alarm.
+  pub value: f32,
+  #[ts(type = "number")]
+  pub raised_ms: u64,
+  // When the condition stopped holding, None while it still does.
+  #[ts(type = "number | null")]
+  pub cleared_ms: Option<u64>,
+  #[ts(type = "number | null")]
+  pub acknowledged_ms: Option<u64>,
+}
+
+impl AlarmRule {
+  pub fn new(
+    name: &str,
+    field: AlarmField,
+    condition: AlarmCondition,
+    threshold: f32,
+    severity: AlarmSeverity,
+  ) -> Self {
+    AlarmRule {
+      name: name.to_string(),
+      field,
+      condition,
+      threshold,
+      duration_ms: 0,
+      hysteresis: 0.0,
+      rate_window_ms: default_rate_window_ms(),
+      severity,
+    }
+  }
+}
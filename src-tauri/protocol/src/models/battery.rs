@@ -0,0 +1,24 @@
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+// How long the battery of a vehicle is expected to last, from how fast its
+// percentage dropped and how hard the thrusters work now compared to then.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct BatteryEstimate {
+  pub battery_percentage: u8,
+  // Within the ROV config's battery voltage range, None until it is known.
+  pub voltage: Option<f32>,
+  // Of the thrusters, from 0 idle to 1 at full thrust.
+  pub load: f32,
+  // Percent a minute at the current load, None until the percentage dropped.
+  pub drain_per_minute: Option<f32>,
+  #[ts(type = "number | null")]
+  pub minimum_voltage_in_ms: Option<u64>,
+  // Ascending from the current depth plus the reserve kept.
+  #[ts(type = "number")]
+  pub return_to_surface_ms: u64,
+  // Dive time left before heading back up, 0 once it is time.
+  #[ts(type = "number | null")]
+  pub remaining_dive_ms: Option<u64>,
+}
@@ -0,0 +1,32 @@
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, TS)]
+#[serde(rename_all = "camelCase")]
+pub enum FrameDirection {
+  Inbound,
+  Outbound,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, TS)]
+#[serde(rename_all = "camelCase")]
+pub enum FrameKind {
+  Text,
+  Binary,
+  Ping,
+  Pong,
+  Close,
+}
+
+// One line of a capture file. `elapsed_ms` counts from the start of the
+// capture on a monotonic clock, `text` is the raw frame, lossily decoded when
+// it is not UTF-8.
+#[derive(Serialize, Deserialize, Clone, Debug, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct CapturedFrame {
+  pub direction: FrameDirection,
+  #[ts(type = "number")]
+  pub elapsed_ms: u64,
+  pub kind: FrameKind,
+  pub text: String,
+}
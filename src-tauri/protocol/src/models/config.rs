@@ -0,0 +1,492 @@
+use super::alarm::{
+  AlarmCondition, AlarmField, AlarmRule, AlarmSeverity, BatteryForecast, HealthFlag,
+};
+use super::rov_config::{MicrocontrollerFirmwareVariant, RovConfig};
+use super::telemetry_history::TelemetryField;
+use super::telemetry_ui::TelemetryUiMode;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use ts_rs::TS;
+
+#[derive(Serialize, Deserialize, Clone, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct KeyboardBindings {
+  pub surge_forward: String,
+  pub surge_backward: String,
+  pub sway_left: String,
+  pub sway_right: String,
+  pub heave_up: String,
+  pub heave_down: String,
+  pub yaw_left: String,
+  pub yaw_right: String,
+  pub pitch_up: String,
+  pub pitch_down: String,
+  pub roll_left: String,
+  pub roll_right: String,
+  pub action1_positive: String,
+  pub action1_negative: String,
+  pub action2_positive: String,
+  pub action2_negative: String,
+  pub pitch_stabilization: String,
+  pub roll_stabilization: String,
+  pub depth_hold: String,
+  pub record: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, TS)]
+#[serde(rename_all = "camelCase")]
+pub enum ControlSource {
+  LeftStick,
+  RightStick,
+  DPad,
+  FaceButtons,
+}
+
+#[derive(Serialize, Deserialize, Clone, TS)]
+#[serde(rename_all = "camelCase")]
+pub enum AttitudeIndicator {
+  Scientific,
+  Dimensional3D,
+  Disabled,
+}
+
+#[derive(Serialize, Deserialize, Clone, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct GamepadBindings {
+  pub surge_sway: ControlSource,
+  pub heave_up: String,
+  pub heave_down: String,
+  pub pitch_yaw: ControlSource,
+  pub roll_left: String,
+  pub roll_right: String,
+  pub action1_positive: String,
+  pub action1_negative: String,
+  pub action2_positive: String,
+  pub action2_negative: String,
+  pub pitch_stabilization: String,
+  pub roll_stabilization: String,
+  pub depth_hold: String,
+  pub record: String,
+}
+
+// A vehicle of the fleet. Switching to a profile copies its endpoint into the
+// top level connection settings of `Config`, which the client connects to.
+#[derive(Serialize, Deserialize, Clone, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct RovProfile {
+  pub name: String,
+  pub ip_address: String,
+  pub web_socket_port: u16,
+  pub webrtc_signaling_api_port: u16,
+  pub webrtc_signaling_api_path: String,
+  #[serde(default)]
+  pub use_tls: bool,
+  #[serde(default)]
+  pub certificate_fingerprint: Option<String>,
+  #[serde(default)]
+  pub auth_token: Option<String>,
+  pub expected_firmware_variant: Option<MicrocontrollerFirmwareVariant>,
+  pub last_known_rov_config: Option<RovConfig>,
+  pub notes: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, TS)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct Config {
+  pub auto_update: bool,
+  pub attitude_indicator: AttitudeIndicator,
+  pub work_indicator: bool,
+  pub thruster_rpm_overlay: bool,
+  pub video_directory: String,
+  pub ip_address: String,
+  pub webrtc_signaling_api_port: u16,
+  pub webrtc_signaling_api_path: String,
+  pub web_socket_port: u16,
+  // Connects with wss:// and trusts the ROV's certificate by its SHA-256
+  // fingerprint, pinned on first use.
+  #[serde(default)]
+  pub use_tls: bool,
+  #[serde(default)]
+  pub certificate_fingerprint: Option<String>,
+  // Sent as a bearer token when opening the websocket.
+  #[serde(default)]
+  pub auth_token: Option<String>,
+  #[serde(default)]
+  pub rov_profiles: Vec<RovProfile>,
+  #[serde(default)]
+  pub active_rov_profile: Option<String>,
+  // Thrust is neutralised when no direction vector arrives for this long.
+  #[serde(default = "default_control_watchdog_timeout_ms")]
+  #[ts(type = "number")]
+  pub control_watchdog_timeout_ms: u64,
+  // How often the latest direction vector is sent to the ROV.
+  #[serde(default = "default_direction_vector_rate_hz")]
+  pub direction_vector_rate_hz: u32,
+  // How often telemetry is passed on to the frontend, 0 passes on every frame.
+  // Message handlers, e.g. recording it, see every frame regardless.
+  #[serde(default = "default_telemetry_ui_rate_hz")]
+  pub telemetry_ui_rate_hz: u32,
+  #[serde(default)]
+  pub telemetry_ui_mode: TelemetryUiMode,
+  // How far back `query_telemetry` reaches, 0 keeps no history.
+  #[serde(default = "default_telemetry_history_minutes")]
+  pub telemetry_history_minutes: u32,
+  // Checked against every telemetry and status frame of every vehicle.
+  #[serde(default = "default_alarm_rules")]
+  pub alarm_rules: Vec<AlarmRule>,
+  // The battery estimate keeps enough for ascending at `ascent_speed` meters a
+  // second plus this reserve, for the pilot to head back up in time.
+  #[serde(default = "default_battery_reserve_minutes")]
+  pub battery_reserve_minutes: u32,
+  #[serde(default = "default_ascent_speed")]
+  pub ascent_speed: f32,
+  // Writes every websocket frame to rotating JSONL files in
+  // `capture_directory`, to reproduce what the pilot saw later.
+  #[serde(default)]
+  pub capture_traffic: bool,
+  #[serde(default = "default_capture_directory")]
+  pub capture_directory: String,
+  // Opens a dive session whenever a vehicle connects, otherwise they are
+  // only opened with `start_dive_session`.
+  #[serde(default)]
+  pub dive_session_on_connect: bool,
+  // Who dives where, recorded with every dive session.
+  #[serde(default)]
+  pub dive_operator: String,
+  #[serde(default)]
+  pub dive_site: String,
+  pub info_logging: bool,
+  pub keyboard: KeyboardBindings,
+  pub gamepad: GamepadBindings,
+}
+
+impl Default for Config {
+  fn default() -> Self {
+    let video_directory = if cfg!(target_os = "windows") {
+      format!(
+        "{}\\Videos\\Manafish",
+        std::env::var("USERPROFILE").unwrap_or_else(|_| "C:\\Users\\Default".to_string())
+      )
+    } else if cfg!(target_os = "macos") {
+      format!(
+        "{}/Movies/Manafish",
+        std::env::var("HOME").unwrap_or_else(|_| "/Users/default".to_string())
+      )
+    } else {
+      format!(
+        "{}/Videos/Manafish",
+        std::env::var("HOME").unwrap_or_else(|_| "/home/user".to_string())
+      )
+    };
+
+    Config {
+      auto_update: false,
+      attitude_indicator: AttitudeIndicator::Scientific,
+      work_indicator: false,
+      thruster_rpm_overlay: false,
+      video_directory,
+      ip_address: "10.10.10.10".to_string(),
+      webrtc_signaling_api_port: 1984,
+      webrtc_signaling_api_path: "/api/webrtc?src=cam".to_string(),
+      web_socket_port: 9000,
+      use_tls: false,
+      certificate_fingerprint: None,
+      auth_token: None,
+      rov_profiles: Vec::new(),
+      active_rov_profile: None,
+      control_watchdog_timeout_ms: default_control_watchdog_timeout_ms(),
+      direction_vector_rate_hz: default_direction_vector_rate_hz(),
+      telemetry_ui_rate_hz: default_telemetry_ui_rate_hz(),
+      telemetry_ui_mode: TelemetryUiMode::default(),
+      telemetry_history_minutes: default_telemetry_history_minutes(),
+      alarm_rules: default_alarm_rules(),
+      battery_reserve_minutes: default_battery_reserve_minutes(),
+      ascent_speed: default_ascent_speed(),
+      capture_traffic: false,
+      capture_directory: default_capture_directory(),
+      dive_session_on_connect: false,
+      dive_operator: String::new(),
+      dive_site: String::new(),
+      info_logging: false,
+      keyboard: KeyboardBindings {
+        surge_forward: "KeyW".to_string(),
+        surge_backward: "KeyS".to_string(),
+        sway_left: "KeyA".to_string(),
+        sway_right: "KeyD".to_string(),
+        heave_up: "Space".to_string(),
+        heave_down: "ShiftLeft".to_string(),
+        pitch_up: "KeyI".to_string(),
+        pitch_down: "KeyK".to_string(),
+        yaw_left: "KeyJ".to_string(),
+        yaw_right: "KeyL".to_string(),
+        roll_left: "KeyQ".to_string(),
+        roll_right: "KeyE".to_string(),
+        action1_positive: "Digit1".to_string(),
+        action1_negative: "Digit2".to_string(),
+        action2_positive: "Digit3".to_string(),
+        action2_negative: "Digit4".to_string(),
+        pitch_stabilization: "KeyU".to_string(),
+        roll_stabilization: "KeyU".to_string(),
+        depth_hold: "KeyO".to_string(),
+        record: "KeyR".to_string(),
+      },
+      gamepad: GamepadBindings {
+        surge_sway: ControlSource::LeftStick,
+        heave_up: "7".to_string(),
+        heave_down: "6".to_string(),
+        pitch_yaw: ControlSource::RightStick,
+        roll_left: "4".to_string(),
+        roll_right: "5".to_string(),
+        action1_positive: "0".to_string(),
+        action1_negative: "1".to_string(),
+        action2_positive: "2".to_string(),
+        action2_negative: "3".to_string(),
+        pitch_stabilization: "12".to_string(),
+        roll_stabilization: "12".to_string(),
+        depth_hold: "13".to_string(),
+        record: "9".to_string(),
+      },
+    }
+  }
+}
+
+impl Config {
+  // Dive recordings go next to the videos, e.g. `Videos/Manafish Data` for
+  // `Videos/Manafish`.
+  pub fn dive_directory(&self) -> PathBuf {
+    let videos = Path::new(&self.video_directory);
+    let name = videos
+      .file_name()
+      .map(|name| name.to_string_lossy().into_owned())
+      .unwrap_or_else(|| "Manafish".to_string());
+    videos.with_file_name(format!("{} Data", name))
+  }
+
+  pub fn active_rov_profile(&self) -> Option<&RovProfile> {
+    let name = self.active_rov_profile.as_ref()?;
+    self
+      .rov_profiles
+      .iter()
+      .find(|profile| &profile.name == name)
+  }
+
+  pub fn active_rov_profile_mut(&mut self) -> Option<&mut RovProfile> {
+    let name = self.active_rov_profile.as_ref()?;
+    self
+      .rov_profiles
+      .iter_mut()
+      .find(|profile| &profile.name == name)
+  }
+
+  pub fn create_rov_profile(&mut self, profile: RovProfile) -> Result<(), String> {
+    self.check_rov_profile_name(&profile.name, None)?;
+    self.rov_profiles.push(profile);
+    Ok(())
+  }
+
+  pub fn update_rov_profile(&mut self, name: &str, mut profile: RovProfile) -> Result<(), String> {
+    self.check_rov_profile_name(&profile.name, Some(name))?;
+    let index = self.rov_profile_index(name)?;
+    let active = self.active_rov_profile.as_deref() == Some(name);
+    // The pinned certificate is learned from the ROV, not edited.
+    profile.certificate_fingerprint = self.rov_profiles[index].certificate_fingerprint.take();
+    self.rov_profiles[index] = profile;
+    if active {
+      self.switch_rov_profile(&self.rov_profiles[index].name.clone())?;
+    }
+    Ok(())
+  }
+
+  pub fn delete_rov_profile(&mut self, name: &str) -> Result<(), String> {
+    let index = self.rov_profile_index(name)?;
+    self.rov_profiles.remove(index);
+    if self.active_rov_profile.as_deref() == Some(name) {
+      self.active_rov_profile = None;
+    }
+    Ok(())
+  }
+
+  pub fn switch_rov_profile(&mut self, name: &str) -> Result<(), String> {
+    let profile = &self.rov_profiles[self.rov_profile_index(name)?];
+    self.ip_address = profile.ip_address.clone();
+    self.web_socket_port = profile.web_socket_port;
+    self.webrtc_signaling_api_port = profile.webrtc_signaling_api_port;
+    self.webrtc_signaling_api_path = profile.webrtc_signaling_api_path.clone();
+    self.use_tls = profile.use_tls;
+    self.certificate_fingerprint = profile.certificate_fingerprint.clone();
+    self.auth_token = profile.auth_token.clone();
+    self.active_rov_profile = Some(name.to_string());
+    Ok(())
+  }
+
+  // Keeps the active profile in step with connection settings edited directly.
+  pub fn store_endpoint_in_active_rov_profile(&mut self) {
+    let ip_address = self.ip_address.clone();
+    let web_socket_port = self.web_socket_port;
+    let webrtc_signaling_api_port = self.webrtc_signaling_api_port;
+    let webrtc_signaling_api_path = self.webrtc_signaling_api_path.clone();
+    let use_tls = self.use_tls;
+    let certificate_fingerprint = self.certificate_fingerprint.clone();
+    let auth_token = self.auth_token.clone();
+    if let Some(profile) = self.active_rov_profile_mut() {
+      profile.ip_address = ip_address;
+      profile.web_socket_port = web_socket_port;
+      profile.webrtc_signaling_api_port = webrtc_signaling_api_port;
+      profile.webrtc_signaling_api_path = webrtc_signaling_api_path;
+      profile.use_tls = use_tls;
+      profile.certificate_fingerprint = certificate_fingerprint;
+      profile.auth_token = auth_token;
+    }
+  }
+
+  // Pins the certificate of a profile's ROV, or of the top level connection
+  // settings when `profile` is None. None as fingerprint trusts the next
+  // certificate seen again.
+  pub fn set_certificate_fingerprint(
+    &mut self,
+    profile: Option<&str>,
+    fingerprint: Option<String>,
+  ) -> Result<(), String> {
+    let active = match profile {
+      Some(name) => {
+        let index = self.rov_profile_index(name)?;
+        self.rov_profiles[index].certificate_fingerprint = fingerprint.clone();
+        self.active_rov_profile.as_deref() == Some(name)
+      }
+      None => {
+        if let Some(profile) = self.active_rov_profile_mut() {
+          profile.certificate_fingerprint = fingerprint.clone();
+        }
+        true
+      }
+    };
+    if active {
+      self.certificate_fingerprint = fingerprint;
+    }
+    Ok(())
+  }
+
+  fn rov_profile_index(&self, name: &str) -> Result<usize, String> {
+    self
+      .rov_profiles
+      .iter()
+      .position(|profile| profile.name == name)
+      .ok_or_else(|| format!("No ROV profile named {}", name))
+  }
+
+  fn check_rov_profile_name(&self, name: &str, renaming: Option<&str>) -> Result<(), String> {
+    if name.trim().is_empty() {
+      return Err("ROV profile name can not be empty".to_string());
+    }
+    if renaming != Some(name) && self.rov_profiles.iter().any(|profile| profile.name == name) {
+      return Err(format!("An ROV profile named {} already exists", name));
+    }
+    Ok(())
+  }
+}
+
+fn default_control_watchdog_timeout_ms() -> u64 {
+  500
+}
+
+fn default_direction_vector_rate_hz() -> u32 {
+  30
+}
+
+fn default_telemetry_ui_rate_hz() -> u32 {
+  30
+}
+
+fn default_telemetry_history_minutes() -> u32 {
+  10
+}
+
+// Overheating electronics, a failing sensor, a draining battery and the time
+// to head back up.
+fn default_alarm_rules() -> Vec<AlarmRule> {
+  let temperature = AlarmField::Reading(TelemetryField::ElectronicsTemperature);
+  let battery = AlarmField::Reading(TelemetryField::BatteryPercentage);
+  let failure = |name: &str, flag: HealthFlag| {
+    AlarmRule::new(
+      name,
+      AlarmField::Health(flag),
+      AlarmCondition::Below,
+      0.5,
+      AlarmSeverity::Critical,
+    )
+  };
+  vec![
+    AlarmRule {
+      duration_ms: 5_000,
+      hysteresis: 5.0,
+      ..AlarmRule::new(
+        "Electronics overheating",
+        temperature,
+        AlarmCondition::Above,
+        70.0,
+        AlarmSeverity::Critical,
+      )
+    },
+    AlarmRule {
+      duration_ms: 30_000,
+      hysteresis: 1.0,
+      ..AlarmRule::new(
+        "Electronics heating up",
+        temperature,
+        AlarmCondition::RisingFaster,
+        3.0,
+        AlarmSeverity::Warning,
+      )
+    },
+    failure("IMU failure", HealthFlag::ImuOk),
+    failure("Pressure sensor failure", HealthFlag::PressureSensorOk),
+    failure("Microcontroller failure", HealthFlag::MicrocontrollerOk),
+    AlarmRule {
+      hysteresis: 2.0,
+      ..AlarmRule::new(
+        "Battery low",
+        battery,
+        AlarmCondition::Below,
+        25.0,
+        AlarmSeverity::Warning,
+      )
+    },
+    AlarmRule {
+      hysteresis: 2.0,
+      ..AlarmRule::new(
+        "Battery critical",
+        battery,
+        AlarmCondition::Below,
+        10.0,
+        AlarmSeverity::Critical,
+      )
+    },
+    AlarmRule {
+      hysteresis: 1.0,
+      ..AlarmRule::new(
+        "Return to surface",
+        AlarmField::Battery(BatteryForecast::RemainingDiveMinutes),
+        AlarmCondition::Below,
+        0.0,
+        AlarmSeverity::Critical,
+      )
+    },
+  ]
+}
+
+fn default_battery_reserve_minutes() -> u32 {
+  2
+}
+
+fn default_ascent_speed() -> f32 {
+  0.3
+}
+
+fn default_capture_directory() -> String {
+  dirs::data_local_dir()
+    .unwrap_or_else(std::env::temp_dir)
+    .join("manafish")
+    .join("captures")
+    .to_string_lossy()
+    .into_owned()
+}
@@ -0,0 +1,49 @@
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, TS)]
+#[serde(rename_all = "camelCase")]
+pub enum ConnectionPhase {
+  Disconnected,
+  Connecting,
+  Handshaking,
+  Connected,
+  Degraded,
+  Reconnecting,
+}
+
+impl ConnectionPhase {
+  pub fn is_connected(self) -> bool {
+    matches!(self, ConnectionPhase::Connected | ConnectionPhase::Degraded)
+  }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct ConnectionState {
+  pub phase: ConnectionPhase,
+  pub reason: String,
+  pub attempt: u32,
+  #[ts(type = "number | null")]
+  pub retry_in_ms: Option<u64>,
+}
+
+impl Default for ConnectionState {
+  fn default() -> Self {
+    ConnectionState {
+      phase: ConnectionPhase::Disconnected,
+      reason: "Not started".to_string(),
+      attempt: 0,
+      retry_in_ms: None,
+    }
+  }
+}
+
+// Whether the ROV answers pings, with the last round trip time.
+#[derive(Serialize, Deserialize, Clone, Debug, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct ConnectionStatus {
+  pub is_connected: bool,
+  #[ts(type = "number | null")]
+  pub delay: Option<u64>,
+}
@@ -0,0 +1,32 @@
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+pub const DISCOVERY_PORT: u16 = 9001;
+
+// Datagrams on the discovery port. The app broadcasts `discover` and every ROV
+// on the network answers the sender with an `announce`.
+#[derive(Serialize, Deserialize, Debug, Clone, TS)]
+#[serde(tag = "type", content = "payload", rename_all = "camelCase")]
+pub enum DiscoveryMessage {
+  Discover,
+  Announce(Announcement),
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct Announcement {
+  pub name: String,
+  pub firmware_version: String,
+  pub web_socket_port: u16,
+  pub webrtc_signaling_api_port: u16,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct DiscoveredRov {
+  pub name: String,
+  pub ip_address: String,
+  pub firmware_version: String,
+  pub web_socket_port: u16,
+  pub webrtc_signaling_api_port: u16,
+}
@@ -0,0 +1,120 @@
+use super::log::LogEntry;
+use super::rov_config::RovConfig;
+use super::rov_status::RovStatus;
+use super::rov_telemetry::RovTelemetry;
+use super::vehicle::VehicleId;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use ts_rs::TS;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq, TS)]
+#[serde(rename_all = "camelCase")]
+pub enum DiveRecordingFormat {
+  // One row per frame with a column per reading, for spreadsheets.
+  #[default]
+  Csv,
+  // One `DiveRecord` per line, keeping every field the ROV sent.
+  Jsonl,
+}
+
+impl DiveRecordingFormat {
+  pub fn extension(&self) -> &'static str {
+    match self {
+      DiveRecordingFormat::Csv => "csv",
+      DiveRecordingFormat::Jsonl => "jsonl",
+    }
+  }
+}
+
+// A dive recording on disk, `recording` while frames are still written to it.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct DiveRecording {
+  pub path: String,
+  pub vehicle_id: VehicleId,
+  pub format: DiveRecordingFormat,
+  #[ts(type = "number")]
+  pub started_ms: u64,
+  #[ts(type = "number")]
+  pub bytes: u64,
+  pub recording: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, TS)]
+#[serde(tag = "kind", content = "data", rename_all = "camelCase")]
+pub enum DiveSample {
+  Telemetry(RovTelemetry),
+  Status(RovStatus),
+  Log(LogEntry),
+  // A message sent to the ROV, as it went over the wire.
+  Control(#[ts(type = "unknown")] Value),
+}
+
+// One line of a JSONL dive recording, `time_ms` since the Unix epoch.
+#[derive(Serialize, Deserialize, Clone, Debug, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct DiveRecord {
+  #[ts(type = "number")]
+  pub time_ms: u64,
+  #[serde(flatten)]
+  pub sample: DiveSample,
+}
+
+// A dive of one vehicle, from `start_dive_session` or connecting until it is
+// stopped. `summary` is filled in when it is.
+#[derive(Serialize, Deserialize, Clone, Debug, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct DiveSession {
+  pub id: String,
+  pub vehicle_id: VehicleId,
+  pub operator: String,
+  pub site: String,
+  pub firmware_version: Option<String>,
+  // The config the ROV last reported during the dive, or before it.
+  pub rov_config: Option<RovConfig>,
+  #[ts(type = "number")]
+  pub started_ms: u64,
+  #[ts(type = "number | null")]
+  pub ended_ms: Option<u64>,
+  // Videos saved with `save_recording` during the dive.
+  pub recordings: Vec<String>,
+  // Dive recordings started during the dive.
+  pub data_files: Vec<String>,
+  pub summary: Option<DiveSummary>,
+}
+
+// Readings are None when the ROV sent none during the dive.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct DiveSummary {
+  #[ts(type = "number")]
+  pub duration_ms: u64,
+  pub max_depth: Option<f32>,
+  pub average_depth: Option<f32>,
+  pub min_water_temperature: Option<f32>,
+  pub max_water_temperature: Option<f32>,
+  // Percentage points between the first and last status update.
+  pub battery_used: Option<u8>,
+  #[ts(type = "number")]
+  pub pitch_stabilization_ms: u64,
+  #[ts(type = "number")]
+  pub roll_stabilization_ms: u64,
+  #[ts(type = "number")]
+  pub depth_hold_ms: u64,
+}
+
+// Where a dive replay is, `position_ms` counting from the first record.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct DiveReplayState {
+  pub path: String,
+  pub vehicle_id: VehicleId,
+  pub speed: f64,
+  pub paused: bool,
+  #[ts(type = "number")]
+  pub position_ms: u64,
+  #[ts(type = "number")]
+  pub duration_ms: u64,
+  // Every record was replayed. Seeking back plays on from there.
+  pub finished: bool,
+}
@@ -1,6 +1,7 @@
 use serde::Serialize;
+use ts_rs::TS;
 
-#[derive(Serialize, Clone)]
+#[derive(Serialize, Clone, TS)]
 #[serde(rename_all = "camelCase")]
 pub enum GamepadEventType {
   Connected,
@@ -12,7 +13,7 @@ pub enum GamepadEventType {
   Dropped,
 }
 
-#[derive(Serialize, Clone)]
+#[derive(Serialize, Clone, TS)]
 #[serde(rename_all = "camelCase")]
 pub struct GamepadData {
   pub id: usize,
@@ -20,6 +21,7 @@ pub struct GamepadData {
   pub connected: bool,
   pub vibration: bool,
   pub event: GamepadEventType,
+  #[ts(type = "number")]
   pub timestamp: u128,
   pub name: String,
   pub buttons: Vec<f32>,
@@ -0,0 +1,103 @@
+use crate::models::unknown::UnknownFields;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+pub const PROTOCOL_VERSION: u32 = 1;
+pub const MIN_PROTOCOL_VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, JsonSchema, TS)]
+#[serde(rename_all = "camelCase")]
+pub enum Capability {
+  PitchStabilization,
+  RollStabilization,
+  DepthHold,
+  Dshot,
+  ThrusterTest,
+  RegulatorAutoTuning,
+  FirmwareFlashing,
+  // Capabilities added by newer firmware that this app does not know about.
+  #[serde(other)]
+  Unknown,
+}
+
+impl Capability {
+  pub const ALL: [Capability; 7] = [
+    Capability::PitchStabilization,
+    Capability::RollStabilization,
+    Capability::DepthHold,
+    Capability::Dshot,
+    Capability::ThrusterTest,
+    Capability::RegulatorAutoTuning,
+    Capability::FirmwareFlashing,
+  ];
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, JsonSchema, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct Hello {
+  pub protocol_version: u32,
+  pub min_protocol_version: u32,
+  pub software_version: String,
+  #[serde(default)]
+  pub capabilities: Vec<Capability>,
+  #[serde(flatten)]
+  #[schemars(skip)]
+  #[ts(skip)]
+  pub unknown_fields: UnknownFields,
+}
+
+impl Hello {
+  pub fn app() -> Self {
+    Hello {
+      protocol_version: PROTOCOL_VERSION,
+      min_protocol_version: MIN_PROTOCOL_VERSION,
+      software_version: env!("CARGO_PKG_VERSION").to_string(),
+      capabilities: Vec::new(),
+      unknown_fields: UnknownFields::default(),
+    }
+  }
+}
+
+// `protocol_version` is None when the ROV firmware predates the handshake, in
+// which case every capability is assumed like before.
+#[derive(Serialize, Deserialize, Clone, Debug, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct Handshake {
+  pub protocol_version: Option<u32>,
+  pub firmware_version: Option<String>,
+  pub capabilities: Vec<Capability>,
+}
+
+impl Handshake {
+  pub fn negotiate(rov: Hello) -> Result<Self, String> {
+    if rov.protocol_version < MIN_PROTOCOL_VERSION || PROTOCOL_VERSION < rov.min_protocol_version {
+      return Err(format!(
+        "ROV protocol version {} (accepts {}+) is incompatible with app version {} (accepts {}+)",
+        rov.protocol_version, rov.min_protocol_version, PROTOCOL_VERSION, MIN_PROTOCOL_VERSION
+      ));
+    }
+
+    Ok(Handshake {
+      protocol_version: Some(rov.protocol_version.min(PROTOCOL_VERSION)),
+      firmware_version: Some(rov.software_version),
+      capabilities: rov
+        .capabilities
+        .into_iter()
+        .filter(|capability| *capability != Capability::Unknown)
+        .collect(),
+    })
+  }
+
+  pub fn legacy() -> Self {
+    Handshake {
+      protocol_version: None,
+      firmware_version: None,
+      capabilities: Capability::ALL.to_vec(),
+    }
+  }
+
+  pub fn supports(&self, capability: Capability) -> bool {
+    self.capabilities.contains(&capability)
+  }
+}
@@ -0,0 +1,31 @@
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+// Counts the round trip times below `up_to_ms`, the last bucket has no upper
+// bound.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct RttBucket {
+  #[ts(type = "number | null")]
+  pub up_to_ms: Option<u64>,
+  pub count: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct LinkQuality {
+  pub rtt_histogram: Vec<RttBucket>,
+  #[ts(type = "number | null")]
+  pub rtt_last_ms: Option<u64>,
+  #[ts(type = "number | null")]
+  pub rtt_min_ms: Option<u64>,
+  pub rtt_mean_ms: Option<f64>,
+  #[ts(type = "number | null")]
+  pub rtt_max_ms: Option<u64>,
+  pub jitter_ms: Option<f64>,
+  pub missed_pong_ratio: f64,
+  pub telemetry_rate: f64,
+  pub bytes_in_per_second: f64,
+  pub bytes_out_per_second: f64,
+  pub reconnect_count: u32,
+}
@@ -0,0 +1,32 @@
+use crate::models::unknown::UnknownFields;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+#[derive(Serialize, Deserialize, Clone, Debug, JsonSchema, TS)]
+#[serde(rename_all = "camelCase")]
+pub enum LogLevel {
+  Info,
+  Warn,
+  Error,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, JsonSchema, TS)]
+#[serde(rename_all = "camelCase")]
+pub enum LogOrigin {
+  Firmware,
+  Backend,
+  Frontend,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug, JsonSchema, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct LogEntry {
+  pub level: LogLevel,
+  pub origin: LogOrigin,
+  pub message: String,
+  #[serde(flatten)]
+  #[schemars(skip)]
+  #[ts(skip)]
+  pub unknown_fields: UnknownFields,
+}
@@ -0,0 +1,34 @@
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+// Lower variants are sent first.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, TS)]
+#[serde(rename_all = "camelCase")]
+pub enum OutboundPriority {
+  Emergency,
+  Control,
+  Configuration,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, TS)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum DeliveryPolicy {
+  DropWhenOffline,
+  HoldUntilReconnect,
+  ExpireAfter {
+    #[ts(type = "number")]
+    seconds: u64,
+  },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct QueuedMessage {
+  #[ts(type = "number")]
+  pub id: u64,
+  pub message_type: String,
+  pub priority: OutboundPriority,
+  pub policy: DeliveryPolicy,
+  #[ts(type = "number")]
+  pub queued_for_ms: u64,
+}
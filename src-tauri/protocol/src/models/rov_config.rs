@@ -1,46 +1,61 @@
+use crate::models::unknown::UnknownFields;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use ts_rs::TS;
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, JsonSchema, TS)]
 #[serde(rename_all = "camelCase")]
 pub enum MicrocontrollerFirmwareVariant {
   Pwm,
   Dshot,
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, TS)]
 #[serde(rename_all = "camelCase")]
 pub enum FluidType {
   Saltwater,
   Freshwater,
 }
 
-#[derive(Deserialize, Serialize, Clone, Debug)]
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq, JsonSchema, TS)]
 #[serde(rename_all = "camelCase")]
 pub struct ThrusterPinSetup {
   pub identifiers: [u8; 8],
   pub spin_directions: [i8; 8],
+  #[serde(flatten)]
+  #[schemars(skip)]
+  #[ts(skip)]
+  pub unknown_fields: UnknownFields,
 }
 
 pub type ThrusterAllocation = [[f32; 8]; 8];
 
-#[derive(Deserialize, Serialize, Clone, Debug)]
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq, JsonSchema, TS)]
 #[serde(rename_all = "camelCase")]
 pub struct Pid {
   pub kp: f32,
   pub ki: f32,
   pub kd: f32,
+  #[serde(flatten)]
+  #[schemars(skip)]
+  #[ts(skip)]
+  pub unknown_fields: UnknownFields,
 }
 
-#[derive(Deserialize, Serialize, Clone, Debug)]
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq, JsonSchema, TS)]
 #[serde(rename_all = "camelCase")]
 pub struct Regulator {
   pub turn_speed: u16,
   pub pitch: Pid,
   pub roll: Pid,
   pub depth: Pid,
+  #[serde(flatten)]
+  #[schemars(skip)]
+  #[ts(skip)]
+  pub unknown_fields: UnknownFields,
 }
 
-#[derive(Deserialize, Serialize, Clone, Debug)]
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq, JsonSchema, TS)]
 #[serde(rename_all = "camelCase")]
 pub struct DirectionCoefficients {
   pub surge: f32,
@@ -49,18 +64,26 @@ pub struct DirectionCoefficients {
   pub pitch: f32,
   pub yaw: f32,
   pub roll: f32,
+  #[serde(flatten)]
+  #[schemars(skip)]
+  #[ts(skip)]
+  pub unknown_fields: UnknownFields,
 }
 
-#[derive(Deserialize, Serialize, Clone, Debug)]
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq, JsonSchema, TS)]
 #[serde(rename_all = "camelCase")]
 pub struct Power {
   pub user_max_power: f32,
   pub regulator_max_power: f32,
   pub battery_min_voltage: f32,
   pub battery_max_voltage: f32,
+  #[serde(flatten)]
+  #[schemars(skip)]
+  #[ts(skip)]
+  pub unknown_fields: UnknownFields,
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, TS)]
 #[serde(rename_all = "camelCase")]
 pub struct RovConfig {
   pub microcontroller_firmware_variant: MicrocontrollerFirmwareVariant,
@@ -70,16 +93,24 @@ pub struct RovConfig {
   pub regulator: Regulator,
   pub direction_coefficients: DirectionCoefficients,
   pub power: Power,
+  #[serde(flatten)]
+  #[schemars(skip)]
+  #[ts(skip)]
+  pub unknown_fields: UnknownFields,
 }
 
 pub type ThrusterTest = u8;
 
 pub type FirmwareVersion = String;
 
-#[derive(Deserialize, Serialize, Clone, Debug)]
+#[derive(Deserialize, Serialize, Clone, Debug, JsonSchema, TS)]
 #[serde(rename_all = "camelCase")]
 pub struct RegulatorSuggestions {
   pub pitch: Pid,
   pub roll: Pid,
   pub depth: Pid,
+  #[serde(flatten)]
+  #[schemars(skip)]
+  #[ts(skip)]
+  pub unknown_fields: UnknownFields,
 }
@@ -0,0 +1,30 @@
+use crate::models::unknown::UnknownFields;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+#[derive(Deserialize, Serialize, Clone, Debug, JsonSchema, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct SystemHealth {
+  pub imu_ok: bool,
+  pub pressure_sensor_ok: bool,
+  pub microcontroller_ok: bool,
+  #[serde(flatten)]
+  #[schemars(skip)]
+  #[ts(skip)]
+  pub unknown_fields: UnknownFields,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug, JsonSchema, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct RovStatus {
+  pub pitch_stabilization: bool,
+  pub roll_stabilization: bool,
+  pub depth_hold: bool,
+  pub battery_percentage: u8,
+  pub health: SystemHealth,
+  #[serde(flatten)]
+  #[schemars(skip)]
+  #[ts(skip)]
+  pub unknown_fields: UnknownFields,
+}
@@ -0,0 +1,31 @@
+use crate::models::unknown::UnknownFields;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+// Temperatures older firmware doesn't report are absent rather than zero, which
+// alarms and statistics would take for a real reading. A frame without one of
+// the other readings fails to parse.
+#[derive(Deserialize, Serialize, Clone, Debug, Default, JsonSchema, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct RovTelemetry {
+  pub pitch: f32,
+  pub roll: f32,
+  pub desired_pitch: f32,
+  pub desired_roll: f32,
+  pub depth: f32,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  #[ts(optional)]
+  pub water_temperature: Option<f32>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  #[ts(optional)]
+  pub electronics_temperature: Option<f32>,
+  pub thruster_rpms: [f32; 8],
+  // Older firmware has no work indicator, which reads as idle.
+  #[serde(default)]
+  pub work_indicator_percentage: u8,
+  #[serde(flatten)]
+  #[schemars(skip)]
+  #[ts(skip)]
+  pub unknown_fields: UnknownFields,
+}
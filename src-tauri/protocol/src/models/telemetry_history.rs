@@ -0,0 +1,62 @@
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+// The readings the telemetry history keeps, each one a series of its own.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, TS)]
+#[serde(rename_all = "camelCase")]
+pub enum TelemetryField {
+  Pitch,
+  Roll,
+  DesiredPitch,
+  DesiredRoll,
+  Depth,
+  WaterTemperature,
+  ElectronicsTemperature,
+  ThrusterRpm1,
+  ThrusterRpm2,
+  ThrusterRpm3,
+  ThrusterRpm4,
+  ThrusterRpm5,
+  ThrusterRpm6,
+  ThrusterRpm7,
+  ThrusterRpm8,
+  WorkIndicatorPercentage,
+  // From the status updates, the others are from the telemetry.
+  BatteryPercentage,
+}
+
+impl TelemetryField {
+  pub const THRUSTER_RPMS: [TelemetryField; 8] = [
+    TelemetryField::ThrusterRpm1,
+    TelemetryField::ThrusterRpm2,
+    TelemetryField::ThrusterRpm3,
+    TelemetryField::ThrusterRpm4,
+    TelemetryField::ThrusterRpm5,
+    TelemetryField::ThrusterRpm6,
+    TelemetryField::ThrusterRpm7,
+    TelemetryField::ThrusterRpm8,
+  ];
+}
+
+// The readings of a field within one time bucket of a query, `from_ms` and
+// `to_ms` being the first and last of them.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct TelemetryBucket {
+  #[ts(type = "number")]
+  pub from_ms: u64,
+  #[ts(type = "number")]
+  pub to_ms: u64,
+  pub min: f32,
+  pub max: f32,
+  pub mean: f32,
+  pub count: u32,
+}
+
+// Buckets without readings are left out.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct TelemetrySeries {
+  pub field: TelemetryField,
+  pub buckets: Vec<TelemetryBucket>,
+}
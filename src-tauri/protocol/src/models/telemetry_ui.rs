@@ -0,0 +1,31 @@
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+// What the frontend gets of the telemetry arriving within one interval of
+// `telemetry_ui_rate_hz`.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq, TS)]
+#[serde(rename_all = "camelCase")]
+pub enum TelemetryUiMode {
+  // The latest frame.
+  #[default]
+  Latest,
+  // The mean of the frames.
+  Average,
+  // Every frame, in one `rov_telemetry_batch` event.
+  Batch,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct TelemetryUiStats {
+  // Frames received from the ROV.
+  #[ts(type = "number")]
+  pub received: u64,
+  // Telemetry events emitted to the frontend.
+  #[ts(type = "number")]
+  pub emitted: u64,
+  // Frames the frontend didn't get on their own, replaced by a later frame or
+  // averaged into one.
+  #[ts(type = "number")]
+  pub dropped: u64,
+}
@@ -1,7 +1,10 @@
-use serde::{Deserialize, Serialize};
 use crate::models::rov_config::ThrusterTest;
+use crate::models::unknown::UnknownFields;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, JsonSchema, TS)]
 #[serde(rename_all = "camelCase")]
 pub enum ToastType {
   Success,
@@ -11,14 +14,14 @@ pub enum ToastType {
   Loading,
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, JsonSchema, TS)]
 #[serde(tag = "type", content = "payload", rename_all = "camelCase")]
 pub enum ToastCancel {
   CancelThrusterTest(ThrusterTest),
   CancelRegulatorAutoTuning,
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, JsonSchema, TS)]
 #[serde(rename_all = "camelCase")]
 pub struct Toast {
   pub id: Option<String>,
@@ -26,4 +29,8 @@ pub struct Toast {
   pub message: String,
   pub description: Option<String>,
   pub cancel: Option<ToastCancel>,
+  #[serde(flatten)]
+  #[schemars(skip)]
+  #[ts(skip)]
+  pub unknown_fields: UnknownFields,
 }
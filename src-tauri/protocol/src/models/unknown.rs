@@ -0,0 +1,18 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use ts_rs::TS;
+
+// Fields of a payload that this app doesn't know, sent by newer firmware. They
+// are kept so a payload passed back to the ROV, like its config, arrives with
+// them intact.
+pub type UnknownFields = BTreeMap<String, Value>;
+
+// A message of a type this app doesn't know, kept as it arrived.
+#[derive(Serialize, Deserialize, Clone, Debug, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct UnknownMessage {
+  pub message_type: String,
+  #[ts(type = "unknown")]
+  pub raw: Value,
+}
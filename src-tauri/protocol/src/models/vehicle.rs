@@ -0,0 +1,41 @@
+use super::connection::ConnectionState;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+pub type VehicleId = String;
+
+// Every event about a ROV carries the vehicle it came from. `vehicle_id` is
+// None for events raised by the app itself, `pilot` tells whether the vehicle
+// is the one receiving pilot input and `replay` whether the event comes from a
+// dive replay rather than the vehicle.
+#[derive(Serialize, Deserialize, Clone, Debug, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct VehicleEvent<T> {
+  pub vehicle_id: Option<VehicleId>,
+  pub pilot: bool,
+  #[serde(default)]
+  pub replay: bool,
+  pub payload: T,
+}
+
+impl<T> VehicleEvent<T> {
+  pub fn app(payload: T) -> Self {
+    VehicleEvent {
+      vehicle_id: None,
+      pilot: false,
+      replay: false,
+      payload,
+    }
+  }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct VehicleInfo {
+  pub id: VehicleId,
+  pub profile: Option<String>,
+  pub ip_address: String,
+  pub web_socket_port: u16,
+  pub pilot: bool,
+  pub connection: ConnectionState,
+}
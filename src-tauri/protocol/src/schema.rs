@@ -0,0 +1,166 @@
+use crate::message::WebsocketEnvelope;
+use jsonschema::Validator;
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+// Where the checked-in schema bundle lives, relative to this crate.
+pub const PROTOCOL_SCHEMA_PATH: &str =
+  concat!(env!("CARGO_MANIFEST_DIR"), "/../../docs/protocol-schema");
+
+// The protocol as JSON Schema, generated from `WebsocketEnvelope` so it can't
+// drift from what the app sends and accepts.
+pub fn protocol_schema() -> Value {
+  schemars::schema_for!(WebsocketEnvelope).to_value()
+}
+
+// The files written by `write_schema_bundle`: the whole protocol, one schema
+// per message type and one per payload model, each usable on its own.
+pub fn schema_bundle() -> Vec<(String, Value)> {
+  let protocol = protocol_schema();
+  let definitions = protocol["$defs"].as_object().cloned().unwrap_or_default();
+  let mut bundle = vec![("protocol.schema.json".to_string(), protocol.clone())];
+
+  for variant in protocol["oneOf"].as_array().into_iter().flatten() {
+    let Some(name) = variant["properties"]["type"]["const"].as_str() else {
+      continue;
+    };
+    let mut schema = json!({
+      "$schema": protocol["$schema"],
+      "title": name,
+      "type": "object",
+      "properties": { "id": protocol["properties"]["id"] },
+    });
+    for (key, value) in variant.as_object().into_iter().flatten() {
+      match (key.as_str(), value) {
+        ("properties", Value::Object(properties)) => {
+          if let Some(all) = schema["properties"].as_object_mut() {
+            all.extend(properties.clone());
+          }
+        }
+        _ => schema[key] = value.clone(),
+      }
+    }
+    with_definitions(&mut schema, &definitions);
+    bundle.push((format!("messages/{}.schema.json", name), schema));
+  }
+
+  for (name, definition) in &definitions {
+    let mut schema = json!({ "$schema": protocol["$schema"], "title": name });
+    for (key, value) in definition.as_object().into_iter().flatten() {
+      schema[key] = value.clone();
+    }
+    with_definitions(&mut schema, &definitions);
+    bundle.push((format!("models/{}.schema.json", name), schema));
+  }
+
+  bundle
+}
+
+pub fn write_schema_bundle(directory: &Path) -> io::Result<()> {
+  for (name, schema) in schema_bundle() {
+    let path = directory.join(name);
+    if let Some(parent) = path.parent() {
+      fs::create_dir_all(parent)?;
+    }
+    let text =
+      serde_json::to_string_pretty(&schema).map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+    fs::write(path, text + "\n")?;
+  }
+  Ok(())
+}
+
+// Only the definitions the schema refers to, directly or through each other.
+fn with_definitions(schema: &mut Value, definitions: &Map<String, Value>) {
+  let mut used = Map::new();
+  let mut pending = references(schema);
+  while let Some(name) = pending.pop() {
+    if used.contains_key(&name) {
+      continue;
+    }
+    if let Some(definition) = definitions.get(&name) {
+      pending.extend(references(definition));
+      used.insert(name, definition.clone());
+    }
+  }
+  if !used.is_empty() {
+    used.sort_keys();
+    schema["$defs"] = Value::Object(used);
+  }
+}
+
+fn references(value: &Value) -> Vec<String> {
+  match value {
+    Value::Object(object) => object
+      .iter()
+      .flat_map(|(key, value)| match (key.as_str(), value) {
+        ("$ref", Value::String(reference)) => reference
+          .strip_prefix("#/$defs/")
+          .map(|name| vec![name.to_string()])
+          .unwrap_or_default(),
+        _ => references(value),
+      })
+      .collect(),
+    Value::Array(values) => values.iter().flat_map(references).collect(),
+    _ => Vec::new(),
+  }
+}
+
+// Checks websocket frames against the protocol schema, used by the mock ROV
+// and the tests to catch either side drifting from it. Each frame is checked
+// against the schema of its own message type, which gives clearer errors than
+// the `oneOf` of the full protocol.
+pub struct ProtocolValidator {
+  messages: HashMap<String, Validator>,
+}
+
+impl ProtocolValidator {
+  pub fn new() -> Self {
+    let messages = schema_bundle()
+      .into_iter()
+      .filter_map(|(name, schema)| {
+        let name = name
+          .strip_prefix("messages/")?
+          .strip_suffix(".schema.json")?;
+        let validator = jsonschema::validator_for(&schema).expect("invalid message schema");
+        Some((name.to_string(), validator))
+      })
+      .collect();
+    ProtocolValidator { messages }
+  }
+
+  pub fn validate(&self, text: &str) -> Result<(), String> {
+    let message: Value =
+      serde_json::from_str(text).map_err(|e| format!("Message is not JSON: {}", e))?;
+    let Some(kind) = message["type"].as_str() else {
+      return Err(format!("Message {} has no type", text));
+    };
+    let Some(validator) = self.messages.get(kind) else {
+      return Err(format!("Message {} has unknown type {}", text, kind));
+    };
+
+    let errors: Vec<String> = validator
+      .iter_errors(&message)
+      .map(|error| match error.instance_path().to_string() {
+        path if path.is_empty() => error.to_string(),
+        path => format!("{}: {}", path, error),
+      })
+      .collect();
+    if errors.is_empty() {
+      return Ok(());
+    }
+    Err(format!(
+      "Message {} violates the protocol: {}",
+      text,
+      errors.join(", ")
+    ))
+  }
+}
+
+impl Default for ProtocolValidator {
+  fn default() -> Self {
+    Self::new()
+  }
+}
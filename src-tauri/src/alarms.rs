@@ -0,0 +1,424 @@
+use crate::events::{ALARM_CHANGED, LOG_MESSAGE, SHOW_TOAST};
+use crate::log_warn;
+use crate::models::alarm::{
+  Alarm, AlarmCondition, AlarmField, AlarmRule, AlarmSeverity, HealthFlag,
+};
+use crate::models::config::Config;
+use crate::models::log::{LogEntry, LogLevel, LogOrigin};
+use crate::models::rov_status::RovStatus;
+use crate::models::telemetry_history::TelemetryField;
+use crate::models::toast::{Toast, ToastType};
+use crate::models::unknown::UnknownFields;
+use crate::models::vehicle::VehicleId;
+use crate::util::{lock, now_ms};
+use crate::websocket::handler::{HandlerRegistry, HandlerResult, MessageHandler, FRONTEND_ORDER};
+use crate::websocket::message::{WebsocketEnvelope, WebsocketMessage};
+use crate::websocket::telemetry_history::telemetry_readings;
+use crate::websocket::vehicle::VehicleEmitter;
+use serde::Serialize;
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Manager, Runtime};
+
+// Where a rule stands for one vehicle.
+#[derive(Default)]
+struct RuleState {
+  // The readings within the rate window, for rules on the rate of change.
+  readings: VecDeque<(u64, f32)>,
+  // Since when the condition holds, until the alarm is raised.
+  holds_since: Option<u64>,
+  // The alarm raised, until its condition clears.
+  raised: Option<u64>,
+}
+
+impl RuleState {
+  // The reading, or its change per minute for rate rules. That isn't known
+  // before the readings span half the rate window.
+  fn value(&mut self, rule: &AlarmRule, time_ms: u64, reading: f32) -> Option<f32> {
+    let falling = match rule.condition {
+      AlarmCondition::Above | AlarmCondition::Below => return Some(reading),
+      AlarmCondition::RisingFaster => false,
+      AlarmCondition::FallingFaster => true,
+    };
+    self.readings.push_back((time_ms, reading));
+    while self
+      .readings
+      .front()
+      .is_some_and(|(first_ms, _)| time_ms.saturating_sub(*first_ms) > rule.rate_window_ms)
+    {
+      self.readings.pop_front();
+    }
+    let (first_ms, first) = *self.readings.front()?;
+    let span_ms = time_ms.saturating_sub(first_ms);
+    if span_ms == 0 || span_ms < rule.rate_window_ms / 2 {
+      return None;
+    }
+    let per_minute = (reading - first) * 60_000.0 / span_ms as f32;
+    Some(if falling { -per_minute } else { per_minute })
+  }
+}
+
+enum Change {
+  Raised(Alarm),
+  Cleared(Alarm),
+}
+
+#[derive(Default)]
+struct Alarms {
+  rules: Vec<AlarmRule>,
+  // By vehicle, then by rule name.
+  states: BTreeMap<VehicleId, BTreeMap<String, RuleState>>,
+  listed: BTreeMap<u64, Alarm>,
+  last_id: u64,
+}
+
+impl Alarms {
+  // Clears the alarm, dropping it from the list once it is acknowledged too.
+  fn clear(&mut self, id: u64, time_ms: u64) -> Option<Alarm> {
+    let alarm = self.listed.get_mut(&id)?;
+    alarm.cleared_ms = Some(time_ms);
+    let alarm = alarm.clone();
+    if alarm.acknowledged_ms.is_some() {
+      self.listed.remove(&id);
+    }
+    Some(alarm)
+  }
+}
+
+// Checks the alarm rules of the config against every telemetry and status
+// frame, and keeps the alarms they raise until the operator acknowledged them.
+// Raising or clearing one is toasted, logged and emitted as `alarm_changed`.
+pub struct AlarmEngine {
+  alarms: Mutex<Alarms>,
+}
+
+impl Default for AlarmEngine {
+  fn default() -> Self {
+    let engine = AlarmEngine {
+      alarms: Mutex::new(Alarms::default()),
+    };
+    engine.configure(&Config::default());
+    engine
+  }
+}
+
+impl AlarmEngine {
+  // Rules that changed start over, clearing the alarms they raised, which are
+  // returned for announcing. Only the first of rules with the same name is
+  // checked.
+  pub fn configure(&self, config: &Config) -> Vec<Alarm> {
+    let mut rules: Vec<AlarmRule> = Vec::new();
+    for rule in &config.alarm_rules {
+      if rules.iter().any(|other| other.name == rule.name) {
+        log_warn!(
+          "Ignoring alarm rule {}, another one has its name",
+          rule.name
+        );
+      } else {
+        rules.push(rule.clone());
+      }
+    }
+
+    let mut alarms = lock(&self.alarms);
+    if alarms.rules == rules {
+      return Vec::new();
+    }
+    let changed: Vec<String> = alarms
+      .rules
+      .iter()
+      .filter(|rule| !rules.contains(rule))
+      .map(|rule| rule.name.clone())
+      .collect();
+    let mut raised = Vec::new();
+    for states in alarms.states.values_mut() {
+      for name in &changed {
+        raised.extend(states.remove(name).and_then(|state| state.raised));
+      }
+    }
+    let time_ms = now_ms();
+    let cleared = raised
+      .into_iter()
+      .filter_map(|id| alarms.clear(id, time_ms))
+      .collect();
+    alarms.rules = rules;
+    cleared
+  }
+
+  pub fn evaluate<R: Runtime>(&self, vehicle: &VehicleEmitter<R>, readings: &[(AlarmField, f32)]) {
+    let time_ms = now_ms();
+    let mut changes = Vec::new();
+    {
+      let mut alarms = lock(&self.alarms);
+      let Alarms {
+        rules,
+        states,
+        listed,
+        last_id,
+      } = &mut *alarms;
+      let states = states.entry(vehicle.id.clone()).or_default();
+      let mut cleared = Vec::new();
+      for rule in rules.iter() {
+        let Some(&(_, reading)) = readings.iter().find(|(field, _)| *field == rule.field) else {
+          continue;
+        };
+        let state = states.entry(rule.name.clone()).or_default();
+        let Some(value) = state.value(rule, time_ms, reading) else {
+          continue;
+        };
+        match state.raised {
+          Some(id) if clears(rule, value) => {
+            state.raised = None;
+            cleared.push(id);
+          }
+          Some(_) => {}
+          None if holds(rule, value) => {
+            let since_ms = *state.holds_since.get_or_insert(time_ms);
+            if time_ms.saturating_sub(since_ms) < rule.duration_ms {
+              continue;
+            }
+            state.holds_since = None;
+            let message = describe(rule, value);
+            let alarm = match listed
+              .values_mut()
+              .find(|alarm| alarm.vehicle_id == vehicle.id && alarm.rule == rule.name)
+            {
+              Some(alarm) => {
+                alarm.severity = rule.severity;
+                alarm.message = message;
+                alarm.value = value;
+                alarm.raised_ms = time_ms;
+                alarm.cleared_ms = None;
+                alarm.acknowledged_ms = None;
+                alarm.clone()
+              }
+              None => {
+                *last_id += 1;
+                let alarm = Alarm {
+                  id: *last_id,
+                  vehicle_id: vehicle.id.clone(),
+                  rule: rule.name.clone(),
+                  severity: rule.severity,
+                  message,
+                  value,
+                  raised_ms: time_ms,
+                  cleared_ms: None,
+                  acknowledged_ms: None,
+                };
+                listed.insert(alarm.id, alarm.clone());
+                alarm
+              }
+            };
+            state.raised = Some(alarm.id);
+            changes.push(Change::Raised(alarm));
+          }
+          None => state.holds_since = None,
+        }
+      }
+      for id in cleared {
+        changes.extend(alarms.clear(id, time_ms).map(Change::Cleared));
+      }
+    }
+    for change in changes {
+      announce(vehicle, change);
+    }
+  }
+
+  pub fn list(&self) -> Vec<Alarm> {
+    lock(&self.alarms).listed.values().cloned().collect()
+  }
+
+  // Acknowledging a cleared alarm drops it from the list.
+  pub fn acknowledge(&self, id: u64) -> Result<Alarm, String> {
+    let mut alarms = lock(&self.alarms);
+    let alarm = alarms
+      .listed
+      .get_mut(&id)
+      .ok_or_else(|| format!("No alarm {}", id))?;
+    alarm.acknowledged_ms.get_or_insert_with(now_ms);
+    let alarm = alarm.clone();
+    if alarm.cleared_ms.is_some() {
+      alarms.listed.remove(&id);
+    }
+    Ok(alarm)
+  }
+
+  // The alarms of a closed vehicle are cleared, it can't clear them anymore.
+  pub fn remove(&self, vehicle: &str) {
+    let mut alarms = lock(&self.alarms);
+    let raised: Vec<_> = alarms
+      .states
+      .remove(vehicle)
+      .into_iter()
+      .flat_map(BTreeMap::into_values)
+      .filter_map(|state| state.raised)
+      .collect();
+    let time_ms = now_ms();
+    for id in raised {
+      alarms.clear(id, time_ms);
+    }
+  }
+}
+
+fn holds(rule: &AlarmRule, value: f32) -> bool {
+  match rule.condition {
+    AlarmCondition::Below => value < rule.threshold,
+    _ => value > rule.threshold,
+  }
+}
+
+fn clears(rule: &AlarmRule, value: f32) -> bool {
+  match rule.condition {
+    AlarmCondition::Below => value > rule.threshold + rule.hysteresis,
+    _ => value < rule.threshold - rule.hysteresis,
+  }
+}
+
+fn describe(rule: &AlarmRule, value: f32) -> String {
+  let field = name(&rule.field);
+  match (rule.field, rule.condition) {
+    (AlarmField::Health(_), _) if value < 0.5 => format!("{} is false", field),
+    (_, AlarmCondition::Above) => format!("{} at {:.1}, above {}", field, value, rule.threshold),
+    (_, AlarmCondition::Below) => format!("{} at {:.1}, below {}", field, value, rule.threshold),
+    (_, AlarmCondition::RisingFaster) => format!(
+      "{} rising {:.1} a minute, faster than {}",
+      field, value, rule.threshold
+    ),
+    (_, AlarmCondition::FallingFaster) => format!(
+      "{} falling {:.1} a minute, faster than {}",
+      field, value, rule.threshold
+    ),
+  }
+}
+
+pub fn announce_acknowledged<R: Runtime>(vehicle: &VehicleEmitter<R>, alarm: Alarm) {
+  emit(vehicle, ALARM_CHANGED, alarm);
+}
+
+fn announce<R: Runtime>(vehicle: &VehicleEmitter<R>, change: Change) {
+  let (alarm, level, toast_type, message) = match &change {
+    Change::Raised(alarm) => {
+      let (level, toast_type) = match alarm.severity {
+        AlarmSeverity::Info => (LogLevel::Info, ToastType::Info),
+        AlarmSeverity::Warning => (LogLevel::Warn, ToastType::Warn),
+        AlarmSeverity::Critical => (LogLevel::Error, ToastType::Error),
+      };
+      (alarm, level, toast_type, format!("Alarm: {}", alarm.rule))
+    }
+    Change::Cleared(alarm) => (
+      alarm,
+      LogLevel::Info,
+      ToastType::Success,
+      format!("Alarm cleared: {}", alarm.rule),
+    ),
+  };
+  let entry = LogEntry {
+    level,
+    origin: LogOrigin::Backend,
+    message: format!("{} ({})", message, alarm.message),
+    unknown_fields: UnknownFields::default(),
+  };
+  // The same toast id, for the clearing to replace the raising.
+  let toast = Toast {
+    id: Some(format!("alarm_{}", alarm.id)),
+    toast_type: Some(toast_type),
+    message,
+    description: Some(alarm.message.clone()),
+    cancel: None,
+    unknown_fields: UnknownFields::default(),
+  };
+  emit(vehicle, LOG_MESSAGE, entry);
+  emit(vehicle, SHOW_TOAST, toast);
+  emit(vehicle, ALARM_CHANGED, alarm);
+}
+
+fn emit<R: Runtime, S: Serialize + Clone>(vehicle: &VehicleEmitter<R>, event: &str, payload: S) {
+  if let Err(e) = vehicle.emit(event, payload) {
+    log_warn!("Failed to emit {}: {}", event, e);
+  }
+}
+
+fn status_readings(status: &RovStatus) -> Vec<(AlarmField, f32)> {
+  let flag = |ok: bool| if ok { 1.0 } else { 0.0 };
+  vec![
+    (
+      AlarmField::Reading(TelemetryField::BatteryPercentage),
+      f32::from(status.battery_percentage),
+    ),
+    (
+      AlarmField::Health(HealthFlag::ImuOk),
+      flag(status.health.imu_ok),
+    ),
+    (
+      AlarmField::Health(HealthFlag::PressureSensorOk),
+      flag(status.health.pressure_sensor_ok),
+    ),
+    (
+      AlarmField::Health(HealthFlag::MicrocontrollerOk),
+      flag(status.health.microcontroller_ok),
+    ),
+  ]
+}
+
+pub struct AlarmHandler {
+  pub engine: Arc<AlarmEngine>,
+}
+
+impl<R: Runtime> MessageHandler<R> for AlarmHandler {
+  fn subscribes_to(&self, message: &WebsocketMessage) -> bool {
+    matches!(
+      message,
+      WebsocketMessage::Telemetry(_) | WebsocketMessage::StatusUpdate(_)
+    )
+  }
+
+  fn handle(&self, vehicle: &VehicleEmitter<R>, envelope: &WebsocketEnvelope) -> HandlerResult {
+    let readings: Vec<_> = match &envelope.message {
+      WebsocketMessage::Telemetry(telemetry) => telemetry_readings(telemetry)
+        .into_iter()
+        .map(|(field, value)| (AlarmField::Reading(field), value))
+        .collect(),
+      WebsocketMessage::StatusUpdate(status) => status_readings(status),
+      _ => return Ok(Vec::new()),
+    };
+    self.engine.evaluate(vehicle, &readings);
+    Ok(Vec::new())
+  }
+
+  fn configured(&self, vehicles: &[VehicleEmitter<R>], config: &Config) -> Result<(), String> {
+    for alarm in self.engine.configure(config) {
+      if let Some(vehicle) = vehicles
+        .iter()
+        .find(|vehicle| vehicle.id == alarm.vehicle_id)
+      {
+        announce(vehicle, Change::Cleared(alarm));
+      }
+    }
+    Ok(())
+  }
+
+  fn closed(&self, vehicle: &str) -> Result<(), String> {
+    self.engine.remove(vehicle);
+    Ok(())
+  }
+}
+
+// Keeps the alarms of every vehicle, managed as `Arc<AlarmEngine>`.
+pub fn register<R: Runtime>(app: &AppHandle<R>, handlers: &HandlerRegistry<R>) {
+  let engine = Arc::new(AlarmEngine::default());
+  handlers.register(
+    "alarms",
+    FRONTEND_ORDER,
+    AlarmHandler {
+      engine: engine.clone(),
+    },
+  );
+  app.manage(engine);
+}
+
+// The name an enum is serialized with, e.g. `imuOk` for `HealthFlag::ImuOk`.
+fn name(value: &impl Serialize) -> String {
+  serde_json::to_value(value)
+    .ok()
+    .and_then(|value| value.as_str().map(str::to_string))
+    .unwrap_or_default()
+}
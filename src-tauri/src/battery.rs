@@ -0,0 +1,322 @@
+use crate::alarms::AlarmEngine;
+use crate::events::BATTERY_ESTIMATE;
+use crate::log_warn;
+use crate::models::alarm::{AlarmField, BatteryForecast};
+use crate::models::battery::BatteryEstimate;
+use crate::models::config::Config;
+use crate::models::rov_config::RovConfig;
+use crate::models::rov_telemetry::RovTelemetry;
+use crate::models::vehicle::VehicleId;
+use crate::util::{lock, now_ms};
+use crate::websocket::handler::{HandlerRegistry, HandlerResult, MessageHandler, FRONTEND_ORDER};
+use crate::websocket::message::{WebsocketEnvelope, WebsocketMessage};
+use crate::websocket::vehicle::VehicleEmitter;
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Manager, Runtime};
+
+// How far back the drain of the battery is measured.
+const DRAIN_WINDOW_MS: u64 = 10 * 60 * 1000;
+// What the current load is averaged over.
+const CURRENT_LOAD_MS: u64 = 10_000;
+// What the electronics draw, next to all thrusters at full thrust drawing 1.
+const IDLE_LOAD: f32 = 0.1;
+
+#[derive(Clone, Copy)]
+struct Settings {
+  reserve_minutes: f32,
+  ascent_speed: f32,
+}
+
+impl Settings {
+  fn new(config: &Config) -> Self {
+    Settings {
+      reserve_minutes: config.battery_reserve_minutes as f32,
+      ascent_speed: config.ascent_speed,
+    }
+  }
+}
+
+// The usage of one vehicle's battery. The percentage only comes in whole
+// percents, so its drain is measured between the times it stepped down.
+#[derive(Default)]
+struct Usage {
+  // When the percentage was first seen and each time it changed, oldest first.
+  steps: VecDeque<(u64, u8)>,
+  loads: VecDeque<(u64, f32)>,
+  // The strongest axis of the direction vector last sent.
+  commanded: f32,
+  // The fastest a thruster turned so far, which the RPMs are taken relative to.
+  peak_rpm: f32,
+  depth: f32,
+  // The minimum and maximum voltage from the ROV config.
+  voltages: Option<(f32, f32)>,
+  estimate: Option<BatteryEstimate>,
+}
+
+impl Usage {
+  // The pilot commanding thrust or the regulator holding depth and attitude
+  // both load the thrusters, whichever works them harder counts.
+  fn telemetry(&mut self, time_ms: u64, telemetry: &RovTelemetry) {
+    self.depth = telemetry.depth;
+    let rpms = telemetry.thruster_rpms.map(f32::abs);
+    self.peak_rpm = rpms.into_iter().fold(self.peak_rpm, f32::max);
+    let turning = if self.peak_rpm > 0.0 {
+      rpms.iter().sum::<f32>() / (rpms.len() as f32 * self.peak_rpm)
+    } else {
+      0.0
+    };
+    self.loads.push_back((time_ms, self.commanded.max(turning)));
+    while self
+      .loads
+      .front()
+      .is_some_and(|&(load_ms, _)| time_ms.saturating_sub(load_ms) > DRAIN_WINDOW_MS)
+    {
+      self.loads.pop_front();
+    }
+  }
+
+  fn status(
+    &mut self,
+    time_ms: u64,
+    percentage: u8,
+    settings: Settings,
+  ) -> (BatteryEstimate, Option<[f32; 2]>) {
+    // Charged or swapped, what drained before says nothing about it.
+    if self
+      .steps
+      .back()
+      .is_some_and(|&(_, last)| percentage > last)
+    {
+      self.steps.clear();
+    }
+    if self
+      .steps
+      .back()
+      .map_or(true, |&(_, last)| last != percentage)
+    {
+      self.steps.push_back((time_ms, percentage));
+    }
+    while self.steps.len() > 2
+      && self
+        .steps
+        .front()
+        .is_some_and(|&(step_ms, _)| time_ms.saturating_sub(step_ms) > DRAIN_WINDOW_MS)
+    {
+      self.steps.pop_front();
+    }
+
+    let current_load = self
+      .mean_load(time_ms.saturating_sub(CURRENT_LOAD_MS), time_ms)
+      .unwrap_or(self.commanded);
+    let drain_per_minute = self.drain_per_minute(time_ms, current_load);
+    let return_minutes = settings.reserve_minutes
+      + if settings.ascent_speed > 0.0 {
+        self.depth.max(0.0) / settings.ascent_speed / 60.0
+      } else {
+        0.0
+      };
+    // The ROV only reports the percentage, which is taken to run linearly from
+    // the minimum voltage at 0 to the maximum at 100. The minimum voltage is
+    // reached when the percentage runs out.
+    let forecast = drain_per_minute.map(|drain| {
+      let minimum_voltage_minutes = f32::from(percentage) / drain;
+      [
+        minimum_voltage_minutes,
+        minimum_voltage_minutes - return_minutes,
+      ]
+    });
+    let estimate = BatteryEstimate {
+      battery_percentage: percentage,
+      voltage: self
+        .voltages
+        .map(|(min, max)| min + (max - min) * f32::from(percentage) / 100.0),
+      load: current_load,
+      drain_per_minute,
+      minimum_voltage_in_ms: forecast.map(|[minutes, _]| as_ms(minutes)),
+      return_to_surface_ms: as_ms(return_minutes),
+      remaining_dive_ms: forecast.map(|[_, minutes]| as_ms(minutes)),
+    };
+    self.estimate = Some(estimate.clone());
+    (estimate, forecast)
+  }
+
+  // The drain measured over the window up to now, scaled by how the current
+  // load compares to the load it was measured at. Counting the time since the
+  // last step lets the drain fall off once the battery stops draining.
+  fn drain_per_minute(&self, time_ms: u64, current_load: f32) -> Option<f32> {
+    let (&(first_ms, first), &(_, last)) = (self.steps.front()?, self.steps.back()?);
+    if last >= first || time_ms <= first_ms {
+      return None;
+    }
+    let measured = f32::from(first - last) * 60_000.0 / (time_ms - first_ms) as f32;
+    let measured_load = self.mean_load(first_ms, time_ms).unwrap_or(current_load);
+    Some(measured * (IDLE_LOAD + current_load) / (IDLE_LOAD + measured_load))
+  }
+
+  fn mean_load(&self, from_ms: u64, to_ms: u64) -> Option<f32> {
+    let (sum, count) = self
+      .loads
+      .iter()
+      .filter(|&&(load_ms, _)| from_ms <= load_ms && load_ms <= to_ms)
+      .fold((0.0, 0), |(sum, count), &(_, load)| (sum + load, count + 1));
+    (count > 0).then(|| sum / count as f32)
+  }
+}
+
+// Predicts from the battery percentage, the thrust commanded and the thruster
+// RPMs how much longer each vehicle can dive before it has to head back up,
+// and how long until its battery is down to the minimum voltage.
+pub struct BatteryEstimator {
+  settings: Mutex<Settings>,
+  vehicles: Mutex<BTreeMap<VehicleId, Usage>>,
+}
+
+impl Default for BatteryEstimator {
+  fn default() -> Self {
+    BatteryEstimator {
+      settings: Mutex::new(Settings::new(&Config::default())),
+      vehicles: Mutex::new(BTreeMap::new()),
+    }
+  }
+}
+
+impl BatteryEstimator {
+  pub fn configure(&self, config: &Config) {
+    *lock(&self.settings) = Settings::new(config);
+  }
+
+  pub fn rov_config_received(&self, vehicle: &str, rov_config: &RovConfig) {
+    let power = &rov_config.power;
+    self.usage(vehicle, |usage| {
+      usage.voltages = Some((power.battery_min_voltage, power.battery_max_voltage))
+    });
+  }
+
+  pub fn get(&self, vehicle: &str) -> Option<BatteryEstimate> {
+    lock(&self.vehicles)
+      .get(vehicle)
+      .and_then(|usage| usage.estimate.clone())
+  }
+
+  pub fn remove(&self, vehicle: &str) {
+    lock(&self.vehicles).remove(vehicle);
+  }
+
+  fn usage<T>(&self, vehicle: &str, update: impl FnOnce(&mut Usage) -> T) -> T {
+    update(lock(&self.vehicles).entry(vehicle.to_string()).or_default())
+  }
+}
+
+// Publishes the estimate with every status update and checks the forecast
+// against the alarm rules, e.g. the default one to return to the surface.
+pub struct BatteryHandler {
+  pub estimator: Arc<BatteryEstimator>,
+  pub alarms: Arc<AlarmEngine>,
+}
+
+impl<R: Runtime> MessageHandler<R> for BatteryHandler {
+  fn subscribes_to(&self, message: &WebsocketMessage) -> bool {
+    matches!(
+      message,
+      WebsocketMessage::Telemetry(_)
+        | WebsocketMessage::StatusUpdate(_)
+        | WebsocketMessage::Config(_)
+    )
+  }
+
+  fn handle(&self, vehicle: &VehicleEmitter<R>, envelope: &WebsocketEnvelope) -> HandlerResult {
+    let time_ms = now_ms();
+    match &envelope.message {
+      WebsocketMessage::Telemetry(telemetry) => self
+        .estimator
+        .usage(&vehicle.id, |usage| usage.telemetry(time_ms, telemetry)),
+      WebsocketMessage::StatusUpdate(status) => {
+        let settings = *lock(&self.estimator.settings);
+        let (estimate, forecast) = self.estimator.usage(&vehicle.id, |usage| {
+          usage.status(time_ms, status.battery_percentage, settings)
+        });
+        if let Err(e) = vehicle.emit(BATTERY_ESTIMATE, estimate) {
+          log_warn!("Failed to emit {}: {}", BATTERY_ESTIMATE, e);
+        }
+        if let Some([minimum_voltage, remaining_dive]) = forecast {
+          self.alarms.evaluate(
+            vehicle,
+            &[
+              (
+                AlarmField::Battery(BatteryForecast::MinimumVoltageMinutes),
+                minimum_voltage,
+              ),
+              (
+                AlarmField::Battery(BatteryForecast::RemainingDiveMinutes),
+                remaining_dive,
+              ),
+            ],
+          );
+        }
+      }
+      WebsocketMessage::Config(rov_config) => {
+        self.estimator.rov_config_received(&vehicle.id, rov_config)
+      }
+      _ => {}
+    }
+    Ok(Vec::new())
+  }
+
+  fn observe_outbound(
+    &self,
+    vehicle: &VehicleEmitter<R>,
+    envelope: &WebsocketEnvelope,
+  ) -> Result<(), String> {
+    if let WebsocketMessage::DirectionVector(direction_vector) = &envelope.message {
+      let commanded = direction_vector
+        .iter()
+        .fold(0.0, |strongest: f32, axis| strongest.max(axis.abs()))
+        .min(1.0);
+      self
+        .estimator
+        .usage(&vehicle.id, |usage| usage.commanded = commanded);
+    }
+    Ok(())
+  }
+
+  fn configured(&self, _vehicles: &[VehicleEmitter<R>], config: &Config) -> Result<(), String> {
+    self.estimator.configure(config);
+    Ok(())
+  }
+
+  fn opened(
+    &self,
+    vehicle: &VehicleEmitter<R>,
+    rov_config: Option<&RovConfig>,
+  ) -> Result<(), String> {
+    if let Some(rov_config) = rov_config {
+      self.estimator.rov_config_received(&vehicle.id, rov_config);
+    }
+    Ok(())
+  }
+
+  fn closed(&self, vehicle: &str) -> Result<(), String> {
+    self.estimator.remove(vehicle);
+    Ok(())
+  }
+}
+
+// Keeps the estimate of every vehicle, managed as `Arc<BatteryEstimator>`. The
+// forecasts raise alarms, so this comes after `alarms::register`.
+pub fn register<R: Runtime>(app: &AppHandle<R>, handlers: &HandlerRegistry<R>) {
+  let estimator = Arc::new(BatteryEstimator::default());
+  handlers.register(
+    "battery",
+    FRONTEND_ORDER,
+    BatteryHandler {
+      estimator: estimator.clone(),
+      alarms: app.state::<Arc<AlarmEngine>>().inner().clone(),
+    },
+  );
+  app.manage(estimator);
+}
+
+fn as_ms(minutes: f32) -> u64 {
+  (minutes.max(0.0) * 60_000.0).round() as u64
+}
@@ -1,7 +1,10 @@
-use crate::models::actions::{CustomAction, DirectionVector};
+use crate::dive::session::DiveSessions;
+use crate::models::actions::{CustomAction, DirectionVector, DirectionVectorStats};
+use crate::models::handshake::Capability;
+use crate::models::vehicle::VehicleId;
 use crate::toast::{toast_error, toast_loading, toast_success};
 use crate::websocket::{
-  client::{DirectionVectorSendChannelState, MessageSendChannelState},
+  manager::ConnectionManager,
   send::actions::{
     handle_send_custom_action, handle_send_direction_vector, handle_toggle_depth_hold,
     handle_toggle_pitch_stabilization, handle_toggle_roll_stabilization,
@@ -12,45 +15,74 @@ use crate::{log_error, log_info};
 use ffmpeg_next as ffmpeg;
 use std::fs;
 use std::path::Path;
+use std::sync::Arc;
 use tauri::{command, State};
 
+// Pilot input always goes to the vehicle marked as receiving it.
 #[command]
 pub async fn send_direction_vector(
-  state: State<'_, DirectionVectorSendChannelState>,
+  manager: State<'_, Arc<ConnectionManager>>,
   payload: DirectionVector,
 ) -> Result<(), String> {
-  handle_send_direction_vector(&state, payload).await
+  let vehicle = manager.control(None)?;
+  handle_send_direction_vector(&vehicle.direction_vectors, payload).await
+}
+
+#[command]
+pub fn get_direction_vector_stats(
+  manager: State<'_, Arc<ConnectionManager>>,
+  vehicle: Option<VehicleId>,
+) -> Result<DirectionVectorStats, String> {
+  let vehicle = manager.get(vehicle.as_deref())?;
+  Ok(vehicle.direction_vectors.stats.get())
 }
 
 #[command]
 pub async fn send_custom_action(
-  state: State<'_, MessageSendChannelState>,
+  manager: State<'_, Arc<ConnectionManager>>,
+  vehicle: Option<VehicleId>,
   payload: CustomAction,
 ) -> Result<(), String> {
-  handle_send_custom_action(&state, payload).await
+  let vehicle = manager.control(vehicle.as_deref())?;
+  handle_send_custom_action(&vehicle.messages, payload).await
 }
 
 #[command]
 pub async fn toggle_pitch_stabilization(
-  state: State<'_, MessageSendChannelState>,
+  manager: State<'_, Arc<ConnectionManager>>,
+  vehicle: Option<VehicleId>,
 ) -> Result<(), String> {
-  handle_toggle_pitch_stabilization(&state).await
+  let vehicle = manager.control(vehicle.as_deref())?;
+  vehicle.handshake.require(Capability::PitchStabilization)?;
+  handle_toggle_pitch_stabilization(&vehicle.messages).await
 }
 
 #[command]
 pub async fn toggle_roll_stabilization(
-  state: State<'_, MessageSendChannelState>,
+  manager: State<'_, Arc<ConnectionManager>>,
+  vehicle: Option<VehicleId>,
 ) -> Result<(), String> {
-  handle_toggle_roll_stabilization(&state).await
+  let vehicle = manager.control(vehicle.as_deref())?;
+  vehicle.handshake.require(Capability::RollStabilization)?;
+  handle_toggle_roll_stabilization(&vehicle.messages).await
 }
 
 #[command]
-pub async fn toggle_depth_hold(state: State<'_, MessageSendChannelState>) -> Result<(), String> {
-  handle_toggle_depth_hold(&state).await
+pub async fn toggle_depth_hold(
+  manager: State<'_, Arc<ConnectionManager>>,
+  vehicle: Option<VehicleId>,
+) -> Result<(), String> {
+  let vehicle = manager.control(vehicle.as_deref())?;
+  vehicle.handshake.require(Capability::DepthHold)?;
+  handle_toggle_depth_hold(&vehicle.messages).await
 }
 
+// The saved video is linked to the dive of every vehicle on one.
 #[command]
-pub async fn save_recording(temp_path: String) -> Result<(), String> {
+pub async fn save_recording(
+  dive_sessions: State<'_, Arc<DiveSessions>>,
+  temp_path: String,
+) -> Result<(), String> {
   let toast_id = format!("save_recording_{}", temp_path.replace(['/', '\\'], "_"));
 
   log_info!("Starting recording conversion for {}", temp_path);
@@ -193,6 +225,7 @@ pub async fn save_recording(temp_path: String) -> Result<(), String> {
   }
 
   log_info!("Recording conversion completed for {}", temp_path);
+  dive_sessions.link_recording(&output_name);
   toast_success(
     Some(toast_id),
     "Recording saved successfully".to_string(),
@@ -0,0 +1,24 @@
+use crate::alarms::{self, AlarmEngine};
+use crate::models::alarm::Alarm;
+use crate::websocket::manager::ConnectionManager;
+use std::sync::Arc;
+use tauri::{command, State};
+
+// The alarms of every vehicle not yet both cleared and acknowledged.
+#[command]
+pub fn list_alarms(engine: State<'_, Arc<AlarmEngine>>) -> Result<Vec<Alarm>, String> {
+  Ok(engine.list())
+}
+
+#[command]
+pub fn acknowledge_alarm(
+  manager: State<'_, Arc<ConnectionManager>>,
+  engine: State<'_, Arc<AlarmEngine>>,
+  id: u64,
+) -> Result<Alarm, String> {
+  let alarm = engine.acknowledge(id)?;
+  if let Ok(vehicle) = manager.emitter(Some(&alarm.vehicle_id)) {
+    alarms::announce_acknowledged(&vehicle, alarm.clone());
+  }
+  Ok(alarm)
+}
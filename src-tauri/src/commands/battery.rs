@@ -0,0 +1,16 @@
+use crate::battery::BatteryEstimator;
+use crate::models::battery::BatteryEstimate;
+use crate::models::vehicle::VehicleId;
+use crate::websocket::manager::ConnectionManager;
+use std::sync::Arc;
+use tauri::{command, State};
+
+// The estimate of the last status update, None before the first one.
+#[command]
+pub fn get_battery_estimate(
+  manager: State<'_, Arc<ConnectionManager>>,
+  estimator: State<'_, Arc<BatteryEstimator>>,
+  vehicle: Option<VehicleId>,
+) -> Result<Option<BatteryEstimate>, String> {
+  Ok(estimator.get(&manager.get(vehicle.as_deref())?.id))
+}
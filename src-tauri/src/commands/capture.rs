@@ -0,0 +1,47 @@
+use crate::config::get_config_from_file;
+use crate::log_info;
+use crate::models::vehicle::VehicleId;
+use crate::toast::toast_info;
+use crate::websocket::capture::read_capture;
+use crate::websocket::manager::ConnectionManager;
+use crate::websocket::replay::{self, check_replay_speed, CaptureReplay};
+use crate::websocket::telemetry_ui::TelemetryUi;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tauri::async_runtime::spawn;
+use tauri::{command, State};
+
+#[command]
+pub async fn replay_capture(
+  manager: State<'_, Arc<ConnectionManager>>,
+  replay: State<'_, CaptureReplay>,
+  paths: Vec<String>,
+  speed: Option<f64>,
+  vehicle: Option<VehicleId>,
+) -> Result<(), String> {
+  let speed = check_replay_speed(speed.unwrap_or(1.0))?;
+  let paths: Vec<PathBuf> = paths.into_iter().map(PathBuf::from).collect();
+  let frames = read_capture(&paths)?;
+  let emitter = manager.emitter(vehicle.as_deref())?;
+  // Kept apart from the live telemetry and its statistics.
+  let telemetry_ui = Arc::new(TelemetryUi::default());
+  telemetry_ui.configure(&get_config_from_file());
+
+  log_info!("Replaying {} captured frames at {}x", frames.len(), speed);
+  replay.start(spawn(async move {
+    let replayed = replay::replay_capture(&emitter, telemetry_ui, &frames, speed).await;
+    log_info!("Replayed {} captured messages", replayed);
+    toast_info(
+      None,
+      "Capture replay finished".to_string(),
+      Some(format!("{} messages replayed", replayed)),
+      None,
+    );
+  }));
+  Ok(())
+}
+
+#[command]
+pub fn stop_capture_replay(replay: State<'_, CaptureReplay>) -> Result<bool, String> {
+  Ok(replay.stop())
+}
@@ -0,0 +1,59 @@
+use crate::config::{update_config_file, ConfigSendChannelState};
+use crate::log_error;
+use crate::models::config::Config;
+use crate::models::connection::ConnectionState;
+use crate::models::vehicle::VehicleId;
+use crate::websocket::connection::ConnectionCommand;
+use crate::websocket::manager::ConnectionManager;
+use std::sync::Arc;
+use tauri::{command, State};
+
+#[command]
+pub fn get_connection_state(
+  manager: State<'_, Arc<ConnectionManager>>,
+  vehicle: Option<VehicleId>,
+) -> Result<ConnectionState, String> {
+  Ok(manager.get(vehicle.as_deref())?.control.state.get())
+}
+
+#[command]
+pub async fn connect(
+  manager: State<'_, Arc<ConnectionManager>>,
+  vehicle: Option<VehicleId>,
+) -> Result<(), String> {
+  let vehicle = manager.get(vehicle.as_deref())?;
+  if let Err(e) = vehicle.control.tx.send(ConnectionCommand::Connect).await {
+    log_error!("Failed to send Connect: {}", e);
+    return Err(e.to_string());
+  }
+  Ok(())
+}
+
+#[command]
+pub async fn disconnect(
+  manager: State<'_, Arc<ConnectionManager>>,
+  vehicle: Option<VehicleId>,
+) -> Result<(), String> {
+  let vehicle = manager.get(vehicle.as_deref())?;
+  if let Err(e) = vehicle.control.tx.send(ConnectionCommand::Disconnect).await {
+    log_error!("Failed to send Disconnect: {}", e);
+    return Err(e.to_string());
+  }
+  Ok(())
+}
+
+// Unpins the certificate of the vehicle's ROV, the next certificate it presents
+// is trusted again. Needed after the ROV got a new certificate.
+#[command]
+pub async fn forget_rov_certificate(
+  config: State<'_, ConfigSendChannelState>,
+  manager: State<'_, Arc<ConnectionManager>>,
+  vehicle: Option<VehicleId>,
+) -> Result<Config, String> {
+  let vehicle = manager.get(vehicle.as_deref())?;
+  update_config_file(&config, |config| {
+    config.set_certificate_fingerprint(vehicle.profile.as_deref(), None)?;
+    Ok(true)
+  })
+  .await
+}
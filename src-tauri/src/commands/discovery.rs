@@ -0,0 +1,11 @@
+use crate::discovery::DiscoveredRovs;
+use crate::models::discovery::DiscoveredRov;
+use std::sync::Arc;
+use tauri::{command, State};
+
+#[command]
+pub async fn discover_rovs(
+  state: State<'_, Arc<DiscoveredRovs>>,
+) -> Result<Vec<DiscoveredRov>, String> {
+  Ok(state.discover().await)
+}
@@ -0,0 +1,131 @@
+use crate::config::get_config_from_file;
+use crate::dive::recorder::DiveRecorder;
+use crate::dive::replay::ActiveDiveReplay;
+use crate::dive::session::DiveSessions;
+use crate::models::dive::{DiveRecording, DiveRecordingFormat, DiveReplayState, DiveSession};
+use crate::models::vehicle::VehicleId;
+use crate::websocket::manager::ConnectionManager;
+use std::path::Path;
+use std::sync::Arc;
+use tauri::{command, State};
+
+// Records the vehicle's traffic to a new file next to the videos until
+// `stop_dive_recording`.
+#[command]
+pub fn start_dive_recording(
+  manager: State<'_, Arc<ConnectionManager>>,
+  recorder: State<'_, Arc<DiveRecorder>>,
+  vehicle: Option<VehicleId>,
+  format: Option<DiveRecordingFormat>,
+) -> Result<DiveRecording, String> {
+  let directory = get_config_from_file().dive_directory();
+  let vehicle = manager.get(vehicle.as_deref())?;
+  recorder.start(&vehicle.id, &directory, format.unwrap_or_default())
+}
+
+#[command]
+pub fn stop_dive_recording(
+  manager: State<'_, Arc<ConnectionManager>>,
+  recorder: State<'_, Arc<DiveRecorder>>,
+  vehicle: Option<VehicleId>,
+) -> Result<DiveRecording, String> {
+  recorder.stop(&manager.get(vehicle.as_deref())?.id)
+}
+
+#[command]
+pub fn list_dive_recordings(
+  recorder: State<'_, Arc<DiveRecorder>>,
+) -> Result<Vec<DiveRecording>, String> {
+  recorder.list(&get_config_from_file().dive_directory())
+}
+
+#[command]
+pub fn start_dive_session(
+  manager: State<'_, Arc<ConnectionManager>>,
+  sessions: State<'_, Arc<DiveSessions>>,
+  vehicle: Option<VehicleId>,
+  operator: Option<String>,
+  site: Option<String>,
+) -> Result<DiveSession, String> {
+  sessions.start(&manager.get(vehicle.as_deref())?.id, operator, site)
+}
+
+// Closes the vehicle's dive session with a summary of the dive.
+#[command]
+pub fn stop_dive_session(
+  manager: State<'_, Arc<ConnectionManager>>,
+  sessions: State<'_, Arc<DiveSessions>>,
+  vehicle: Option<VehicleId>,
+) -> Result<DiveSession, String> {
+  sessions.stop(&manager.get(vehicle.as_deref())?.id)
+}
+
+#[command]
+pub fn list_dive_sessions(
+  sessions: State<'_, Arc<DiveSessions>>,
+) -> Result<Vec<DiveSession>, String> {
+  sessions.list()
+}
+
+// Plays a dive recording back as the vehicle. Its control commands are refused
+// and its own events held back until `stop_dive_replay`.
+#[command]
+pub fn start_dive_replay(
+  manager: State<'_, Arc<ConnectionManager>>,
+  replay: State<'_, Arc<ActiveDiveReplay>>,
+  path: String,
+  speed: Option<f64>,
+  vehicle: Option<VehicleId>,
+) -> Result<DiveReplayState, String> {
+  replay.start(
+    &manager,
+    vehicle.as_deref(),
+    Path::new(&path),
+    speed.unwrap_or(1.0),
+  )
+}
+
+#[command]
+pub fn pause_dive_replay(
+  replay: State<'_, Arc<ActiveDiveReplay>>,
+) -> Result<DiveReplayState, String> {
+  replay.pause(true)
+}
+
+#[command]
+pub fn resume_dive_replay(
+  replay: State<'_, Arc<ActiveDiveReplay>>,
+) -> Result<DiveReplayState, String> {
+  replay.pause(false)
+}
+
+#[command]
+pub fn seek_dive_replay(
+  replay: State<'_, Arc<ActiveDiveReplay>>,
+  position_ms: u64,
+) -> Result<DiveReplayState, String> {
+  replay.seek(position_ms)
+}
+
+#[command]
+pub fn set_dive_replay_speed(
+  replay: State<'_, Arc<ActiveDiveReplay>>,
+  speed: f64,
+) -> Result<DiveReplayState, String> {
+  replay.set_speed(speed)
+}
+
+#[command]
+pub fn stop_dive_replay(
+  manager: State<'_, Arc<ConnectionManager>>,
+  replay: State<'_, Arc<ActiveDiveReplay>>,
+) -> Result<DiveReplayState, String> {
+  replay.stop(&manager)
+}
+
+#[command]
+pub fn get_dive_replay_state(
+  replay: State<'_, Arc<ActiveDiveReplay>>,
+) -> Result<Option<DiveReplayState>, String> {
+  Ok(replay.state())
+}
@@ -0,0 +1,13 @@
+use crate::models::handshake::Handshake;
+use crate::models::vehicle::VehicleId;
+use crate::websocket::manager::ConnectionManager;
+use std::sync::Arc;
+use tauri::{command, State};
+
+#[command]
+pub fn get_rov_handshake(
+  manager: State<'_, Arc<ConnectionManager>>,
+  vehicle: Option<VehicleId>,
+) -> Result<Option<Handshake>, String> {
+  Ok(manager.get(vehicle.as_deref())?.handshake.get())
+}
@@ -0,0 +1,13 @@
+use crate::models::link_quality::LinkQuality;
+use crate::models::vehicle::VehicleId;
+use crate::websocket::manager::ConnectionManager;
+use std::sync::Arc;
+use tauri::{command, State};
+
+#[command]
+pub fn get_link_quality(
+  manager: State<'_, Arc<ConnectionManager>>,
+  vehicle: Option<VehicleId>,
+) -> Result<LinkQuality, String> {
+  Ok(manager.get(vehicle.as_deref())?.link.get())
+}
@@ -0,0 +1,22 @@
+use crate::models::outbound::QueuedMessage;
+use crate::models::vehicle::VehicleId;
+use crate::websocket::manager::ConnectionManager;
+use std::sync::Arc;
+use tauri::{command, State};
+
+#[command]
+pub fn get_outbound_queue(
+  manager: State<'_, Arc<ConnectionManager>>,
+  vehicle: Option<VehicleId>,
+) -> Result<Vec<QueuedMessage>, String> {
+  Ok(manager.get(vehicle.as_deref())?.messages.queue.list())
+}
+
+#[command]
+pub fn cancel_outbound_message(
+  manager: State<'_, Arc<ConnectionManager>>,
+  vehicle: Option<VehicleId>,
+  id: u64,
+) -> Result<(), String> {
+  manager.get(vehicle.as_deref())?.messages.queue.cancel(id)
+}
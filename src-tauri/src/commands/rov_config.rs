@@ -1,60 +1,90 @@
-use crate::models::rov_config::{RovConfig, ThrusterTest, MicrocontrollerFirmwareVariant};
+use crate::config::{remember_rov_config, ConfigSendChannelState};
+use crate::models::handshake::Capability;
+use crate::models::rov_config::{MicrocontrollerFirmwareVariant, RovConfig, ThrusterTest};
+use crate::models::vehicle::VehicleId;
 use crate::websocket::{
-  client::MessageSendChannelState,
+  manager::ConnectionManager,
   send::config::{
-    handle_cancel_regulator_auto_tuning, handle_cancel_thruster_test, handle_request_rov_config,
-    handle_set_rov_config, handle_start_regulator_auto_tuning, handle_start_thruster_test, handle_flash_microcontroller_firmware,
+    handle_cancel_regulator_auto_tuning, handle_cancel_thruster_test,
+    handle_flash_microcontroller_firmware, handle_request_rov_config, handle_set_rov_config,
+    handle_start_regulator_auto_tuning, handle_start_thruster_test,
   },
 };
+use std::sync::Arc;
 use tauri::{command, State};
 
 #[command]
-pub async fn request_rov_config(state: State<'_, MessageSendChannelState>) -> Result<(), String> {
-  handle_request_rov_config(&state).await
+pub async fn request_rov_config(
+  manager: State<'_, Arc<ConnectionManager>>,
+  config: State<'_, ConfigSendChannelState>,
+  vehicle: Option<VehicleId>,
+) -> Result<RovConfig, String> {
+  let vehicle = manager.get(vehicle.as_deref())?;
+  let rov_config = handle_request_rov_config(&vehicle.messages).await?;
+  remember_rov_config(&config, vehicle.profile.as_deref(), &rov_config).await;
+  Ok(rov_config)
 }
 
 #[command]
 pub async fn set_rov_config(
-  state: State<'_, MessageSendChannelState>,
+  manager: State<'_, Arc<ConnectionManager>>,
+  config: State<'_, ConfigSendChannelState>,
+  vehicle: Option<VehicleId>,
   payload: RovConfig,
-) -> Result<(), String> {
-  handle_set_rov_config(&state, payload).await
+) -> Result<RovConfig, String> {
+  let vehicle = manager.control(vehicle.as_deref())?;
+  let rov_config = handle_set_rov_config(&vehicle.messages, payload).await?;
+  remember_rov_config(&config, vehicle.profile.as_deref(), &rov_config).await;
+  Ok(rov_config)
 }
 
 #[command]
 pub async fn start_thruster_test(
-  state: State<'_, MessageSendChannelState>,
+  manager: State<'_, Arc<ConnectionManager>>,
+  vehicle: Option<VehicleId>,
   payload: ThrusterTest,
 ) -> Result<(), String> {
-  handle_start_thruster_test(&state, payload).await
+  let vehicle = manager.control(vehicle.as_deref())?;
+  vehicle.handshake.require(Capability::ThrusterTest)?;
+  handle_start_thruster_test(&vehicle.messages, payload).await
 }
 
 #[command]
 pub async fn cancel_thruster_test(
-  state: State<'_, MessageSendChannelState>,
+  manager: State<'_, Arc<ConnectionManager>>,
+  vehicle: Option<VehicleId>,
   payload: ThrusterTest,
 ) -> Result<(), String> {
-  handle_cancel_thruster_test(&state, payload).await
+  let vehicle = manager.get(vehicle.as_deref())?;
+  handle_cancel_thruster_test(&vehicle.messages, payload).await
 }
 
 #[command]
 pub async fn start_regulator_auto_tuning(
-  state: State<'_, MessageSendChannelState>,
+  manager: State<'_, Arc<ConnectionManager>>,
+  vehicle: Option<VehicleId>,
 ) -> Result<(), String> {
-  handle_start_regulator_auto_tuning(&state).await
+  let vehicle = manager.control(vehicle.as_deref())?;
+  vehicle.handshake.require(Capability::RegulatorAutoTuning)?;
+  handle_start_regulator_auto_tuning(&vehicle.messages).await
 }
 
 #[command]
 pub async fn cancel_regulator_auto_tuning(
-  state: State<'_, MessageSendChannelState>,
+  manager: State<'_, Arc<ConnectionManager>>,
+  vehicle: Option<VehicleId>,
 ) -> Result<(), String> {
-  handle_cancel_regulator_auto_tuning(&state).await
+  let vehicle = manager.get(vehicle.as_deref())?;
+  handle_cancel_regulator_auto_tuning(&vehicle.messages).await
 }
 
 #[command]
 pub async fn flash_microcontroller_firmware(
-  state: State<'_, MessageSendChannelState>,
+  manager: State<'_, Arc<ConnectionManager>>,
+  vehicle: Option<VehicleId>,
   payload: MicrocontrollerFirmwareVariant,
 ) -> Result<(), String> {
-  handle_flash_microcontroller_firmware(&state, payload).await
+  let vehicle = manager.control(vehicle.as_deref())?;
+  vehicle.handshake.require(Capability::FirmwareFlashing)?;
+  handle_flash_microcontroller_firmware(&vehicle.messages, payload).await
 }
@@ -0,0 +1,53 @@
+use crate::config::{update_config_file, ConfigSendChannelState};
+use crate::models::config::{Config, RovProfile};
+use tauri::{command, State};
+
+#[command]
+pub async fn create_rov_profile(
+  state: State<'_, ConfigSendChannelState>,
+  payload: RovProfile,
+) -> Result<Config, String> {
+  update_config_file(&state, |config| {
+    config.create_rov_profile(payload)?;
+    Ok(true)
+  })
+  .await
+}
+
+#[command]
+pub async fn update_rov_profile(
+  state: State<'_, ConfigSendChannelState>,
+  name: String,
+  payload: RovProfile,
+) -> Result<Config, String> {
+  update_config_file(&state, |config| {
+    config.update_rov_profile(&name, payload)?;
+    Ok(true)
+  })
+  .await
+}
+
+#[command]
+pub async fn delete_rov_profile(
+  state: State<'_, ConfigSendChannelState>,
+  name: String,
+) -> Result<Config, String> {
+  update_config_file(&state, |config| {
+    config.delete_rov_profile(&name)?;
+    Ok(true)
+  })
+  .await
+}
+
+// The client reconnects when the profile points at another endpoint.
+#[command]
+pub async fn switch_rov_profile(
+  state: State<'_, ConfigSendChannelState>,
+  name: String,
+) -> Result<Config, String> {
+  update_config_file(&state, |config| {
+    config.switch_rov_profile(&name)?;
+    Ok(true)
+  })
+  .await
+}
@@ -0,0 +1,22 @@
+use crate::models::telemetry_history::{TelemetryField, TelemetrySeries};
+use crate::models::vehicle::VehicleId;
+use crate::websocket::manager::ConnectionManager;
+use crate::websocket::telemetry_history::TelemetryHistory;
+use std::sync::Arc;
+use tauri::{command, State};
+
+// The readings of `fields` between `from` and `to`, in milliseconds since the
+// epoch, downsampled to at most `max_points` buckets for plotting.
+#[command]
+pub fn query_telemetry(
+  manager: State<'_, Arc<ConnectionManager>>,
+  history: State<'_, Arc<TelemetryHistory>>,
+  vehicle: Option<VehicleId>,
+  fields: Vec<TelemetryField>,
+  from: Option<u64>,
+  to: Option<u64>,
+  max_points: u32,
+) -> Result<Vec<TelemetrySeries>, String> {
+  let vehicle = manager.get(vehicle.as_deref())?;
+  history.query(&vehicle.id, &fields, from, to, max_points)
+}
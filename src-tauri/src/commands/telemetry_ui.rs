@@ -0,0 +1,16 @@
+use crate::models::telemetry_ui::TelemetryUiStats;
+use crate::models::vehicle::VehicleId;
+use crate::websocket::manager::ConnectionManager;
+use crate::websocket::telemetry_ui::TelemetryUi;
+use std::sync::Arc;
+use tauri::{command, State};
+
+// How the telemetry of a vehicle reached the frontend.
+#[command]
+pub fn get_telemetry_ui_stats(
+  manager: State<'_, Arc<ConnectionManager>>,
+  telemetry_ui: State<'_, Arc<TelemetryUi>>,
+  vehicle: Option<VehicleId>,
+) -> Result<TelemetryUiStats, String> {
+  Ok(telemetry_ui.stats(&manager.get(vehicle.as_deref())?.id))
+}
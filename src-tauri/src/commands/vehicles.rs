@@ -0,0 +1,40 @@
+use crate::config::get_config_from_file;
+use crate::models::vehicle::{VehicleId, VehicleInfo};
+use crate::websocket::manager::ConnectionManager;
+use std::sync::Arc;
+use tauri::{command, State};
+
+#[command]
+pub fn list_vehicles(manager: State<'_, Arc<ConnectionManager>>) -> Vec<VehicleInfo> {
+  manager.list()
+}
+
+// Connects to the ROV of a profile alongside the others, the profile name
+// becomes the vehicle id. A profile named after the primary vehicle, or one
+// whose ROV an open vehicle already connects to, is refused.
+#[command]
+pub fn open_vehicle(
+  manager: State<'_, Arc<ConnectionManager>>,
+  profile: String,
+) -> Result<Vec<VehicleInfo>, String> {
+  manager.open(profile.clone(), Some(profile), &get_config_from_file())?;
+  Ok(manager.list())
+}
+
+#[command]
+pub async fn close_vehicle(
+  manager: State<'_, Arc<ConnectionManager>>,
+  vehicle: VehicleId,
+) -> Result<Vec<VehicleInfo>, String> {
+  manager.close(&vehicle).await?;
+  Ok(manager.list())
+}
+
+#[command]
+pub fn set_pilot_vehicle(
+  manager: State<'_, Arc<ConnectionManager>>,
+  vehicle: VehicleId,
+) -> Result<Vec<VehicleInfo>, String> {
+  manager.set_pilot(&vehicle)?;
+  Ok(manager.list())
+}
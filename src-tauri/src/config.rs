@@ -1,15 +1,21 @@
 use crate::models::config::Config;
+use crate::models::rov_config::RovConfig;
 use crate::toast::toast_success;
 use crate::toast::toast_warn;
 use crate::{log_error, log_warn};
 use std::fs;
 use std::path::PathBuf;
 use tokio::sync::mpsc::Sender;
+use tokio::sync::Mutex;
 
 pub struct ConfigSendChannelState {
   pub tx: Sender<Config>,
 }
 
+// Held while the saved config is read, changed and written back, so changes
+// made at the same time don't drop each other.
+static CONFIG_FILE: Mutex<()> = Mutex::const_new(());
+
 pub fn get_config_path() -> Option<PathBuf> {
   dirs::config_dir().map(|base_dir| base_dir.join("manafish").join("config.json"))
 }
@@ -70,6 +76,107 @@ pub fn get_config_from_file() -> Config {
 }
 
 pub async fn set_config_to_file(
+  state: &ConfigSendChannelState,
+  mut payload: Config,
+) -> Result<(), String> {
+  let _saving = CONFIG_FILE.lock().await;
+  // Profiles are only changed through the profile commands, the frontend may
+  // hold an older copy of them.
+  let saved = get_config_from_file();
+  payload.rov_profiles = saved.rov_profiles;
+  payload.active_rov_profile = saved.active_rov_profile;
+  // Pinned certificates are learned from the ROV and only forgotten on request.
+  payload.certificate_fingerprint = saved.certificate_fingerprint;
+  payload.store_endpoint_in_active_rov_profile();
+  save_config_to_file(state, payload).await?;
+
+  toast_success(None, "Config set successfully".to_string(), None, None);
+
+  Ok(())
+}
+
+// Applies `change` to the saved config and returns the result. `change` tells
+// whether it changed anything, only then the config is saved and passed on.
+pub async fn update_config_file(
+  state: &ConfigSendChannelState,
+  change: impl FnOnce(&mut Config) -> Result<bool, String>,
+) -> Result<Config, String> {
+  let _saving = CONFIG_FILE.lock().await;
+  let mut config = get_config_from_file();
+  if change(&mut config)? {
+    save_config_to_file(state, config.clone()).await?;
+  }
+  Ok(config)
+}
+
+// Stores the config reported by the ROV in its profile, the active profile
+// when the ROV is the primary vehicle.
+pub async fn remember_rov_config(
+  state: &ConfigSendChannelState,
+  profile: Option<&str>,
+  rov_config: &RovConfig,
+) {
+  let result = update_config_file(state, |config| {
+    let profile = match profile {
+      Some(name) => config
+        .rov_profiles
+        .iter_mut()
+        .find(|profile| profile.name == name),
+      None => config.active_rov_profile_mut(),
+    };
+    let Some(profile) = profile else {
+      return Ok(false);
+    };
+    if let Some(expected) = profile.expected_firmware_variant {
+      if expected != rov_config.microcontroller_firmware_variant {
+        log_warn!(
+          "ROV runs {:?} firmware but profile {} expects {:?}",
+          rov_config.microcontroller_firmware_variant,
+          profile.name,
+          expected
+        );
+        toast_warn(
+          None,
+          "ROV firmware variant does not match the profile".to_string(),
+          Some(format!(
+            "Profile {} expects {:?} firmware",
+            profile.name, expected
+          )),
+          None,
+        );
+      }
+    }
+    if profile.last_known_rov_config.as_ref() == Some(rov_config) {
+      return Ok(false);
+    }
+    profile.last_known_rov_config = Some(rov_config.clone());
+    Ok(true)
+  })
+  .await;
+
+  if let Err(e) = result {
+    log_warn!("Failed to store ROV config in profile: {}", e);
+  }
+}
+
+// Pins a certificate trusted on first use to the profile of its ROV.
+pub async fn remember_certificate(
+  state: &ConfigSendChannelState,
+  profile: Option<&str>,
+  fingerprint: &str,
+) {
+  let result = update_config_file(state, |config| {
+    config.set_certificate_fingerprint(profile, Some(fingerprint.to_string()))?;
+    Ok(true)
+  })
+  .await;
+
+  if let Err(e) = result {
+    log_warn!("Failed to pin ROV certificate: {}", e);
+  }
+}
+
+async fn save_config_to_file(
   state: &ConfigSendChannelState,
   payload: Config,
 ) -> Result<(), String> {
@@ -90,7 +197,5 @@ pub async fn set_config_to_file(
 
   state.tx.send(payload).await.map_err(|e| e.to_string())?;
 
-  toast_success(None, "Config set successfully".to_string(), None, None);
-
   Ok(())
 }
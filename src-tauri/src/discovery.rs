@@ -0,0 +1,167 @@
+use crate::events::DISCOVERED_ROVS;
+use crate::log_warn;
+use crate::models::discovery::{Announcement, DiscoveredRov, DiscoveryMessage, DISCOVERY_PORT};
+use crate::util::lock;
+use std::io::ErrorKind;
+use std::net::{Ipv4Addr, SocketAddr};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Runtime};
+use tokio::net::UdpSocket;
+use tokio::sync::Notify;
+use tokio::time::{interval, sleep, Instant};
+
+const PROBE_INTERVAL: Duration = Duration::from_secs(2);
+// ROVs that stop answering are forgotten after three missed probes.
+const FORGET_AFTER: Duration = Duration::from_secs(6);
+// How long `discover` waits for answers to its probe.
+const ANSWER_WINDOW: Duration = Duration::from_millis(500);
+
+pub fn broadcast_targets() -> Vec<SocketAddr> {
+  vec![SocketAddr::from((Ipv4Addr::BROADCAST, DISCOVERY_PORT))]
+}
+
+#[derive(Default)]
+pub struct DiscoveredRovs {
+  rovs: Mutex<Vec<(DiscoveredRov, Instant)>>,
+  probe: Notify,
+}
+
+impl DiscoveredRovs {
+  pub fn get(&self) -> Vec<DiscoveredRov> {
+    lock(&self.rovs)
+      .iter()
+      .map(|(rov, _)| rov.clone())
+      .collect()
+  }
+
+  // Probes right away and returns the ROVs that are known once the answers
+  // had time to arrive.
+  pub async fn discover(&self) -> Vec<DiscoveredRov> {
+    self.probe.notify_one();
+    sleep(ANSWER_WINDOW).await;
+    self.get()
+  }
+
+  // Returns whether the list changed.
+  fn seen(&self, rov: DiscoveredRov) -> bool {
+    let mut rovs = lock(&self.rovs);
+    let now = Instant::now();
+    match rovs.iter_mut().find(|(known, _)| {
+      known.ip_address == rov.ip_address && known.web_socket_port == rov.web_socket_port
+    }) {
+      Some((known, last_seen)) => {
+        *last_seen = now;
+        let changed = *known != rov;
+        *known = rov;
+        changed
+      }
+      None => {
+        rovs.push((rov, now));
+        rovs.sort_by(|(a, _), (b, _)| a.name.cmp(&b.name).then(a.ip_address.cmp(&b.ip_address)));
+        true
+      }
+    }
+  }
+
+  fn forget_silent(&self) -> bool {
+    let mut rovs = lock(&self.rovs);
+    let count = rovs.len();
+    rovs.retain(|(_, last_seen)| last_seen.elapsed() < FORGET_AFTER);
+    rovs.len() != count
+  }
+}
+
+// Probes the `targets` periodically and publishes the ROVs that answer as
+// `discovered_rovs` events whenever the list changes.
+pub struct DiscoveryScanner<R: Runtime> {
+  pub app: AppHandle<R>,
+  pub targets: Vec<SocketAddr>,
+  pub rovs: Arc<DiscoveredRovs>,
+}
+
+impl<R: Runtime> DiscoveryScanner<R> {
+  pub async fn run(self) {
+    let socket = match UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0)).await {
+      Ok(socket) => socket,
+      Err(e) => {
+        log_warn!(
+          "Failed to bind discovery socket, ROV discovery is off: {}",
+          e
+        );
+        return;
+      }
+    };
+    if let Err(e) = socket.set_broadcast(true) {
+      log_warn!("Failed to enable broadcast on discovery socket: {}", e);
+    }
+
+    let mut probe_interval = interval(PROBE_INTERVAL);
+    let mut buffer = [0u8; 2048];
+    // Only warn once while the network stays unreachable.
+    let mut probe_failing = false;
+
+    loop {
+      tokio::select! {
+        _ = probe_interval.tick() => {
+          self.probe(&socket, &mut probe_failing).await;
+          if self.rovs.forget_silent() {
+            self.publish();
+          }
+        }
+        _ = self.rovs.probe.notified() => {
+          self.probe(&socket, &mut probe_failing).await;
+        }
+        received = socket.recv_from(&mut buffer) => match received {
+          Ok((len, from)) => {
+            if let Some(rov) = parse_announcement(&buffer[..len], from) {
+              if self.rovs.seen(rov) {
+                self.publish();
+              }
+            }
+          }
+          // Windows reports unreachable probe targets on the next receive.
+          Err(e) if e.kind() == ErrorKind::ConnectionReset => {}
+          Err(e) => log_warn!("Failed to receive on discovery socket: {}", e),
+        },
+      }
+    }
+  }
+
+  async fn probe(&self, socket: &UdpSocket, probe_failing: &mut bool) {
+    let probe = serde_json::to_vec(&DiscoveryMessage::Discover).unwrap();
+    let mut failed = false;
+    for target in &self.targets {
+      if let Err(e) = socket.send_to(&probe, target).await {
+        if !*probe_failing {
+          log_warn!("Failed to send discovery probe to {}: {}", target, e);
+        }
+        failed = true;
+      }
+    }
+    *probe_failing = failed;
+  }
+
+  fn publish(&self) {
+    self.app.emit(DISCOVERED_ROVS, self.rovs.get()).unwrap();
+  }
+}
+
+fn parse_announcement(datagram: &[u8], from: SocketAddr) -> Option<DiscoveredRov> {
+  let Ok(DiscoveryMessage::Announce(announcement)) = serde_json::from_slice(datagram) else {
+    return None;
+  };
+  let Announcement {
+    name,
+    firmware_version,
+    web_socket_port,
+    webrtc_signaling_api_port,
+  } = announcement;
+  Some(DiscoveredRov {
+    name,
+    ip_address: from.ip().to_string(),
+    firmware_version,
+    web_socket_port,
+    webrtc_signaling_api_port,
+  })
+}
@@ -0,0 +1,368 @@
+use crate::dive::session::DiveSessions;
+use crate::log_warn;
+use crate::models::dive::{DiveRecord, DiveRecording, DiveRecordingFormat, DiveSample};
+use crate::models::rov_status::RovStatus;
+use crate::models::rov_telemetry::RovTelemetry;
+use crate::models::vehicle::VehicleId;
+use crate::util::{lock, now_ms};
+use crate::websocket::capture::safe_file_name;
+use crate::websocket::handler::{HandlerRegistry, HandlerResult, MessageHandler, FRONTEND_ORDER};
+use crate::websocket::message::{WebsocketEnvelope, WebsocketMessage};
+use crate::websocket::vehicle::VehicleEmitter;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, LineWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager, Runtime};
+
+// Every line reaches the OS as soon as it is written, so a crash of the app
+// loses nothing. At most this much is lost when the machine itself goes down.
+const SYNC_INTERVAL: Duration = Duration::from_secs(1);
+
+const CSV_HEADER: &str = "time_ms,kind,\
+  pitch,roll,desired_pitch,desired_roll,depth,water_temperature,electronics_temperature,\
+  thruster_rpm_1,thruster_rpm_2,thruster_rpm_3,thruster_rpm_4,\
+  thruster_rpm_5,thruster_rpm_6,thruster_rpm_7,thruster_rpm_8,work_indicator_percentage,\
+  pitch_stabilization,roll_stabilization,depth_hold,battery_percentage,\
+  imu_ok,pressure_sensor_ok,microcontroller_ok,\
+  level,message\n";
+const TELEMETRY_COLUMNS: usize = 16;
+const STATUS_COLUMNS: usize = 7;
+
+// The recording of one vehicle, written to `<vehicle>-<start>.<format>`.
+struct DiveFile {
+  recording: DiveRecording,
+  file: LineWriter<File>,
+  synced: Instant,
+}
+
+impl DiveFile {
+  fn create(directory: &Path, vehicle: &str, format: DiveRecordingFormat) -> io::Result<Self> {
+    fs::create_dir_all(directory)?;
+    let started_ms = now_ms();
+    let path = directory.join(format!(
+      "{}-{}.{}",
+      safe_file_name(vehicle),
+      started_ms,
+      format.extension()
+    ));
+    let mut file = DiveFile {
+      recording: DiveRecording {
+        path: path.to_string_lossy().into_owned(),
+        vehicle_id: vehicle.to_string(),
+        format,
+        started_ms,
+        bytes: 0,
+        recording: true,
+      },
+      file: LineWriter::new(
+        OpenOptions::new()
+          .write(true)
+          .create_new(true)
+          .open(&path)?,
+      ),
+      synced: Instant::now(),
+    };
+    if format == DiveRecordingFormat::Csv {
+      file.write(CSV_HEADER.as_bytes())?;
+    }
+    Ok(file)
+  }
+
+  fn record(&mut self, record: &DiveRecord) -> io::Result<()> {
+    let line = match self.recording.format {
+      DiveRecordingFormat::Csv => csv_row(record).into_bytes(),
+      DiveRecordingFormat::Jsonl => {
+        let mut line = serde_json::to_vec(record)?;
+        line.push(b'\n');
+        line
+      }
+    };
+    self.write(&line)
+  }
+
+  fn write(&mut self, line: &[u8]) -> io::Result<()> {
+    self.file.write_all(line)?;
+    self.recording.bytes += line.len() as u64;
+    if self.synced.elapsed() >= SYNC_INTERVAL {
+      self.file.get_ref().sync_data()?;
+      self.synced = Instant::now();
+    }
+    Ok(())
+  }
+
+  fn finish(mut self) -> io::Result<DiveRecording> {
+    self.file.flush()?;
+    self.file.get_ref().sync_all()?;
+    self.recording.recording = false;
+    Ok(self.recording)
+  }
+}
+
+// Records the telemetry, status updates and logs of a vehicle and everything
+// sent to it while a dive recording runs, one recording per vehicle. Each file
+// is linked to the vehicle's dive session.
+pub struct DiveRecorder {
+  files: Mutex<BTreeMap<VehicleId, DiveFile>>,
+  sessions: Arc<DiveSessions>,
+}
+
+impl DiveRecorder {
+  pub fn new(sessions: Arc<DiveSessions>) -> Self {
+    DiveRecorder {
+      files: Mutex::new(BTreeMap::new()),
+      sessions,
+    }
+  }
+
+  pub fn start(
+    &self,
+    vehicle: &str,
+    directory: &Path,
+    format: DiveRecordingFormat,
+  ) -> Result<DiveRecording, String> {
+    let mut files = lock(&self.files);
+    if files.contains_key(vehicle) {
+      return Err(format!("Vehicle {} is already recording a dive", vehicle));
+    }
+    let file = DiveFile::create(directory, vehicle, format)
+      .map_err(|e| format!("Failed to start dive recording: {}", e))?;
+    let recording = file.recording.clone();
+    files.insert(vehicle.to_string(), file);
+    self.sessions.link_data_file(vehicle, &recording.path);
+    Ok(recording)
+  }
+
+  pub fn stop(&self, vehicle: &str) -> Result<DiveRecording, String> {
+    let file = lock(&self.files)
+      .remove(vehicle)
+      .ok_or_else(|| format!("Vehicle {} is not recording a dive", vehicle))?;
+    file
+      .finish()
+      .map_err(|e| format!("Failed to finish dive recording: {}", e))
+  }
+
+  pub fn is_recording(&self, vehicle: &str) -> bool {
+    lock(&self.files).contains_key(vehicle)
+  }
+
+  pub fn record(&self, vehicle: &str, sample: DiveSample) {
+    let mut files = lock(&self.files);
+    let Some(file) = files.get_mut(vehicle) else {
+      return;
+    };
+    let record = DiveRecord {
+      time_ms: now_ms(),
+      sample,
+    };
+    if let Err(e) = file.record(&record) {
+      log_warn!("Failed to record dive, stopping the recording: {}", e);
+      if let Some(file) = files.remove(vehicle) {
+        let _ = file.finish();
+      }
+    }
+  }
+
+  // The recordings in `directory`, oldest first.
+  pub fn list(&self, directory: &Path) -> Result<Vec<DiveRecording>, String> {
+    let entries = match fs::read_dir(directory) {
+      Ok(entries) => entries,
+      Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+      Err(e) => return Err(format!("Failed to list dive recordings: {}", e)),
+    };
+    let active: BTreeMap<PathBuf, DiveRecording> = lock(&self.files)
+      .values()
+      .map(|file| (PathBuf::from(&file.recording.path), file.recording.clone()))
+      .collect();
+
+    let mut recordings: Vec<_> = entries
+      .filter_map(|entry| {
+        let path = entry.ok()?.path();
+        if let Some(recording) = active.get(&path) {
+          return Some(recording.clone());
+        }
+        let format = match path.extension()?.to_str()? {
+          "csv" => DiveRecordingFormat::Csv,
+          "jsonl" => DiveRecordingFormat::Jsonl,
+          _ => return None,
+        };
+        let (vehicle, started_ms) = path.file_stem()?.to_str()?.rsplit_once('-')?;
+        Some(DiveRecording {
+          path: path.to_string_lossy().into_owned(),
+          vehicle_id: vehicle.to_string(),
+          format,
+          started_ms: started_ms.parse().ok()?,
+          bytes: fs::metadata(&path).ok()?.len(),
+          recording: false,
+        })
+      })
+      .collect();
+    recordings.sort_by(|a, b| (a.started_ms, &a.path).cmp(&(b.started_ms, &b.path)));
+    Ok(recordings)
+  }
+}
+
+// Feeds a vehicle's traffic to the dive recorder.
+pub struct DiveRecordingHandler {
+  pub recorder: Arc<DiveRecorder>,
+}
+
+impl<R: Runtime> MessageHandler<R> for DiveRecordingHandler {
+  fn subscribes_to(&self, message: &WebsocketMessage) -> bool {
+    matches!(
+      message,
+      WebsocketMessage::Telemetry(_)
+        | WebsocketMessage::StatusUpdate(_)
+        | WebsocketMessage::LogMessage(_)
+    )
+  }
+
+  fn handle(&self, vehicle: &VehicleEmitter<R>, envelope: &WebsocketEnvelope) -> HandlerResult {
+    if !self.recorder.is_recording(&vehicle.id) {
+      return Ok(Vec::new());
+    }
+    let sample = match &envelope.message {
+      WebsocketMessage::Telemetry(telemetry) => DiveSample::Telemetry(telemetry.clone()),
+      WebsocketMessage::StatusUpdate(status) => DiveSample::Status(status.clone()),
+      WebsocketMessage::LogMessage(entry) => DiveSample::Log(entry.clone()),
+      _ => return Ok(Vec::new()),
+    };
+    self.recorder.record(&vehicle.id, sample);
+    Ok(Vec::new())
+  }
+
+  fn observe_outbound(
+    &self,
+    vehicle: &VehicleEmitter<R>,
+    envelope: &WebsocketEnvelope,
+  ) -> Result<(), String> {
+    if self.recorder.is_recording(&vehicle.id) {
+      let message = serde_json::to_value(envelope).map_err(|e| e.to_string())?;
+      self
+        .recorder
+        .record(&vehicle.id, DiveSample::Control(message));
+    }
+    Ok(())
+  }
+
+  fn closed(&self, vehicle: &str) -> Result<(), String> {
+    if self.recorder.is_recording(vehicle) {
+      self.recorder.stop(vehicle)?;
+    }
+    Ok(())
+  }
+}
+
+// Keeps the dive recordings of every vehicle, managed as `Arc<DiveRecorder>`.
+// The recordings are linked to dive sessions, so this comes after
+// `session::register`.
+pub fn register<R: Runtime>(app: &AppHandle<R>, handlers: &HandlerRegistry<R>) {
+  let recorder = Arc::new(DiveRecorder::new(
+    app.state::<Arc<DiveSessions>>().inner().clone(),
+  ));
+  handlers.register(
+    "dive_recorder",
+    FRONTEND_ORDER,
+    DiveRecordingHandler {
+      recorder: recorder.clone(),
+    },
+  );
+  app.manage(recorder);
+}
+
+fn csv_row(record: &DiveRecord) -> String {
+  let blank = |count| vec![String::new(); count];
+  let (kind, telemetry, status, message) = match &record.sample {
+    DiveSample::Telemetry(telemetry) => (
+      "telemetry",
+      telemetry_fields(telemetry),
+      blank(STATUS_COLUMNS),
+      blank(2),
+    ),
+    DiveSample::Status(status) => (
+      "status",
+      blank(TELEMETRY_COLUMNS),
+      status_fields(status),
+      blank(2),
+    ),
+    DiveSample::Log(entry) => (
+      "log",
+      blank(TELEMETRY_COLUMNS),
+      blank(STATUS_COLUMNS),
+      vec![name(&entry.level), entry.message.clone()],
+    ),
+    DiveSample::Control(message) => (
+      "control",
+      blank(TELEMETRY_COLUMNS),
+      blank(STATUS_COLUMNS),
+      vec![String::new(), message.to_string()],
+    ),
+  };
+
+  let fields: Vec<_> = [record.time_ms.to_string(), kind.to_string()]
+    .into_iter()
+    .chain(telemetry)
+    .chain(status)
+    .chain(message)
+    .map(|field| csv_field(&field))
+    .collect();
+  fields.join(",") + "\n"
+}
+
+fn telemetry_fields(telemetry: &RovTelemetry) -> Vec<String> {
+  [
+    telemetry.pitch,
+    telemetry.roll,
+    telemetry.desired_pitch,
+    telemetry.desired_roll,
+    telemetry.depth,
+  ]
+  .iter()
+  .map(f32::to_string)
+  // A temperature the ROV doesn't report is left empty.
+  .chain(
+    [
+      telemetry.water_temperature,
+      telemetry.electronics_temperature,
+    ]
+    .map(|reading| {
+      reading
+        .map(|reading| reading.to_string())
+        .unwrap_or_default()
+    }),
+  )
+  .chain(telemetry.thruster_rpms.iter().map(f32::to_string))
+  .chain([telemetry.work_indicator_percentage.to_string()])
+  .collect()
+}
+
+fn status_fields(status: &RovStatus) -> Vec<String> {
+  vec![
+    status.pitch_stabilization.to_string(),
+    status.roll_stabilization.to_string(),
+    status.depth_hold.to_string(),
+    status.battery_percentage.to_string(),
+    status.health.imu_ok.to_string(),
+    status.health.pressure_sensor_ok.to_string(),
+    status.health.microcontroller_ok.to_string(),
+  ]
+}
+
+// The name an enum is serialized with, e.g. `warn` for `LogLevel::Warn`.
+fn name(value: &impl Serialize) -> String {
+  serde_json::to_value(value)
+    .ok()
+    .and_then(|value| value.as_str().map(str::to_string))
+    .unwrap_or_default()
+}
+
+fn csv_field(field: &str) -> String {
+  if field.contains([',', '"', '\n', '\r']) {
+    format!("\"{}\"", field.replace('"', "\"\""))
+  } else {
+    field.to_string()
+  }
+}
@@ -0,0 +1,556 @@
+use crate::events::{
+  DIVE_REPLAY_STATE, LOG_MESSAGE, ROV_CONNECTION_STATE_CHANGED, ROV_CONNECTION_STATUS_UPDATED,
+  ROV_STATUS_UPDATE, ROV_TELEMETRY,
+};
+use crate::log_warn;
+use crate::models::connection::{ConnectionPhase, ConnectionState, ConnectionStatus};
+use crate::models::dive::{DiveRecord, DiveReplayState, DiveSample};
+use crate::models::log::{LogEntry, LogLevel, LogOrigin};
+use crate::models::rov_status::{RovStatus, SystemHealth};
+use crate::models::rov_telemetry::RovTelemetry;
+use crate::models::unknown::UnknownFields;
+use crate::util::lock;
+use crate::websocket::direction_vector::NEUTRAL_DIRECTION_VECTOR;
+use crate::websocket::handler::{HandlerRegistry, MessageHandler, FRONTEND_ORDER};
+use crate::websocket::manager::ConnectionManager;
+use crate::websocket::vehicle::VehicleEmitter;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::async_runtime::{spawn, JoinHandle};
+use tauri::{AppHandle, Manager, Runtime, Wry};
+use tokio::sync::Notify;
+use tokio::time::{sleep_until, Instant};
+
+pub const MIN_DIVE_REPLAY_SPEED: f64 = 0.25;
+pub const MAX_DIVE_REPLAY_SPEED: f64 = 8.0;
+
+pub fn check_dive_replay_speed(speed: f64) -> Result<f64, String> {
+  if !(MIN_DIVE_REPLAY_SPEED..=MAX_DIVE_REPLAY_SPEED).contains(&speed) {
+    return Err(format!(
+      "Replay speed must be between {} and {}, got {}",
+      MIN_DIVE_REPLAY_SPEED, MAX_DIVE_REPLAY_SPEED, speed
+    ));
+  }
+  Ok(speed)
+}
+
+// Reads a dive recording in either format. Lines a crash cut short are
+// skipped.
+pub fn read_dive_recording(path: &Path) -> Result<Vec<DiveRecord>, String> {
+  let text = fs::read_to_string(path)
+    .map_err(|e| format!("Failed to read dive recording {}: {}", path.display(), e))?;
+  let records = match path.extension().and_then(|extension| extension.to_str()) {
+    Some("jsonl") => text
+      .lines()
+      .filter(|line| !line.trim().is_empty())
+      .filter_map(|line| match serde_json::from_str(line) {
+        Ok(record) => Some(record),
+        Err(e) => {
+          log_warn!("Skipping dive record in {}: {}", path.display(), e);
+          None
+        }
+      })
+      .collect(),
+    Some("csv") => csv_records(&text),
+    _ => return Err(format!("{} is not a dive recording", path.display())),
+  };
+  if records.is_empty() {
+    return Err(format!("Dive recording {} is empty", path.display()));
+  }
+  Ok(records)
+}
+
+fn csv_records(text: &str) -> Vec<DiveRecord> {
+  let mut rows = csv_rows(text).into_iter();
+  let Some(header) = rows.next() else {
+    return Vec::new();
+  };
+  let columns: HashMap<String, usize> = header
+    .into_iter()
+    .enumerate()
+    .map(|(index, name)| (name, index))
+    .collect();
+  rows
+    .filter_map(|row| {
+      let field = |name: &str| {
+        columns
+          .get(name)
+          .and_then(|index| row.get(*index))
+          .map(String::as_str)
+          .unwrap_or_default()
+      };
+      let sample = csv_sample(field("kind"), &field)?;
+      Some(DiveRecord {
+        time_ms: field("time_ms").parse().ok()?,
+        sample,
+      })
+    })
+    .collect()
+}
+
+fn csv_sample<'a>(kind: &str, field: &dyn Fn(&str) -> &'a str) -> Option<DiveSample> {
+  fn parse<T: FromStr>(value: &str) -> Option<T> {
+    value.parse().ok()
+  }
+  Some(match kind {
+    "telemetry" => {
+      let mut thruster_rpms = [0.0; 8];
+      for (index, rpm) in thruster_rpms.iter_mut().enumerate() {
+        *rpm = parse(field(&format!("thruster_rpm_{}", index + 1)))?;
+      }
+      DiveSample::Telemetry(RovTelemetry {
+        pitch: parse(field("pitch"))?,
+        roll: parse(field("roll"))?,
+        desired_pitch: parse(field("desired_pitch"))?,
+        desired_roll: parse(field("desired_roll"))?,
+        depth: parse(field("depth"))?,
+        water_temperature: parse(field("water_temperature")),
+        electronics_temperature: parse(field("electronics_temperature")),
+        thruster_rpms,
+        work_indicator_percentage: parse(field("work_indicator_percentage"))?,
+        unknown_fields: UnknownFields::default(),
+      })
+    }
+    "status" => DiveSample::Status(RovStatus {
+      pitch_stabilization: parse(field("pitch_stabilization"))?,
+      roll_stabilization: parse(field("roll_stabilization"))?,
+      depth_hold: parse(field("depth_hold"))?,
+      battery_percentage: parse(field("battery_percentage"))?,
+      health: SystemHealth {
+        imu_ok: parse(field("imu_ok"))?,
+        pressure_sensor_ok: parse(field("pressure_sensor_ok"))?,
+        microcontroller_ok: parse(field("microcontroller_ok"))?,
+        unknown_fields: UnknownFields::default(),
+      },
+      unknown_fields: UnknownFields::default(),
+    }),
+    // Only the ROV's own logs are recorded.
+    "log" => DiveSample::Log(LogEntry {
+      level: serde_json::from_value::<LogLevel>(Value::from(field("level"))).ok()?,
+      origin: LogOrigin::Firmware,
+      message: field("message").to_string(),
+      unknown_fields: UnknownFields::default(),
+    }),
+    "control" => DiveSample::Control(serde_json::from_str(field("message")).ok()?),
+    _ => return None,
+  })
+}
+
+// Splits CSV into rows of fields, undoing the quoting of the recorder.
+fn csv_rows(text: &str) -> Vec<Vec<String>> {
+  let mut rows = Vec::new();
+  let mut row = Vec::new();
+  let mut field = String::new();
+  let mut quoted = false;
+  let mut chars = text.chars().peekable();
+  while let Some(c) = chars.next() {
+    match (quoted, c) {
+      (true, '"') if chars.peek() == Some(&'"') => {
+        chars.next();
+        field.push('"');
+      }
+      (true, '"') => quoted = false,
+      (true, c) => field.push(c),
+      (false, '"') => quoted = true,
+      (false, ',') => row.push(std::mem::take(&mut field)),
+      (false, '\r') => {}
+      (false, '\n') => {
+        row.push(std::mem::take(&mut field));
+        rows.push(std::mem::take(&mut row));
+      }
+      (false, c) => field.push(c),
+    }
+  }
+  rows
+}
+
+struct Playback {
+  speed: f64,
+  paused: bool,
+  // The recording was at `position_ms` at `at`.
+  position_ms: u64,
+  at: Instant,
+  finished: bool,
+  // Bumped by every seek, for the replay to pick up from the new position.
+  seeks: u64,
+}
+
+impl Playback {
+  fn position_ms(&self) -> u64 {
+    if self.paused || self.finished {
+      return self.position_ms;
+    }
+    self.position_ms + (self.at.elapsed().as_secs_f64() * self.speed * 1000.0) as u64
+  }
+
+  fn due(&self, offset_ms: u64) -> Instant {
+    let ahead_ms = offset_ms.saturating_sub(self.position_ms) as f64 / self.speed;
+    self.at + Duration::from_secs_f64(ahead_ms / 1000.0)
+  }
+
+  // Moves the reference point to now, before the speed or pause changes.
+  fn settle(&mut self) {
+    self.position_ms = self.position_ms();
+    self.at = Instant::now();
+  }
+}
+
+struct Shared<R: Runtime> {
+  emitter: VehicleEmitter<R>,
+  path: String,
+  duration_ms: u64,
+  playback: Mutex<Playback>,
+  changed: Notify,
+}
+
+impl<R: Runtime> Shared<R> {
+  fn state(&self) -> DiveReplayState {
+    let playback = lock(&self.playback);
+    DiveReplayState {
+      path: self.path.clone(),
+      vehicle_id: self.emitter.id.clone(),
+      speed: playback.speed,
+      paused: playback.paused,
+      position_ms: playback.position_ms().min(self.duration_ms),
+      duration_ms: self.duration_ms,
+      finished: playback.finished,
+    }
+  }
+
+  fn emit_state(&self) -> DiveReplayState {
+    let state = self.state();
+    self.emit(DIVE_REPLAY_STATE, state.clone());
+    state
+  }
+
+  fn replaying(&self) -> String {
+    let name = Path::new(&self.path)
+      .file_name()
+      .map(|name| name.to_string_lossy().into_owned())
+      .unwrap_or_default();
+    format!("Replaying {}", name)
+  }
+
+  fn emit_connection(&self, phase: ConnectionPhase, reason: String) {
+    let state = ConnectionState {
+      phase,
+      reason,
+      attempt: 0,
+      retry_in_ms: None,
+    };
+    self.emit(ROV_CONNECTION_STATE_CHANGED, state);
+    let status = ConnectionStatus {
+      is_connected: phase.is_connected(),
+      delay: None,
+    };
+    self.emit(ROV_CONNECTION_STATUS_UPDATED, status);
+  }
+
+  fn emit<S: serde::Serialize + Clone>(&self, event: &str, payload: S) {
+    if let Err(e) = self.emitter.emit_replay(event, payload) {
+      log_warn!("Failed to emit replayed {}: {}", event, e);
+    }
+  }
+}
+
+// Plays a dive recording back on the events the vehicle's own readings go out
+// on, flagged as replayed. Every one of them is emitted, as fast as it was
+// recorded times the speed.
+pub struct DiveReplay<R: Runtime> {
+  shared: Arc<Shared<R>>,
+  task: JoinHandle<()>,
+  // Whether the vehicle is still replayed is up to the latest replay of it.
+  claim: u64,
+}
+
+impl<R: Runtime> DiveReplay<R> {
+  pub fn start(
+    emitter: VehicleEmitter<R>,
+    path: &Path,
+    records: Vec<DiveRecord>,
+    speed: f64,
+  ) -> Result<Self, String> {
+    let speed = check_dive_replay_speed(speed)?;
+    let first_ms = records.first().map_or(0, |record| record.time_ms);
+    let last_ms = records.last().map_or(0, |record| record.time_ms);
+    let shared = Arc::new(Shared {
+      emitter,
+      path: path.to_string_lossy().into_owned(),
+      duration_ms: last_ms.saturating_sub(first_ms),
+      playback: Mutex::new(Playback {
+        speed,
+        paused: false,
+        position_ms: 0,
+        at: Instant::now(),
+        finished: false,
+        seeks: 0,
+      }),
+      changed: Notify::new(),
+    });
+    let emitter = &shared.emitter;
+    let claim = emitter.replayed.claim(emitter.id.clone());
+    shared.emit_connection(ConnectionPhase::Connected, shared.replaying());
+    shared.emit_state();
+    let task = spawn(play(shared.clone(), records));
+    Ok(DiveReplay {
+      shared,
+      task,
+      claim,
+    })
+  }
+
+  pub fn vehicle_id(&self) -> &str {
+    &self.shared.emitter.id
+  }
+
+  pub fn state(&self) -> DiveReplayState {
+    self.shared.state()
+  }
+
+  pub fn pause(&self, paused: bool) -> DiveReplayState {
+    self.update(|playback| {
+      playback.settle();
+      playback.paused = paused;
+    })
+  }
+
+  pub fn set_speed(&self, speed: f64) -> Result<DiveReplayState, String> {
+    let speed = check_dive_replay_speed(speed)?;
+    Ok(self.update(|playback| {
+      playback.settle();
+      playback.speed = speed;
+    }))
+  }
+
+  // Jumps to `position_ms` into the recording, passing on the readings the
+  // frontend would have shown there.
+  pub fn seek(&self, position_ms: u64) -> DiveReplayState {
+    let position_ms = position_ms.min(self.shared.duration_ms);
+    if lock(&self.shared.playback).finished {
+      self
+        .shared
+        .emit_connection(ConnectionPhase::Connected, self.shared.replaying());
+    }
+    self.update(|playback| {
+      playback.position_ms = position_ms;
+      playback.at = Instant::now();
+      playback.finished = false;
+      playback.seeks += 1;
+    })
+  }
+
+  pub fn stop(self) -> DiveReplayState {
+    self.task.abort();
+    self
+      .shared
+      .emit_connection(ConnectionPhase::Disconnected, "Replay stopped".to_string());
+    self.shared.state()
+  }
+
+  fn update(&self, update: impl FnOnce(&mut Playback)) -> DiveReplayState {
+    update(&mut lock(&self.shared.playback));
+    self.shared.changed.notify_one();
+    self.shared.emit_state()
+  }
+}
+
+impl<R: Runtime> Drop for DiveReplay<R> {
+  fn drop(&mut self) {
+    self.task.abort();
+    self.shared.emitter.replayed.release(self.claim);
+  }
+}
+
+// The dive replay in progress, at most one at a time.
+pub struct ActiveDiveReplay<R: Runtime = Wry> {
+  replay: Mutex<Option<DiveReplay<R>>>,
+}
+
+impl<R: Runtime> Default for ActiveDiveReplay<R> {
+  fn default() -> Self {
+    ActiveDiveReplay {
+      replay: Mutex::new(None),
+    }
+  }
+}
+
+impl<R: Runtime> ActiveDiveReplay<R> {
+  // Plays a dive recording back as a vehicle, the pilot vehicle by default,
+  // replacing the replay in progress. Until the replay is stopped the vehicle
+  // is held neutral, takes no control commands and its own events are held
+  // back.
+  pub fn start(
+    &self,
+    manager: &ConnectionManager<R>,
+    vehicle: Option<&str>,
+    path: &Path,
+    speed: f64,
+  ) -> Result<DiveReplayState, String> {
+    let speed = check_dive_replay_speed(speed)?;
+    let records = read_dive_recording(path)?;
+    let emitter = manager.emitter(vehicle)?;
+    let vehicle = manager.get(Some(&emitter.id))?;
+    // Held throughout, so a replay started at the same time can't slip in
+    // between and get dropped while it plays.
+    let mut active = lock(&self.replay);
+    let _ = stop(&mut active, manager);
+    let _ = vehicle.direction_vectors.tx.send(NEUTRAL_DIRECTION_VECTOR);
+    let replay = DiveReplay::start(emitter, path, records, speed)?;
+    let state = replay.state();
+    *active = Some(replay);
+    Ok(state)
+  }
+
+  pub fn stop(&self, manager: &ConnectionManager<R>) -> Result<DiveReplayState, String> {
+    stop(&mut lock(&self.replay), manager)
+  }
+
+  pub fn pause(&self, paused: bool) -> Result<DiveReplayState, String> {
+    self.with_replay(|replay| Ok(replay.pause(paused)))
+  }
+
+  pub fn seek(&self, position_ms: u64) -> Result<DiveReplayState, String> {
+    self.with_replay(|replay| Ok(replay.seek(position_ms)))
+  }
+
+  pub fn set_speed(&self, speed: f64) -> Result<DiveReplayState, String> {
+    self.with_replay(|replay| replay.set_speed(speed))
+  }
+
+  pub fn state(&self) -> Option<DiveReplayState> {
+    lock(&self.replay).as_ref().map(DiveReplay::state)
+  }
+
+  // Stops the replay of a vehicle that is closed.
+  pub fn remove(&self, vehicle: &str) {
+    let mut replay = lock(&self.replay);
+    if replay
+      .as_ref()
+      .is_some_and(|replay| replay.vehicle_id() == vehicle)
+    {
+      if let Some(replay) = replay.take() {
+        replay.stop();
+      }
+    }
+  }
+
+  fn with_replay<T>(
+    &self,
+    action: impl FnOnce(&DiveReplay<R>) -> Result<T, String>,
+  ) -> Result<T, String> {
+    match lock(&self.replay).as_ref() {
+      Some(replay) => action(replay),
+      None => Err("No dive replay in progress".to_string()),
+    }
+  }
+}
+
+// Stops the replay and passes on the vehicle's own connection state again.
+fn stop<R: Runtime>(
+  active: &mut Option<DiveReplay<R>>,
+  manager: &ConnectionManager<R>,
+) -> Result<DiveReplayState, String> {
+  let replay = active
+    .take()
+    .ok_or_else(|| "No dive replay in progress".to_string())?;
+  let state = replay.stop();
+  manager.announce_connection(&state.vehicle_id);
+  Ok(state)
+}
+
+struct DiveReplayHandler<R: Runtime> {
+  replay: Arc<ActiveDiveReplay<R>>,
+}
+
+impl<R: Runtime> MessageHandler<R> for DiveReplayHandler<R> {
+  fn closed(&self, vehicle: &str) -> Result<(), String> {
+    self.replay.remove(vehicle);
+    Ok(())
+  }
+}
+
+// Plays dives back, managed as `Arc<ActiveDiveReplay>`.
+pub fn register<R: Runtime>(app: &AppHandle<R>, handlers: &HandlerRegistry<R>) {
+  let replay = Arc::new(ActiveDiveReplay::default());
+  handlers.register(
+    "dive_replay",
+    FRONTEND_ORDER,
+    DiveReplayHandler {
+      replay: replay.clone(),
+    },
+  );
+  app.manage(replay);
+}
+
+async fn play<R: Runtime>(shared: Arc<Shared<R>>, records: Vec<DiveRecord>) {
+  let first_ms = records.first().map_or(0, |record| record.time_ms);
+  let offset_ms = |record: &DiveRecord| record.time_ms.saturating_sub(first_ms);
+  let mut next = 0;
+  let mut seeks = 0;
+  loop {
+    let due = {
+      let mut playback = lock(&shared.playback);
+      if playback.seeks != seeks {
+        seeks = playback.seeks;
+        next = records.partition_point(|record| offset_ms(record) < playback.position_ms);
+        drop(playback);
+        emit_latest_readings(&shared, &records[..next]);
+        continue;
+      }
+      match records.get(next) {
+        Some(record) if !playback.paused => Some(playback.due(offset_ms(record))),
+        Some(_) => None,
+        None if playback.finished => None,
+        None => {
+          playback.settle();
+          playback.position_ms = shared.duration_ms;
+          playback.finished = true;
+          drop(playback);
+          shared.emit_state();
+          shared.emit_connection(ConnectionPhase::Disconnected, "Replay finished".to_string());
+          continue;
+        }
+      }
+    };
+
+    match due {
+      Some(due) => tokio::select! {
+        _ = sleep_until(due) => {
+          emit_record(&shared, &records[next]);
+          next += 1;
+        }
+        _ = shared.changed.notified() => {}
+      },
+      None => shared.changed.notified().await,
+    }
+  }
+}
+
+fn emit_record<R: Runtime>(shared: &Shared<R>, record: &DiveRecord) {
+  match &record.sample {
+    DiveSample::Telemetry(telemetry) => shared.emit(ROV_TELEMETRY, telemetry),
+    DiveSample::Status(status) => shared.emit(ROV_STATUS_UPDATE, status),
+    DiveSample::Log(entry) => shared.emit(LOG_MESSAGE, entry),
+    // What was sent to the ROV stays in the recording.
+    DiveSample::Control(_) => {}
+  }
+}
+
+// The telemetry and status the frontend shows after `records`.
+fn emit_latest_readings<R: Runtime>(shared: &Shared<R>, records: &[DiveRecord]) {
+  let latest = |telemetry: bool| {
+    records.iter().rev().find(|record| match record.sample {
+      DiveSample::Telemetry(_) => telemetry,
+      DiveSample::Status(_) => !telemetry,
+      _ => false,
+    })
+  };
+  for record in [latest(false), latest(true)].into_iter().flatten() {
+    emit_record(shared, record);
+  }
+}
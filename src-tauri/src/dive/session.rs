@@ -0,0 +1,420 @@
+use crate::log_warn;
+use crate::models::config::Config;
+use crate::models::dive::{DiveSession, DiveSummary};
+use crate::models::handshake::Handshake;
+use crate::models::rov_config::RovConfig;
+use crate::models::rov_status::RovStatus;
+use crate::models::rov_telemetry::RovTelemetry;
+use crate::models::vehicle::VehicleId;
+use crate::util::{lock, now_ms};
+use crate::websocket::capture::safe_file_name;
+use crate::websocket::handler::{HandlerRegistry, HandlerResult, MessageHandler, FRONTEND_ORDER};
+use crate::websocket::message::{WebsocketEnvelope, WebsocketMessage};
+use crate::websocket::vehicle::VehicleEmitter;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager, Runtime};
+
+#[derive(Clone, Default)]
+struct Settings {
+  directory: PathBuf,
+  on_connect: bool,
+  operator: String,
+  site: String,
+}
+
+// What the stabilization and depth hold were set to by a status update.
+struct Modes {
+  since: Instant,
+  pitch_stabilization: bool,
+  roll_stabilization: bool,
+  depth_hold: bool,
+}
+
+// The readings of a dive so far, summed up when it is stopped.
+#[derive(Default)]
+struct Totals {
+  max_depth: Option<f32>,
+  depth_sum: f64,
+  depth_samples: u64,
+  water_temperature: Option<(f32, f32)>,
+  battery: Option<(u8, u8)>,
+  modes: Option<Modes>,
+  pitch_stabilization: Duration,
+  roll_stabilization: Duration,
+  depth_hold: Duration,
+}
+
+impl Totals {
+  fn telemetry(&mut self, telemetry: &RovTelemetry) {
+    self.max_depth = Some(
+      self
+        .max_depth
+        .map_or(telemetry.depth, |max| max.max(telemetry.depth)),
+    );
+    self.depth_sum += telemetry.depth as f64;
+    self.depth_samples += 1;
+    if let Some(temperature) = telemetry.water_temperature {
+      self.water_temperature = Some(match self.water_temperature {
+        Some((min, max)) => (min.min(temperature), max.max(temperature)),
+        None => (temperature, temperature),
+      });
+    }
+  }
+
+  fn status(&mut self, status: &RovStatus) {
+    self.accrue_modes();
+    let first = self
+      .battery
+      .map_or(status.battery_percentage, |(first, _)| first);
+    self.battery = Some((first, status.battery_percentage));
+    self.modes = Some(Modes {
+      since: Instant::now(),
+      pitch_stabilization: status.pitch_stabilization,
+      roll_stabilization: status.roll_stabilization,
+      depth_hold: status.depth_hold,
+    });
+  }
+
+  // Adds the time since the last status update to the modes it turned on.
+  fn accrue_modes(&mut self) {
+    let Some(modes) = &mut self.modes else {
+      return;
+    };
+    let elapsed = modes.since.elapsed();
+    modes.since = Instant::now();
+    if modes.pitch_stabilization {
+      self.pitch_stabilization += elapsed;
+    }
+    if modes.roll_stabilization {
+      self.roll_stabilization += elapsed;
+    }
+    if modes.depth_hold {
+      self.depth_hold += elapsed;
+    }
+  }
+
+  fn summary(mut self, duration: Duration) -> DiveSummary {
+    self.accrue_modes();
+    DiveSummary {
+      duration_ms: duration.as_millis() as u64,
+      max_depth: self.max_depth,
+      average_depth: (self.depth_samples > 0)
+        .then(|| (self.depth_sum / self.depth_samples as f64) as f32),
+      min_water_temperature: self.water_temperature.map(|(min, _)| min),
+      max_water_temperature: self.water_temperature.map(|(_, max)| max),
+      battery_used: self.battery.map(|(first, last)| first.saturating_sub(last)),
+      pitch_stabilization_ms: self.pitch_stabilization.as_millis() as u64,
+      roll_stabilization_ms: self.roll_stabilization.as_millis() as u64,
+      depth_hold_ms: self.depth_hold.as_millis() as u64,
+    }
+  }
+}
+
+struct OpenSession {
+  session: DiveSession,
+  path: PathBuf,
+  started: Instant,
+  totals: Totals,
+}
+
+impl OpenSession {
+  fn save(&self) -> io::Result<()> {
+    save(&self.path, &self.session)
+  }
+}
+
+// What is known of a vehicle for the next dive, and the dive it is on.
+#[derive(Default)]
+struct VehicleDives {
+  firmware_version: Option<String>,
+  rov_config: Option<RovConfig>,
+  open: Option<OpenSession>,
+}
+
+// The dive sessions of every vehicle, saved as `<vehicle>-<start>.json` in the
+// dive directory when they open and again when they are stopped.
+#[derive(Default)]
+pub struct DiveSessions {
+  settings: Mutex<Settings>,
+  vehicles: Mutex<BTreeMap<VehicleId, VehicleDives>>,
+}
+
+impl DiveSessions {
+  pub fn configure(&self, config: &Config) {
+    *lock(&self.settings) = Settings {
+      directory: config.dive_directory(),
+      on_connect: config.dive_session_on_connect,
+      operator: config.dive_operator.clone(),
+      site: config.dive_site.clone(),
+    };
+  }
+
+  // Opens a session for the vehicle, with the operator and site of the config
+  // unless given.
+  pub fn start(
+    &self,
+    vehicle: &str,
+    operator: Option<String>,
+    site: Option<String>,
+  ) -> Result<DiveSession, String> {
+    let settings = lock(&self.settings).clone();
+    let mut vehicles = lock(&self.vehicles);
+    let dives = vehicles.entry(vehicle.to_string()).or_default();
+    if dives.open.is_some() {
+      return Err(format!("Vehicle {} is already on a dive", vehicle));
+    }
+
+    let started_ms = now_ms();
+    let id = format!("{}-{}", safe_file_name(vehicle), started_ms);
+    let open = OpenSession {
+      path: settings.directory.join(format!("{}.json", id)),
+      session: DiveSession {
+        id,
+        vehicle_id: vehicle.to_string(),
+        operator: operator.unwrap_or(settings.operator),
+        site: site.unwrap_or(settings.site),
+        firmware_version: dives.firmware_version.clone(),
+        rov_config: dives.rov_config.clone(),
+        started_ms,
+        ended_ms: None,
+        recordings: Vec::new(),
+        data_files: Vec::new(),
+        summary: None,
+      },
+      started: Instant::now(),
+      totals: Totals::default(),
+    };
+    open
+      .save()
+      .map_err(|e| format!("Failed to save dive session: {}", e))?;
+    let session = open.session.clone();
+    dives.open = Some(open);
+    Ok(session)
+  }
+
+  pub fn stop(&self, vehicle: &str) -> Result<DiveSession, String> {
+    let mut open = lock(&self.vehicles)
+      .get_mut(vehicle)
+      .and_then(|dives| dives.open.take())
+      .ok_or_else(|| format!("Vehicle {} is not on a dive", vehicle))?;
+    open.session.ended_ms = Some(now_ms());
+    open.session.summary = Some(open.totals.summary(open.started.elapsed()));
+    save(&open.path, &open.session).map_err(|e| format!("Failed to save dive session: {}", e))?;
+    Ok(open.session)
+  }
+
+  pub fn current(&self, vehicle: &str) -> Option<DiveSession> {
+    lock(&self.vehicles)
+      .get(vehicle)?
+      .open
+      .as_ref()
+      .map(|open| open.session.clone())
+  }
+
+  // The sessions in the dive directory, oldest first. Open sessions are
+  // listed as they are now rather than as last saved.
+  pub fn list(&self) -> Result<Vec<DiveSession>, String> {
+    let directory = lock(&self.settings).directory.clone();
+    let entries = match fs::read_dir(&directory) {
+      Ok(entries) => entries,
+      Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+      Err(e) => return Err(format!("Failed to list dive sessions: {}", e)),
+    };
+    let open: BTreeMap<PathBuf, DiveSession> = lock(&self.vehicles)
+      .values()
+      .filter_map(|dives| dives.open.as_ref())
+      .map(|open| (open.path.clone(), open.session.clone()))
+      .collect();
+
+    let mut sessions: Vec<DiveSession> = entries
+      .filter_map(|entry| {
+        let path = entry.ok()?.path();
+        if path.extension()? != "json" {
+          return None;
+        }
+        if let Some(session) = open.get(&path) {
+          return Some(session.clone());
+        }
+        let text = fs::read_to_string(&path).ok()?;
+        match serde_json::from_str(&text) {
+          Ok(session) => Some(session),
+          Err(e) => {
+            log_warn!("Skipping dive session {}: {}", path.display(), e);
+            None
+          }
+        }
+      })
+      .collect();
+    sessions.sort_by(|a, b| (a.started_ms, &a.id).cmp(&(b.started_ms, &b.id)));
+    Ok(sessions)
+  }
+
+  // Links a saved video to the dive of every vehicle on one.
+  pub fn link_recording(&self, path: &str) {
+    for open in lock(&self.vehicles)
+      .values_mut()
+      .filter_map(|dives| dives.open.as_mut())
+    {
+      open.session.recordings.push(path.to_string());
+      if let Err(e) = open.save() {
+        log_warn!("Failed to save dive session: {}", e);
+      }
+    }
+  }
+
+  pub fn link_data_file(&self, vehicle: &str, path: &str) {
+    let mut vehicles = lock(&self.vehicles);
+    let Some(open) = vehicles
+      .get_mut(vehicle)
+      .and_then(|dives| dives.open.as_mut())
+    else {
+      return;
+    };
+    open.session.data_files.push(path.to_string());
+    if let Err(e) = open.save() {
+      log_warn!("Failed to save dive session: {}", e);
+    }
+  }
+
+  // Records the firmware of a vehicle and opens a session for it when
+  // `dive_session_on_connect` is set. A reconnect stays on the same dive.
+  pub fn connected(&self, vehicle: &str, handshake: &Handshake) -> Result<(), String> {
+    if let Some(version) = &handshake.firmware_version {
+      self.firmware_received(vehicle, version);
+    }
+    let on_connect = lock(&self.settings).on_connect;
+    if on_connect && self.current(vehicle).is_none() {
+      self.start(vehicle, None, None)?;
+    }
+    Ok(())
+  }
+
+  pub fn firmware_received(&self, vehicle: &str, version: &str) {
+    let mut vehicles = lock(&self.vehicles);
+    let dives = vehicles.entry(vehicle.to_string()).or_default();
+    dives.firmware_version = Some(version.to_string());
+    if let Some(open) = &mut dives.open {
+      open.session.firmware_version = Some(version.to_string());
+    }
+  }
+
+  pub fn rov_config_received(&self, vehicle: &str, rov_config: &RovConfig) {
+    let mut vehicles = lock(&self.vehicles);
+    let dives = vehicles.entry(vehicle.to_string()).or_default();
+    dives.rov_config = Some(rov_config.clone());
+    if let Some(open) = &mut dives.open {
+      open.session.rov_config = Some(rov_config.clone());
+    }
+  }
+
+  // Stops the dive of a vehicle that is closed.
+  pub fn remove(&self, vehicle: &str) {
+    if self.current(vehicle).is_some() {
+      if let Err(e) = self.stop(vehicle) {
+        log_warn!("{}", e);
+      }
+    }
+    lock(&self.vehicles).remove(vehicle);
+  }
+
+  fn update(&self, vehicle: &str, update: impl FnOnce(&mut Totals)) {
+    let mut vehicles = lock(&self.vehicles);
+    if let Some(open) = vehicles
+      .get_mut(vehicle)
+      .and_then(|dives| dives.open.as_mut())
+    {
+      update(&mut open.totals);
+    }
+  }
+}
+
+// Feeds a vehicle's readings, config and firmware to its dive sessions.
+pub struct DiveSessionHandler {
+  pub sessions: Arc<DiveSessions>,
+}
+
+impl<R: Runtime> MessageHandler<R> for DiveSessionHandler {
+  fn subscribes_to(&self, message: &WebsocketMessage) -> bool {
+    matches!(
+      message,
+      WebsocketMessage::Telemetry(_)
+        | WebsocketMessage::StatusUpdate(_)
+        | WebsocketMessage::Config(_)
+        | WebsocketMessage::FirmwareVersion(_)
+    )
+  }
+
+  fn handle(&self, vehicle: &VehicleEmitter<R>, envelope: &WebsocketEnvelope) -> HandlerResult {
+    match &envelope.message {
+      WebsocketMessage::Telemetry(telemetry) => self
+        .sessions
+        .update(&vehicle.id, |totals| totals.telemetry(telemetry)),
+      WebsocketMessage::StatusUpdate(status) => self
+        .sessions
+        .update(&vehicle.id, |totals| totals.status(status)),
+      WebsocketMessage::Config(rov_config) => {
+        self.sessions.rov_config_received(&vehicle.id, rov_config)
+      }
+      WebsocketMessage::FirmwareVersion(version) => {
+        self.sessions.firmware_received(&vehicle.id, version)
+      }
+      _ => {}
+    }
+    Ok(Vec::new())
+  }
+
+  fn connected(&self, vehicle: &VehicleEmitter<R>, handshake: &Handshake) -> Result<(), String> {
+    self.sessions.connected(&vehicle.id, handshake)
+  }
+
+  fn configured(&self, _vehicles: &[VehicleEmitter<R>], config: &Config) -> Result<(), String> {
+    self.sessions.configure(config);
+    Ok(())
+  }
+
+  // Until the ROV reports it, a dive has the config its profile last saw.
+  fn opened(
+    &self,
+    vehicle: &VehicleEmitter<R>,
+    rov_config: Option<&RovConfig>,
+  ) -> Result<(), String> {
+    if let Some(rov_config) = rov_config {
+      self.sessions.rov_config_received(&vehicle.id, rov_config);
+    }
+    Ok(())
+  }
+
+  fn closed(&self, vehicle: &str) -> Result<(), String> {
+    self.sessions.remove(vehicle);
+    Ok(())
+  }
+}
+
+// Keeps the dives of every vehicle, managed as `Arc<DiveSessions>`.
+pub fn register<R: Runtime>(app: &AppHandle<R>, handlers: &HandlerRegistry<R>) {
+  let sessions = Arc::new(DiveSessions::default());
+  handlers.register(
+    "dive_session",
+    FRONTEND_ORDER,
+    DiveSessionHandler {
+      sessions: sessions.clone(),
+    },
+  );
+  app.manage(sessions);
+}
+
+// Written next to the session and moved over it, so a crash never leaves half
+// a session behind.
+fn save(path: &Path, session: &DiveSession) -> io::Result<()> {
+  if let Some(directory) = path.parent() {
+    fs::create_dir_all(directory)?;
+  }
+  let partial = path.with_extension("json.partial");
+  fs::write(&partial, serde_json::to_vec_pretty(session)?)?;
+  fs::rename(&partial, path)
+}
@@ -1,3 +1,4 @@
+use crate::events::GAMEPAD_EVENT;
 use crate::models::gamepad::{GamepadData, GamepadEventType};
 use gilrs::{Axis, Button, Event, EventType, Gamepad, Gilrs, MappingSource};
 use std::time::{SystemTime, UNIX_EPOCH};
@@ -139,7 +140,7 @@ pub fn handle_start_gamepad_stream<R: Runtime>(app: AppHandle<R>) {
     {
       let gamepad = gilrs.gamepad(id);
       let payload = gamepad_to_json(gamepad, event, time);
-      app.emit("gamepad_event", payload).unwrap();
+      app.emit(GAMEPAD_EVENT, payload).unwrap();
     }
   }
 }
@@ -1,36 +1,65 @@
 mod commands {
   pub mod actions;
+  pub mod alarms;
+  pub mod battery;
+  pub mod capture;
   pub mod config;
+  pub mod connection;
+  pub mod discovery;
+  pub mod dive;
   pub mod gamepad;
+  pub mod handshake;
+  pub mod link_quality;
+  pub mod outbound;
   pub mod rov_config;
+  pub mod rov_profiles;
+  pub mod telemetry_history;
+  pub mod telemetry_ui;
+  pub mod vehicles;
 }
 
-mod models {
-  pub mod actions;
-  pub mod config;
-  pub mod gamepad;
-  pub mod log;
-  pub mod rov_config;
-  pub mod rov_status;
-  pub mod rov_telemetry;
-  pub mod toast;
-}
-
-mod websocket {
+pub mod websocket {
+  pub use manafish_protocol::message;
+  pub mod capture;
   pub mod client;
+  pub mod connection;
+  pub mod direction_vector;
   pub mod handler;
-  pub mod message;
+  pub mod handshake;
+  pub mod link_quality;
+  pub mod manager;
+  pub mod outbound;
+  pub mod replay;
+  pub mod request;
   pub mod receive {
     pub mod config;
     pub mod log;
+    pub mod reply;
     pub mod status;
     pub mod telemetry;
     pub mod toast;
+    pub mod unknown;
   }
   pub mod send {
     pub mod actions;
     pub mod config;
   }
+  pub mod telemetry_history;
+  pub mod telemetry_ui;
+  pub mod tls;
+  pub mod vehicle;
+  pub mod watchdog;
+}
+
+pub use manafish_protocol::{events, models};
+
+pub mod alarms;
+pub mod battery;
+pub mod discovery;
+pub mod dive {
+  pub mod recorder;
+  pub mod replay;
+  pub mod session;
 }
 
 mod config;
@@ -38,29 +67,61 @@ mod gamepad;
 mod log;
 mod toast;
 mod updater;
+mod util;
 
 use commands::actions::{
-  append_recording_chunk, save_recording, send_custom_action, send_direction_vector, toggle_depth_hold,
-  toggle_pitch_stabilization, toggle_roll_stabilization,
+  append_recording_chunk, get_direction_vector_stats, save_recording, send_custom_action,
+  send_direction_vector, toggle_depth_hold, toggle_pitch_stabilization, toggle_roll_stabilization,
 };
+use commands::alarms::{acknowledge_alarm, list_alarms};
+use commands::battery::get_battery_estimate;
+use commands::capture::{replay_capture, stop_capture_replay};
 use commands::config::{get_config, set_config};
+use commands::connection::{connect, disconnect, forget_rov_certificate, get_connection_state};
+use commands::discovery::discover_rovs;
+use commands::dive::{
+  get_dive_replay_state, list_dive_recordings, list_dive_sessions, pause_dive_replay,
+  resume_dive_replay, seek_dive_replay, set_dive_replay_speed, start_dive_recording,
+  start_dive_replay, start_dive_session, stop_dive_recording, stop_dive_replay, stop_dive_session,
+};
 use commands::gamepad::start_gamepad_stream;
+use commands::handshake::get_rov_handshake;
+use commands::link_quality::get_link_quality;
+use commands::outbound::{cancel_outbound_message, get_outbound_queue};
 use commands::rov_config::{
   cancel_regulator_auto_tuning, cancel_thruster_test, flash_microcontroller_firmware,
   request_rov_config, set_rov_config, start_regulator_auto_tuning, start_thruster_test,
 };
-use config::ConfigSendChannelState;
+use commands::rov_profiles::{
+  create_rov_profile, delete_rov_profile, switch_rov_profile, update_rov_profile,
+};
+use commands::telemetry_history::query_telemetry;
+use commands::telemetry_ui::get_telemetry_ui_stats;
+use commands::vehicles::{close_vehicle, list_vehicles, open_vehicle, set_pilot_vehicle};
+use config::{get_config_from_file, remember_certificate, ConfigSendChannelState};
+use discovery::{broadcast_targets, DiscoveredRovs, DiscoveryScanner};
 use log::log_init;
 use models::config::Config;
+use std::sync::Arc;
 use tauri::async_runtime::spawn;
-use tauri::{generate_handler, App, Builder, Manager};
+use tauri::{generate_handler, App, AppHandle, Builder, Manager, Runtime};
 use toast::toast_init;
 use tokio::sync::mpsc::channel;
 use updater::update_app;
-use websocket::client::{
-  start_websocket_client, DirectionVectorSendChannelState, MessageSendChannelState,
-};
-use websocket::message::WebsocketMessage;
+use websocket::handler::HandlerRegistry;
+use websocket::manager::{ConnectionManager, PRIMARY_VEHICLE};
+use websocket::replay::CaptureReplay;
+
+// Registers with `handlers` what watches the traffic of every vehicle, each
+// keeping its state as managed state of the app.
+pub fn register_subsystems<R: Runtime>(app: &AppHandle<R>, handlers: &HandlerRegistry<R>) {
+  websocket::telemetry_history::register(app, handlers);
+  alarms::register(app, handlers);
+  battery::register(app, handlers);
+  dive::session::register(app, handlers);
+  dive::recorder::register(app, handlers);
+  dive::replay::register(app, handlers);
+}
 
 fn setup_handlers(app: &mut App) -> Result<(), Box<dyn std::error::Error>> {
   let log_handle = app.app_handle().clone();
@@ -74,19 +135,46 @@ fn setup_handlers(app: &mut App) -> Result<(), Box<dyn std::error::Error>> {
     update_app(update_handle).await.unwrap();
   });
 
-  let websocket_handle = app.app_handle().clone();
-  let (config_tx, config_rx) = channel::<Config>(1);
-  app.manage(ConfigSendChannelState { tx: config_tx });
-  let (message_tx, message_rx) = channel::<WebsocketMessage>(1);
-  app.manage(MessageSendChannelState { tx: message_tx });
-  let (direction_vector_tx, direction_vector_rx) = channel::<WebsocketMessage>(8);
-  app.manage(DirectionVectorSendChannelState {
-    tx: direction_vector_tx,
+  let (config_tx, mut config_rx) = channel::<Config>(1);
+  app.manage(ConfigSendChannelState {
+    tx: config_tx.clone(),
+  });
+  let (certificates_tx, mut certificates_rx) = channel(1);
+  let manager = Arc::new(ConnectionManager::new(
+    app.app_handle().clone(),
+    certificates_tx,
+  ));
+  register_subsystems(app.app_handle(), &manager.handlers());
+  manager.open(PRIMARY_VEHICLE.to_string(), None, &get_config_from_file())?;
+  app.manage(manager.clone());
+  app.manage(CaptureReplay::default());
+
+  let pinning_manager = manager.clone();
+  spawn(async move {
+    let state = ConfigSendChannelState { tx: config_tx };
+    while let Some(trusted) = certificates_rx.recv().await {
+      let profile = pinning_manager
+        .get(Some(&trusted.vehicle_id))
+        .ok()
+        .and_then(|vehicle| vehicle.profile.clone());
+      remember_certificate(&state, profile.as_deref(), &trusted.fingerprint).await;
+    }
   });
   spawn(async move {
-    start_websocket_client(websocket_handle, config_rx, message_rx, direction_vector_rx).await;
+    while let Some(config) = config_rx.recv().await {
+      manager.apply_config(config).await;
+    }
   });
 
+  let discovered_rovs = Arc::new(DiscoveredRovs::default());
+  app.manage(discovered_rovs.clone());
+  let scanner = DiscoveryScanner {
+    app: app.app_handle().clone(),
+    targets: broadcast_targets(),
+    rovs: discovered_rovs,
+  };
+  spawn(scanner.run());
+
   Ok(())
 }
 
@@ -107,6 +195,7 @@ pub fn run() {
       start_regulator_auto_tuning,
       cancel_regulator_auto_tuning,
       send_direction_vector,
+      get_direction_vector_stats,
       send_custom_action,
       toggle_pitch_stabilization,
       toggle_roll_stabilization,
@@ -114,6 +203,43 @@ pub fn run() {
       toggle_depth_hold,
       flash_microcontroller_firmware,
       save_recording,
+      get_rov_handshake,
+      get_connection_state,
+      connect,
+      disconnect,
+      forget_rov_certificate,
+      replay_capture,
+      stop_capture_replay,
+      get_link_quality,
+      get_telemetry_ui_stats,
+      query_telemetry,
+      list_alarms,
+      acknowledge_alarm,
+      get_battery_estimate,
+      start_dive_recording,
+      stop_dive_recording,
+      list_dive_recordings,
+      start_dive_session,
+      stop_dive_session,
+      list_dive_sessions,
+      start_dive_replay,
+      pause_dive_replay,
+      resume_dive_replay,
+      seek_dive_replay,
+      set_dive_replay_speed,
+      stop_dive_replay,
+      get_dive_replay_state,
+      get_outbound_queue,
+      cancel_outbound_message,
+      discover_rovs,
+      create_rov_profile,
+      update_rov_profile,
+      delete_rov_profile,
+      switch_rov_profile,
+      list_vehicles,
+      open_vehicle,
+      close_vehicle,
+      set_pilot_vehicle,
     ])
     .setup(setup_handlers);
 
@@ -1,4 +1,7 @@
+use crate::events::LOG_MESSAGE;
 use crate::models::log::{LogEntry, LogLevel, LogOrigin};
+use crate::models::unknown::UnknownFields;
+use crate::models::vehicle::VehicleEvent;
 use once_cell::sync::OnceCell;
 use tauri::{AppHandle, Emitter};
 
@@ -15,12 +18,13 @@ pub fn log_info(message: &str) {
   if let Some(handle) = APP_HANDLE.get() {
     handle
       .emit(
-        "log_message",
-        LogEntry {
+        LOG_MESSAGE,
+        VehicleEvent::app(LogEntry {
           origin: LogOrigin::Backend,
           level: LogLevel::Info,
           message: message.to_string(),
-        },
+          unknown_fields: UnknownFields::default(),
+        }),
       )
       .unwrap();
   } else {
@@ -33,12 +37,13 @@ pub fn log_warn(message: &str) {
   if let Some(handle) = APP_HANDLE.get() {
     handle
       .emit(
-        "log_message",
-        LogEntry {
+        LOG_MESSAGE,
+        VehicleEvent::app(LogEntry {
           origin: LogOrigin::Backend,
           level: LogLevel::Warn,
           message: message.to_string(),
-        },
+          unknown_fields: UnknownFields::default(),
+        }),
       )
       .unwrap();
   } else {
@@ -51,12 +56,13 @@ pub fn log_error(message: &str) {
   if let Some(handle) = APP_HANDLE.get() {
     handle
       .emit(
-        "log_message",
-        LogEntry {
+        LOG_MESSAGE,
+        VehicleEvent::app(LogEntry {
           origin: LogOrigin::Backend,
           level: LogLevel::Error,
           message: message.to_string(),
-        },
+          unknown_fields: UnknownFields::default(),
+        }),
       )
       .unwrap();
   } else {
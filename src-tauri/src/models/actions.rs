@@ -1,3 +0,0 @@
-pub type DirectionVector = [f32; 8];
-
-pub type CustomAction = String;
@@ -1,5 +1,8 @@
+use crate::events::SHOW_TOAST;
 use crate::log_error;
-use crate::models::toast::{ToastCancel, Toast, ToastType};
+use crate::models::toast::{Toast, ToastCancel, ToastType};
+use crate::models::unknown::UnknownFields;
+use crate::models::vehicle::VehicleEvent;
 use once_cell::sync::OnceCell;
 use tauri::{AppHandle, Emitter};
 
@@ -81,14 +84,15 @@ fn toast_message(
   if let Some(handle) = APP_HANDLE.get() {
     handle
       .emit(
-        "show_toast",
-        Toast {
+        SHOW_TOAST,
+        VehicleEvent::app(Toast {
           id,
           toast_type,
           message,
           description,
           cancel,
-        },
+          unknown_fields: UnknownFields::default(),
+        }),
       )
       .unwrap();
   } else {
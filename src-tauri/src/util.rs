@@ -0,0 +1,24 @@
+use once_cell::sync::OnceCell;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+// A panic while the lock was held leaves the data as it was, which is still
+// better to go on with than to panic again.
+pub fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+  mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+// Milliseconds since the Unix epoch. The wall clock is only read once, later
+// times count on from it on a monotonic clock, so they never go back when the
+// system clock is stepped, e.g. resynced mid-dive.
+pub fn now_ms() -> u64 {
+  static START: OnceCell<(Instant, u64)> = OnceCell::new();
+  let (started, started_ms) = START.get_or_init(|| {
+    let started_ms = SystemTime::now()
+      .duration_since(UNIX_EPOCH)
+      .unwrap_or_default()
+      .as_millis() as u64;
+    (Instant::now(), started_ms)
+  });
+  started_ms + started.elapsed().as_millis() as u64
+}
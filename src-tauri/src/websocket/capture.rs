@@ -0,0 +1,250 @@
+use crate::log_warn;
+use crate::models::capture::{CapturedFrame, FrameDirection, FrameKind};
+use crate::models::config::Config;
+use crate::util::lock;
+use futures_util::{Sink, SinkExt, Stream};
+use std::collections::VecDeque;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, LineWriter, Write};
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+use tokio_tungstenite::tungstenite::protocol::CloseFrame;
+use tokio_tungstenite::tungstenite::{Error, Message};
+
+pub const MAX_CAPTURE_FILE_BYTES: u64 = 16 * 1024 * 1024;
+pub const MAX_CAPTURE_FILES: usize = 8;
+
+// Writes frames to `<name>-<start>-<n>.jsonl` files, starting a new file once
+// one reaches `max_file_bytes` and deleting the oldest beyond `max_files`.
+pub struct TrafficCapture {
+  directory: PathBuf,
+  prefix: String,
+  started: Instant,
+  max_file_bytes: u64,
+  max_files: usize,
+  files: VecDeque<PathBuf>,
+  file: LineWriter<File>,
+  file_bytes: u64,
+  file_index: u32,
+}
+
+impl TrafficCapture {
+  pub fn open(
+    directory: &Path,
+    name: &str,
+    max_file_bytes: u64,
+    max_files: usize,
+  ) -> io::Result<Self> {
+    fs::create_dir_all(directory)?;
+    let name = safe_file_name(name);
+    let started_ms = SystemTime::now()
+      .duration_since(UNIX_EPOCH)
+      .unwrap_or_default()
+      .as_millis();
+    let prefix = format!("{}-{}", name, started_ms);
+    let path = directory.join(format!("{}-0.jsonl", prefix));
+
+    Ok(TrafficCapture {
+      directory: directory.to_path_buf(),
+      prefix,
+      started: Instant::now(),
+      max_file_bytes,
+      max_files: max_files.max(1),
+      file: LineWriter::new(File::create(&path)?),
+      files: VecDeque::from([path]),
+      file_bytes: 0,
+      file_index: 0,
+    })
+  }
+
+  // The capture files still on disk, oldest first.
+  pub fn files(&self) -> Vec<PathBuf> {
+    self.files.iter().cloned().collect()
+  }
+
+  pub fn record(&mut self, direction: FrameDirection, message: &Message) -> io::Result<()> {
+    let Some((kind, text)) = frame(message) else {
+      return Ok(());
+    };
+    let frame = CapturedFrame {
+      direction,
+      elapsed_ms: self.started.elapsed().as_millis() as u64,
+      kind,
+      text,
+    };
+    let mut line = serde_json::to_vec(&frame)?;
+    line.push(b'\n');
+
+    if self.file_bytes > 0 && self.file_bytes + line.len() as u64 > self.max_file_bytes {
+      self.rotate()?;
+    }
+    self.file.write_all(&line)?;
+    self.file_bytes += line.len() as u64;
+    Ok(())
+  }
+
+  fn rotate(&mut self) -> io::Result<()> {
+    self.file_index += 1;
+    let path = self
+      .directory
+      .join(format!("{}-{}.jsonl", self.prefix, self.file_index));
+    self.file = LineWriter::new(File::create(&path)?);
+    self.file_bytes = 0;
+    self.files.push_back(path);
+    while self.files.len() > self.max_files {
+      if let Some(oldest) = self.files.pop_front() {
+        fs::remove_file(oldest)?;
+      }
+    }
+    Ok(())
+  }
+}
+
+// `name` with anything but ASCII letters, digits and dashes replaced, to use
+// it in a file name.
+pub fn safe_file_name(name: &str) -> String {
+  name
+    .chars()
+    .map(|c| {
+      if c.is_ascii_alphanumeric() || c == '-' {
+        c
+      } else {
+        '_'
+      }
+    })
+    .collect()
+}
+
+fn frame(message: &Message) -> Option<(FrameKind, String)> {
+  let lossy = |data: &[u8]| String::from_utf8_lossy(data).into_owned();
+  match message {
+    Message::Text(text) => Some((FrameKind::Text, text.to_string())),
+    Message::Binary(data) => Some((FrameKind::Binary, lossy(data))),
+    Message::Ping(data) => Some((FrameKind::Ping, lossy(data))),
+    Message::Pong(data) => Some((FrameKind::Pong, lossy(data))),
+    Message::Close(frame) => Some((
+      FrameKind::Close,
+      frame
+        .as_ref()
+        .map(|frame| frame.reason.to_string())
+        .unwrap_or_default(),
+    )),
+    Message::Frame(_) => None,
+  }
+}
+
+// The capture of one client, started and stopped as the config changes. A
+// capture outlives reconnects so an incident and its recovery end up in the
+// same files.
+#[derive(Default)]
+pub struct TrafficRecorder {
+  capture: Mutex<Option<TrafficCapture>>,
+}
+
+impl TrafficRecorder {
+  pub fn configure(&self, config: &Config, name: &str) {
+    let mut capture = lock(&self.capture);
+    let directory = Path::new(&config.capture_directory);
+    match &*capture {
+      Some(current) if config.capture_traffic && current.directory == directory => {}
+      _ if config.capture_traffic => {
+        let opened =
+          TrafficCapture::open(directory, name, MAX_CAPTURE_FILE_BYTES, MAX_CAPTURE_FILES);
+        *capture = match opened {
+          Ok(opened) => Some(opened),
+          Err(e) => {
+            log_warn!("Failed to start traffic capture: {}", e);
+            None
+          }
+        };
+      }
+      _ => *capture = None,
+    }
+  }
+
+  pub fn record(&self, direction: FrameDirection, message: &Message) {
+    let mut capture = lock(&self.capture);
+    let Some(current) = capture.as_mut() else {
+      return;
+    };
+    if let Err(e) = current.record(direction, message) {
+      log_warn!("Failed to capture traffic, stopping the capture: {}", e);
+      *capture = None;
+    }
+  }
+}
+
+// A websocket that records every frame passing through it, including pings and
+// the handshake, before the client looks at them.
+pub struct CapturedStream<S> {
+  inner: S,
+  recorder: Arc<TrafficRecorder>,
+}
+
+impl<S> CapturedStream<S> {
+  pub fn new(inner: S, recorder: Arc<TrafficRecorder>) -> Self {
+    CapturedStream { inner, recorder }
+  }
+}
+
+impl<S: Sink<Message, Error = Error> + Unpin> CapturedStream<S> {
+  // Mirrors `WebSocketStream::close`, so the close frame is captured as well.
+  pub async fn close(&mut self, frame: Option<CloseFrame>) -> Result<(), Error> {
+    self.send(Message::Close(frame)).await
+  }
+}
+
+impl<S: Stream<Item = Result<Message, Error>> + Unpin> Stream for CapturedStream<S> {
+  type Item = Result<Message, Error>;
+
+  fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+    let polled = Pin::new(&mut self.inner).poll_next(cx);
+    if let Poll::Ready(Some(Ok(message))) = &polled {
+      self.recorder.record(FrameDirection::Inbound, message);
+    }
+    polled
+  }
+}
+
+impl<S: Sink<Message, Error = Error> + Unpin> Sink<Message> for CapturedStream<S> {
+  type Error = Error;
+
+  fn poll_ready(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Error>> {
+    Pin::new(&mut self.inner).poll_ready(cx)
+  }
+
+  fn start_send(mut self: Pin<&mut Self>, message: Message) -> Result<(), Error> {
+    self.recorder.record(FrameDirection::Outbound, &message);
+    Pin::new(&mut self.inner).start_send(message)
+  }
+
+  fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Error>> {
+    Pin::new(&mut self.inner).poll_flush(cx)
+  }
+
+  fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Error>> {
+    Pin::new(&mut self.inner).poll_close(cx)
+  }
+}
+
+// Reads the frames of one capture file, or of several rotated files read one
+// after another.
+pub fn read_capture(paths: &[PathBuf]) -> Result<Vec<CapturedFrame>, String> {
+  let mut frames = Vec::new();
+  for path in paths {
+    let file = File::open(path).map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    for (index, line) in BufReader::new(file).lines().enumerate() {
+      let line = line.map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+      if line.trim().is_empty() {
+        continue;
+      }
+      let frame = serde_json::from_str(&line)
+        .map_err(|e| format!("{} line {}: {}", path.display(), index + 1, e))?;
+      frames.push(frame);
+    }
+  }
+  Ok(frames)
+}
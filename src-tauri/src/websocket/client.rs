@@ -1,203 +1,615 @@
-use super::handler::handle_message;
-use super::message::WebsocketMessage;
-use crate::config::get_config_from_file;
+use super::capture::{CapturedStream, TrafficRecorder};
+use super::connection::{Backoff, ConnectionCommand, SharedConnectionState};
+use super::direction_vector::{send_interval, DirectionVectorCounters};
+use super::handler::{handle_message, HandlerRegistry};
+use super::handshake::{perform_handshake, HandshakeState};
+use super::link_quality::LinkMonitor;
+use super::message::{WebsocketEnvelope, WebsocketMessage};
+use super::outbound::OutboundQueue;
+use super::request::PendingRequests;
+use super::tls::{pinned_connector, same_fingerprint, PresentedCertificate, TrustedCertificate};
+use super::vehicle::VehicleEmitter;
+use super::watchdog::{expired, ControlWatchdog};
+use crate::events::{
+  LINK_QUALITY, ROV_CONNECTION_STATE_CHANGED, ROV_CONNECTION_STATUS_UPDATED,
+  ROV_HANDSHAKE_COMPLETED,
+};
+use crate::models::actions::DirectionVector;
 use crate::models::config::Config;
-use crate::{log_info, log_warn};
+use crate::models::connection::{ConnectionPhase, ConnectionState, ConnectionStatus};
+use crate::toast::{toast_error, toast_info, toast_warn};
+use crate::{log_error, log_info, log_warn};
 use futures_util::{SinkExt, StreamExt};
-use std::time::{Duration, SystemTime, UNIX_EPOCH};
-use tauri::{AppHandle, Emitter};
-use tokio::sync::mpsc::{self, Receiver};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tauri::Runtime;
+use tokio::net::TcpStream;
+use tokio::sync::mpsc::{Receiver, Sender};
+use tokio::sync::watch;
 use tokio::time::{interval, sleep, timeout};
-use tokio_tungstenite::{connect_async, tungstenite::Message};
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::handshake::client::Request;
+use tokio_tungstenite::tungstenite::http::{header::AUTHORIZATION, HeaderValue, StatusCode};
+use tokio_tungstenite::tungstenite::protocol::{frame::coding::CloseCode, CloseFrame};
+use tokio_tungstenite::tungstenite::{Error, Message};
+use tokio_tungstenite::{connect_async_tls_with_config, MaybeTlsStream, WebSocketStream};
 
-#[derive(Clone, serde::Serialize)]
-#[serde(rename_all = "camelCase")]
-struct ConnectionStatus {
-  is_connected: bool,
-  delay: Option<u128>,
-}
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+const PING_INTERVAL: Duration = Duration::from_secs(2);
+const LINK_QUALITY_INTERVAL: Duration = Duration::from_secs(1);
+// Without a pong for `DEGRADED_AFTER` the link is reported as degraded, after
+// `LINK_TIMEOUT` it is considered dead and the client reconnects.
+const DEGRADED_AFTER: Duration = Duration::from_secs(5);
+const LINK_TIMEOUT: Duration = Duration::from_secs(10);
+const HIGH_ROUND_TRIP_MS: u64 = 1000;
+const WATCHDOG_TOAST_ID: &str = "control_watchdog";
+
+type WsStream = CapturedStream<WebSocketStream<MaybeTlsStream<TcpStream>>>;
 
 pub struct MessageSendChannelState {
-  pub tx: mpsc::Sender<WebsocketMessage>,
+  pub queue: Arc<OutboundQueue>,
+  pub requests: Arc<PendingRequests>,
 }
 
 pub struct DirectionVectorSendChannelState {
-  pub tx: mpsc::Sender<WebsocketMessage>,
+  pub tx: watch::Sender<DirectionVector>,
+  pub stats: Arc<DirectionVectorCounters>,
 }
 
-pub async fn start_websocket_client(
-  app: AppHandle,
-  mut config_rx: Receiver<Config>,
-  mut message_rx: Receiver<WebsocketMessage>,
-  mut direction_vector_rx: Receiver<WebsocketMessage>,
-) {
-  let mut config = get_config_from_file();
+pub struct WebsocketClient<R: Runtime> {
+  pub vehicle: VehicleEmitter<R>,
+  pub config: Config,
+  pub config_rx: Receiver<Config>,
+  pub outbound: Arc<OutboundQueue>,
+  pub direction_vector_rx: watch::Receiver<DirectionVector>,
+  pub direction_vector_stats: Arc<DirectionVectorCounters>,
+  pub control_rx: Receiver<ConnectionCommand>,
+  pub requests: Arc<PendingRequests>,
+  pub handshake: Arc<HandshakeState>,
+  pub connection: Arc<SharedConnectionState>,
+  pub link: Arc<LinkMonitor>,
+  pub certificates: Sender<TrustedCertificate>,
+  pub recorder: Arc<TrafficRecorder>,
+  pub handlers: Arc<HandlerRegistry<R>>,
+}
 
-  loop {
-    let url = format!("ws://{}:{}", config.ip_address, config.web_socket_port);
-    let connect_timeout = Duration::from_secs(5);
-
-    log_info!("Attempting to connect to {}", url);
-    let ws_stream = match timeout(connect_timeout, connect_async(&url)).await {
-      Ok(Ok((stream, _))) => {
-        log_info!("Successfully connected to {}", url);
-        stream
-      }
-      Ok(Err(e)) => {
-        log_info!("WebSocket connect error: {}. Retrying...", e);
-        app
-          .emit(
-            "rov_connection_status_updated",
-            ConnectionStatus {
-              is_connected: false,
-              delay: None,
-            },
-          )
-          .unwrap();
-        if let Some(new_config) = wait_before_retry(&mut config_rx).await {
-          config = new_config;
+enum Outcome {
+  Retry(String),
+  Reconfigure,
+  Stop,
+}
+
+impl<R: Runtime> WebsocketClient<R> {
+  pub async fn run(mut self) {
+    let mut backoff = Backoff::new();
+    let mut last_rejection = None;
+
+    loop {
+      let attempt = backoff.attempt() + 1;
+      let outcome = match self.open(attempt, &mut last_rejection).await {
+        Ok(ws_stream) => {
+          backoff.reset();
+          self.link.connected();
+          self.outbound.set_online(true);
+          let outcome = self.run_connection(ws_stream, attempt).await;
+          self.outbound.set_online(false);
+          self.requests.fail_all("Disconnected from ROV");
+          self.handshake.set(None);
+          let quality = self.link.disconnected();
+          self.vehicle.emit(LINK_QUALITY, quality).unwrap();
+          outcome
+        }
+        Err(outcome) => outcome,
+      };
+
+      let stopped = match outcome {
+        Outcome::Retry(reason) => {
+          let delay = backoff.next_delay();
+          self.transition(
+            ConnectionPhase::Reconnecting,
+            reason,
+            backoff.attempt(),
+            Some(delay),
+          );
+          !self.wait_before_retry(delay).await
+        }
+        Outcome::Reconfigure => {
+          backoff.reset();
+          self.transition(
+            ConnectionPhase::Reconnecting,
+            "Connection settings changed".to_string(),
+            0,
+            None,
+          );
+          false
         }
-        continue;
+        Outcome::Stop => true,
+      };
+
+      if stopped {
+        backoff.reset();
+        self.transition(
+          ConnectionPhase::Disconnected,
+          "Disconnected by operator".to_string(),
+          0,
+          None,
+        );
+        self.wait_for_connect().await;
+      }
+    }
+  }
+
+  async fn open(
+    &mut self,
+    attempt: u32,
+    last_rejection: &mut Option<String>,
+  ) -> Result<WsStream, Outcome> {
+    self.recorder.configure(&self.config, &self.vehicle.id);
+    let scheme = if self.config.use_tls { "wss" } else { "ws" };
+    let url = format!(
+      "{}://{}:{}",
+      scheme, self.config.ip_address, self.config.web_socket_port
+    );
+    self.transition(
+      ConnectionPhase::Connecting,
+      format!("Connecting to {}", url),
+      attempt,
+      None,
+    );
+
+    let request = match connect_request(&url, self.config.auth_token.as_deref()) {
+      Ok(request) => request,
+      Err(e) => return Err(Outcome::Retry(format!("Invalid WebSocket request: {}", e))),
+    };
+    let presented = Arc::new(PresentedCertificate::default());
+    let connector = if self.config.use_tls {
+      let pinned = self.config.certificate_fingerprint.as_deref();
+      match pinned_connector(pinned, presented.clone()) {
+        Ok(connector) => Some(connector),
+        Err(e) => return Err(Outcome::Retry(format!("TLS setup error: {}", e))),
+      }
+    } else {
+      None
+    };
+
+    let connect = connect_async_tls_with_config(request, None, false, connector);
+    let connected = tokio::select! {
+      result = timeout(CONNECT_TIMEOUT, connect) => result,
+      outcome = interruption(&mut self.control_rx, &mut self.config_rx, &mut self.config) => {
+        return Err(outcome);
+      }
+    };
+    let mut ws_stream = match connected {
+      Ok(Ok((stream, _))) => CapturedStream::new(stream, self.recorder.clone()),
+      Ok(Err(e)) => return Err(self.connect_failed(e, &presented, last_rejection)),
+      Err(_) => return Err(Outcome::Retry("WebSocket connect timeout".to_string())),
+    };
+    self.trust_on_first_use(&presented).await;
+
+    self.transition(
+      ConnectionPhase::Handshaking,
+      format!("Connected to {}, waiting for handshake", url),
+      attempt,
+      None,
+    );
+    let handshake = tokio::select! {
+      result = perform_handshake(
+        &self.vehicle,
+        &self.handlers,
+        &self.requests,
+        &self.link,
+        &mut ws_stream,
+        &self.handshake,
+        &url,
+      ) => Ok(result),
+      outcome = interruption(&mut self.control_rx, &mut self.config_rx, &mut self.config) => {
+        Err(outcome)
       }
-      Err(_) => {
-        log_info!("WebSocket connect timeout. Retrying...");
-        app
-          .emit(
-            "rov_connection_status_updated",
-            ConnectionStatus {
-              is_connected: false,
-              delay: None,
-            },
-          )
+    };
+
+    match handshake {
+      Ok(Ok(handshake)) => {
+        *last_rejection = None;
+        self.handshake.set(Some(handshake.clone()));
+        self
+          .requests
+          .set_legacy(handshake.protocol_version.is_none());
+        self.handlers.connected(&self.vehicle, &handshake);
+        self
+          .vehicle
+          .emit(ROV_HANDSHAKE_COMPLETED, handshake)
           .unwrap();
-        if let Some(new_config) = wait_before_retry(&mut config_rx).await {
-          config = new_config;
+        self.transition(
+          ConnectionPhase::Connected,
+          format!("Connected to {}", url),
+          attempt,
+          None,
+        );
+        Ok(ws_stream)
+      }
+      Ok(Err(e)) => {
+        log_error!("ROV handshake failed: {}", e);
+        // Only toast once while the same incompatible ROV keeps answering.
+        if last_rejection.as_ref() != Some(&e) {
+          toast_error(
+            None,
+            "Failed to connect to ROV".to_string(),
+            Some(e.clone()),
+            None,
+          );
+          *last_rejection = Some(e.clone());
         }
-        continue;
+        let frame = CloseFrame {
+          code: CloseCode::Policy,
+          reason: "Handshake failed".into(),
+        };
+        let _ = ws_stream.close(Some(frame)).await;
+        Err(Outcome::Retry(format!("Handshake failed: {}", e)))
+      }
+      Err(outcome) => {
+        let _ = ws_stream.close(None).await;
+        Err(outcome)
+      }
+    }
+  }
+
+  // Refusals the operator has to act on are toasted, once while the ROV keeps
+  // refusing for the same reason.
+  fn connect_failed(
+    &self,
+    error: Error,
+    presented: &PresentedCertificate,
+    last_rejection: &mut Option<String>,
+  ) -> Outcome {
+    let pinned = self.config.certificate_fingerprint.as_deref();
+    let rejection = match (&error, pinned, presented.get()) {
+      (Error::Http(response), _, _) if response.status() == StatusCode::UNAUTHORIZED => {
+        "The ROV did not accept the authentication token".to_string()
       }
+      (_, Some(pinned), Some(presented)) if !same_fingerprint(pinned, &presented) => format!(
+        "The ROV presented certificate {} but {} is pinned",
+        presented, pinned
+      ),
+      _ => return Outcome::Retry(format!("WebSocket connect error: {}", error)),
+    };
+
+    log_error!("ROV refused the connection: {}", rejection);
+    if last_rejection.as_ref() != Some(&rejection) {
+      toast_error(
+        None,
+        "Failed to connect to ROV".to_string(),
+        Some(rejection.clone()),
+        None,
+      );
+      *last_rejection = Some(rejection.clone());
+    }
+    Outcome::Retry(rejection)
+  }
+
+  // Pins the certificate of a ROV connected to over TLS for the first time, so
+  // a different certificate is refused from now on.
+  async fn trust_on_first_use(&mut self, presented: &PresentedCertificate) {
+    if self.config.certificate_fingerprint.is_some() {
+      return;
+    }
+    let Some(fingerprint) = presented.get() else {
+      return;
+    };
+    log_info!("Trusting ROV certificate {} on first use", fingerprint);
+    toast_info(
+      None,
+      "Trusting the ROV certificate".to_string(),
+      Some(fingerprint.clone()),
+      None,
+    );
+    self.config.certificate_fingerprint = Some(fingerprint.clone());
+    let trusted = TrustedCertificate {
+      vehicle_id: self.vehicle.id.clone(),
+      fingerprint,
     };
+    if let Err(e) = self.certificates.send(trusted).await {
+      log_warn!("Failed to pin ROV certificate: {}", e);
+    }
+  }
 
+  async fn run_connection(&mut self, ws_stream: WsStream, attempt: u32) -> Outcome {
     let (mut write, mut read) = ws_stream.split();
 
-    let ping_interval_duration = Duration::from_secs(2);
-    let mut ping_timer = interval(ping_interval_duration);
+    let mut ping_timer = interval(PING_INTERVAL);
     ping_timer.tick().await;
+    let mut report_timer = interval(LINK_QUALITY_INTERVAL);
+    report_timer.tick().await;
+    let mut last_pong = Instant::now();
+    let mut degraded = false;
+    // The ROV may still hold a stale command from before the link dropped, the
+    // watchdog holds it neutral from the first tick until fresh input arrives.
+    let mut watchdog = ControlWatchdog::new(self.watchdog_timeout());
+    let mut direction_vector_timer = send_interval(self.config.direction_vector_rate_hz);
+    self.direction_vector_rx.mark_unchanged();
 
     loop {
       tokio::select! {
-          Some(new_config) = config_rx.recv() => {
-            if new_config.ip_address != config.ip_address
-              || new_config.web_socket_port != config.web_socket_port
-            {
-              log_info!("WebSocket config updated. Reconnecting websocket.");
-              config = new_config;
-              app
-                .emit(
-            "rov_connection_status_updated",
-                  ConnectionStatus {
-                    is_connected: false,
-                    delay: None,
-                  },
-                )
-                .unwrap();
-              break;
+        Some(new_config) = self.config_rx.recv() => {
+          let reconnect = endpoint_changed(&self.config, &new_config);
+          let rate_changed =
+            new_config.direction_vector_rate_hz != self.config.direction_vector_rate_hz;
+          self.config = new_config;
+          self.recorder.configure(&self.config, &self.vehicle.id);
+          if reconnect {
+            log_info!("WebSocket config updated. Reconnecting websocket.");
+            return Outcome::Reconfigure;
+          }
+          watchdog.set_timeout(self.watchdog_timeout());
+          if rate_changed {
+            direction_vector_timer = send_interval(self.config.direction_vector_rate_hz);
+          }
+        }
+        Some(command) = self.control_rx.recv() => {
+          if command == ConnectionCommand::Disconnect {
+            let _ = write.send(Message::Close(None)).await;
+            return Outcome::Stop;
+          }
+        }
+        mut outgoing = self.outbound.next() => {
+          let message_text = match serde_json::to_string(&outgoing.envelope) {
+            Ok(text) => text,
+            Err(e) => {
+              log_warn!("Failed to serialize message: {}", e);
+              continue;
             }
-            config = new_config;
+          };
+          if let (Some(id), Some(reply)) = (outgoing.envelope.id, outgoing.reply.take()) {
+            self.requests.insert(id, reply, &outgoing.envelope.message);
           }
-          Some(message_to_send) = message_rx.recv() => {
-              let message_text = match serde_json::to_string(&message_to_send) {
-                Ok(text) => text,
-                Err(e) => {
-                  log_warn!("Failed to serialize message: {}", e);
-                  continue;
-                }
-              };
 
-              if let Err(e) = write.send(Message::Text(message_text.into())).await {
-                  log_warn!("Websocket send error: {}. Reconnecting...", e);
-                      app.emit("rov_connection_status_updated", ConnectionStatus { is_connected: false, delay: None }).unwrap();
-                  break;
-              }
+          let message = Message::Text(message_text.into());
+          self.link.record_sent(&message);
+          if let Err(e) = write.send(message).await {
+            return Outcome::Retry(format!("Websocket send error: {}", e));
           }
-          Some(direction_vector) = direction_vector_rx.recv() => {
-              let message_text = match serde_json::to_string(&direction_vector) {
-                  Ok(text) => text,
-                  Err(e) => {
-                      log_warn!("Failed to serialize direction vector: {}", e);
-                      continue;
-                  }
-              };
+          self.handlers.observe_outbound(&self.vehicle, &outgoing.envelope);
+          outgoing.sent();
+        }
+        _ = direction_vector_timer.tick() => {
+          if self.direction_vector_rx.has_changed().unwrap_or(false) {
+            let direction_vector = *self.direction_vector_rx.borrow_and_update();
+            self.direction_vector_stats.sampled();
+            watchdog.feed(&direction_vector);
+          }
+          let direction_vector = watchdog.apply(*self.direction_vector_rx.borrow());
+          let envelope =
+            WebsocketEnvelope::from(WebsocketMessage::DirectionVector(direction_vector));
+          let message_text = match serde_json::to_string(&envelope) {
+            Ok(text) => text,
+            Err(e) => {
+              log_warn!("Failed to serialize direction vector: {}", e);
+              continue;
+            }
+          };
 
-              if let Err(e) = write.send(Message::Text(message_text.into())).await {
-                  log_warn!("Websocket send error (direction vector): {}. Reconnecting...", e);
-                  app.emit("rov_connection_status_updated", ConnectionStatus { is_connected: false, delay: None }).unwrap();
-                  break;
-              }
+          let message = Message::Text(message_text.into());
+          self.link.record_sent(&message);
+          if let Err(e) = write.send(message).await {
+            return Outcome::Retry(format!("Websocket send error (direction vector): {}", e));
           }
-          _ = ping_timer.tick() => {
-              let timestamp_ms = SystemTime::now()
-                  .duration_since(UNIX_EPOCH)
-                  .unwrap_or_default()
-                  .as_millis();
-              let ping_data = timestamp_ms.to_string().into_bytes();
-
-              if let Err(e) = write.send(Message::Ping(ping_data.into())).await {
-                  log_warn!("Failed to send ping: {}. Reconnecting...", e);
-                      app.emit("rov_connection_status_updated", ConnectionStatus { is_connected: false, delay: None }).unwrap();
-                  break;
-              }
+          self.handlers.observe_outbound(&self.vehicle, &envelope);
+          self.direction_vector_stats.sent();
+        }
+        _ = ping_timer.tick() => {
+          let silence = last_pong.elapsed();
+          if silence >= LINK_TIMEOUT {
+            return Outcome::Retry(format!("No pong from ROV for {} seconds", silence.as_secs()));
+          }
+          if silence >= DEGRADED_AFTER && !degraded {
+            degraded = true;
+            let reason = format!("No pong from ROV for {} seconds", silence.as_secs());
+            self.transition(ConnectionPhase::Degraded, reason, attempt, None);
+          }
+
+          let timestamp_ms = now_ms();
+          let ping = Message::Ping(timestamp_ms.to_string().into_bytes().into());
+          self.link.ping_sent(timestamp_ms);
+          self.link.record_sent(&ping);
+
+          if let Err(e) = write.send(ping).await {
+            return Outcome::Retry(format!("Failed to send ping: {}", e));
           }
-          Some(message) = read.next() => {
-              match message {
-                  Ok(msg) => {
-                      if msg.is_text() || msg.is_binary() {
-                          if let Some(response) = handle_message(&app, msg).await {
-                              if let Err(e) = write.send(response).await {
-                                  log_warn!("Websocket send error: {}. Reconnecting...", e);
-                      app.emit("rov_connection_status_updated", ConnectionStatus { is_connected: false, delay: None }).unwrap();
-                                  break;
-                              }
-                          }
-                      } else if msg.is_close() {
-                          log_warn!("Websocket connection closed by peer. Reconnecting...");
-                      app.emit("rov_connection_status_updated", ConnectionStatus { is_connected: false, delay: None }).unwrap();
-                          break;
-                      }
-                      else if msg.is_pong() {
-                          if let Ok(timestamp_str) = String::from_utf8(msg.into_data().to_vec()) {
-                              if let Ok(sent_timestamp_ms) = timestamp_str.parse::<u128>() {
-                                  let now_ms = SystemTime::now().duration_since(UNIX_EPOCH)
-                                      .unwrap_or_default().as_millis();
-                                  let rtt = now_ms.saturating_sub(sent_timestamp_ms);
-                                  app.emit("rov_connection_status_updated", ConnectionStatus {
-                                      is_connected: true,
-                                      delay: Some(rtt),
-                                  }).unwrap();
-                              }
-                          }
-                      }
-                  }
-                  Err(e) => {
-                      log_warn!("Websocket read error: {}. Reconnecting...", e);
-                      app.emit("rov_connection_status_updated", ConnectionStatus { is_connected: false, delay: None }).unwrap();
-                      break;
-                  }
+        }
+        _ = expired(watchdog.deadline()) => {
+          watchdog.trip();
+          log_warn!(
+            "No direction vector for {} ms, neutralising thrust",
+            self.config.control_watchdog_timeout_ms
+          );
+          toast_warn(
+            Some(WATCHDOG_TOAST_ID.to_string()),
+            "Control input stalled, thrusters stopped".to_string(),
+            None,
+            None,
+          );
+        }
+        _ = report_timer.tick() => {
+          let quality = self.link.report(now_ms());
+          self.vehicle.emit(LINK_QUALITY, quality).unwrap();
+        }
+        message = read.next() => match message {
+          Some(Ok(msg)) => {
+            self.link.record_received(&msg);
+            if msg.is_text() || msg.is_binary() {
+              let responses =
+                handle_message(&self.vehicle, &self.handlers, &self.requests, &self.link, msg)
+                  .await;
+              for response in responses {
+                self.link.record_sent(&response);
+                if let Err(e) = write.send(response).await {
+                  return Outcome::Retry(format!("Websocket send error: {}", e));
+                }
+              }
+            } else if let Message::Close(frame) = msg {
+              return Outcome::Retry(match frame {
+                Some(frame) if !frame.reason.is_empty() => {
+                  format!("Connection closed by ROV: {}", frame.reason)
+                }
+                _ => "Connection closed by ROV".to_string(),
+              });
+            } else if msg.is_pong() {
+              let Some(rtt) = ping_timestamp(msg)
+                .and_then(|sent_ms| self.link.pong_received(sent_ms, now_ms()))
+              else {
+                continue;
+              };
+              last_pong = Instant::now();
+              self.emit_status(true, Some(rtt));
+
+              let healthy = rtt <= HIGH_ROUND_TRIP_MS;
+              if healthy == degraded {
+                degraded = !healthy;
+                let (phase, reason) = if healthy {
+                  (ConnectionPhase::Connected, "Link recovered".to_string())
+                } else {
+                  (ConnectionPhase::Degraded, format!("Round trip time is {} ms", rtt))
+                };
+                self.transition(phase, reason, attempt, None);
               }
+            }
+          }
+          Some(Err(e)) => return Outcome::Retry(format!("Websocket read error: {}", e)),
+          None => return Outcome::Retry("Connection closed".to_string()),
+        }
+      }
+    }
+  }
+
+  // Returns false when the operator disconnects instead.
+  async fn wait_before_retry(&mut self, delay: Duration) -> bool {
+    tokio::select! {
+      _ = sleep(delay) => true,
+      Some(new_config) = self.config_rx.recv() => {
+        log_info!("Config updated, retrying immediately.");
+        self.config = new_config;
+        true
+      }
+      Some(command) = self.control_rx.recv() => command == ConnectionCommand::Connect,
+    }
+  }
+
+  async fn wait_for_connect(&mut self) {
+    loop {
+      tokio::select! {
+        Some(new_config) = self.config_rx.recv() => self.config = new_config,
+        Some(command) = self.control_rx.recv() => {
+          if command == ConnectionCommand::Connect {
+            return;
           }
+        }
+        else => std::future::pending::<()>().await,
       }
     }
   }
+
+  fn transition(
+    &self,
+    phase: ConnectionPhase,
+    reason: String,
+    attempt: u32,
+    retry_in: Option<Duration>,
+  ) {
+    match retry_in {
+      Some(delay) => log_info!("{}. Retrying in {} ms...", reason, delay.as_millis()),
+      None => log_info!("{}", reason),
+    }
+
+    let state = ConnectionState {
+      phase,
+      reason,
+      attempt,
+      retry_in_ms: retry_in.map(|delay| delay.as_millis() as u64),
+    };
+    self.connection.set(state.clone());
+    self
+      .vehicle
+      .emit(ROV_CONNECTION_STATE_CHANGED, state)
+      .unwrap();
+
+    if matches!(
+      phase,
+      ConnectionPhase::Reconnecting | ConnectionPhase::Disconnected
+    ) {
+      self.emit_status(false, None);
+    }
+  }
+
+  fn watchdog_timeout(&self) -> Duration {
+    Duration::from_millis(self.config.control_watchdog_timeout_ms)
+  }
+
+  fn emit_status(&self, is_connected: bool, delay: Option<u64>) {
+    self
+      .vehicle
+      .emit(
+        ROV_CONNECTION_STATUS_UPDATED,
+        ConnectionStatus {
+          is_connected,
+          delay,
+        },
+      )
+      .unwrap();
+  }
 }
 
-async fn wait_before_retry(rx: &mut Receiver<Config>) -> Option<Config> {
-  tokio::select! {
-      Some(new_config) = rx.recv() => {
-          log_info!("Config updated, retrying immediately.");
-          Some(new_config)
-      },
-      _ = sleep(Duration::from_secs(3)) => {
-          log_info!("3 seconds passed, retrying connection.");
-          None
+// Resolves when a command or config change should abort an attempt that is
+// still connecting or handshaking.
+async fn interruption(
+  control_rx: &mut Receiver<ConnectionCommand>,
+  config_rx: &mut Receiver<Config>,
+  config: &mut Config,
+) -> Outcome {
+  loop {
+    tokio::select! {
+      Some(command) = control_rx.recv() => {
+        if command == ConnectionCommand::Disconnect {
+          return Outcome::Stop;
+        }
+      }
+      Some(new_config) = config_rx.recv() => {
+        let reconnect = endpoint_changed(config, &new_config);
+        *config = new_config;
+        if reconnect {
+          return Outcome::Reconfigure;
+        }
       }
+      else => std::future::pending::<()>().await,
+    }
+  }
+}
+
+fn endpoint_changed(old: &Config, new: &Config) -> bool {
+  new.ip_address != old.ip_address
+    || new.web_socket_port != old.web_socket_port
+    || new.use_tls != old.use_tls
+    || new.certificate_fingerprint != old.certificate_fingerprint
+    || new.auth_token != old.auth_token
+}
+
+// The websocket upgrade request, carrying the pre-shared token if one is set.
+fn connect_request(url: &str, auth_token: Option<&str>) -> Result<Request, Error> {
+  let mut request = url.into_client_request()?;
+  if let Some(token) = auth_token {
+    let value = HeaderValue::from_str(&format!("Bearer {}", token))
+      .map_err(|e| Error::HttpFormat(e.into()))?;
+    request.headers_mut().insert(AUTHORIZATION, value);
   }
+  Ok(request)
+}
+
+fn ping_timestamp(pong: Message) -> Option<u128> {
+  let timestamp_str = String::from_utf8(pong.into_data().to_vec()).ok()?;
+  timestamp_str.parse::<u128>().ok()
+}
+
+fn now_ms() -> u128 {
+  SystemTime::now()
+    .duration_since(UNIX_EPOCH)
+    .unwrap_or_default()
+    .as_millis()
 }
@@ -0,0 +1,82 @@
+use crate::models::connection::ConnectionState;
+use crate::util::lock;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::sync::mpsc;
+
+const BACKOFF_BASE: Duration = Duration::from_millis(500);
+const BACKOFF_MAX: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConnectionCommand {
+  Connect,
+  Disconnect,
+}
+
+pub struct ConnectionControlState {
+  pub tx: mpsc::Sender<ConnectionCommand>,
+  pub state: Arc<SharedConnectionState>,
+}
+
+#[derive(Default)]
+pub struct SharedConnectionState {
+  current: Mutex<ConnectionState>,
+}
+
+impl SharedConnectionState {
+  pub fn get(&self) -> ConnectionState {
+    lock(&self.current).clone()
+  }
+
+  pub fn set(&self, state: ConnectionState) {
+    *lock(&self.current) = state;
+  }
+}
+
+// Exponential backoff with jitter, the delay doubles per failed attempt up to
+// `BACKOFF_MAX` and is then scaled by a random factor between 0.5 and 1.
+pub struct Backoff {
+  attempt: u32,
+  rng: u64,
+}
+
+impl Backoff {
+  pub fn new() -> Self {
+    let seed = SystemTime::now()
+      .duration_since(UNIX_EPOCH)
+      .unwrap_or_default()
+      .as_nanos() as u64;
+    Backoff {
+      attempt: 0,
+      rng: seed | 1,
+    }
+  }
+
+  pub fn attempt(&self) -> u32 {
+    self.attempt
+  }
+
+  pub fn reset(&mut self) {
+    self.attempt = 0;
+  }
+
+  pub fn next_delay(&mut self) -> Duration {
+    self.attempt = self.attempt.saturating_add(1);
+    let exponent = (self.attempt - 1).min(16);
+    let delay = BACKOFF_BASE.saturating_mul(1 << exponent).min(BACKOFF_MAX);
+    delay.mul_f64(0.5 + self.random() * 0.5)
+  }
+
+  fn random(&mut self) -> f64 {
+    self.rng ^= self.rng << 13;
+    self.rng ^= self.rng >> 7;
+    self.rng ^= self.rng << 17;
+    (self.rng >> 11) as f64 / (1u64 << 53) as f64
+  }
+}
+
+impl Default for Backoff {
+  fn default() -> Self {
+    Self::new()
+  }
+}
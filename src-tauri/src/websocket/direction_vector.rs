@@ -0,0 +1,48 @@
+use crate::models::actions::{DirectionVector, DirectionVectorStats};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+use tokio::time::{interval, Interval, MissedTickBehavior};
+
+pub const NEUTRAL_DIRECTION_VECTOR: DirectionVector = [0.0; 8];
+
+// The frontend writes every frame into a latest-value slot that the client
+// samples at `direction_vector_rate_hz`, vectors replaced before they were
+// sampled count as coalesced.
+#[derive(Default)]
+pub struct DirectionVectorCounters {
+  received: AtomicU64,
+  sampled: AtomicU64,
+  sent: AtomicU64,
+}
+
+impl DirectionVectorCounters {
+  pub fn received(&self) {
+    self.received.fetch_add(1, Ordering::Relaxed);
+  }
+
+  pub fn sampled(&self) {
+    self.sampled.fetch_add(1, Ordering::Relaxed);
+  }
+
+  pub fn sent(&self) {
+    self.sent.fetch_add(1, Ordering::Relaxed);
+  }
+
+  pub fn get(&self) -> DirectionVectorStats {
+    let received = self.received.load(Ordering::Relaxed);
+    let sampled = self.sampled.load(Ordering::Relaxed);
+    DirectionVectorStats {
+      received,
+      sent: self.sent.load(Ordering::Relaxed),
+      coalesced: received.saturating_sub(sampled),
+    }
+  }
+}
+
+// Ticks missed while a send was blocked are skipped rather than sent in a
+// burst once the link frees up.
+pub fn send_interval(rate_hz: u32) -> Interval {
+  let mut timer = interval(Duration::from_secs(1) / rate_hz.max(1));
+  timer.set_missed_tick_behavior(MissedTickBehavior::Skip);
+  timer
+}
@@ -1,39 +1,261 @@
-use super::message::WebsocketMessage;
+use super::link_quality::LinkMonitor;
+use super::message::{WebsocketEnvelope, WebsocketMessage};
 use super::receive::{
-  config::{handle_config, handle_firmware_version, handle_regulator_suggestions},
-  log::handle_log_message,
-  status::handle_status_update,
-  telemetry::handle_telemetry,
-  toast::handle_show_toast,
+  config::ConfigHandler, log::LogHandler, reply::ReplyHandler, status::StatusHandler,
+  telemetry::TelemetryHandler, toast::ToastHandler, unknown::UnknownMessageHandler,
 };
-use crate::log_warn;
-use tauri::AppHandle;
+use super::request::PendingRequests;
+use super::telemetry_ui::TelemetryUi;
+use super::vehicle::VehicleEmitter;
+use crate::models::config::Config;
+use crate::models::handshake::Handshake;
+use crate::models::rov_config::RovConfig;
+use crate::util::lock;
+use crate::{log_error, log_warn};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tauri::Runtime;
 use tokio_tungstenite::tungstenite::Message;
 
-pub async fn handle_message(app_handle: &AppHandle, message: Message) -> Option<Message> {
+// Order of the handlers that pass messages on to the frontend. Handlers with a
+// lower order see a message before them, those with a higher one after.
+pub const FRONTEND_ORDER: i32 = 0;
+
+// Replies to send back to the ROV, or why the handler failed.
+pub type HandlerResult = Result<Vec<Message>, String>;
+
+// Something that wants to see websocket traffic, e.g. to forward it to the
+// frontend or record it. Handlers run on the connection's task and should
+// hand anything slow off to a task of their own.
+pub trait MessageHandler<R: Runtime>: Send + Sync {
+  // Whether `handle` wants to see this inbound message.
+  fn subscribes_to(&self, _message: &WebsocketMessage) -> bool {
+    false
+  }
+
+  fn handle(&self, _vehicle: &VehicleEmitter<R>, _envelope: &WebsocketEnvelope) -> HandlerResult {
+    Ok(Vec::new())
+  }
+
+  // Sees every message sent to the ROV.
+  fn observe_outbound(
+    &self,
+    _vehicle: &VehicleEmitter<R>,
+    _envelope: &WebsocketEnvelope,
+  ) -> Result<(), String> {
+    Ok(())
+  }
+
+  // Called each time the vehicle (re)connects, once the handshake completed.
+  fn connected(&self, _vehicle: &VehicleEmitter<R>, _handshake: &Handshake) -> Result<(), String> {
+    Ok(())
+  }
+
+  // Called with every open vehicle when the app config changes, and when a
+  // vehicle opens.
+  fn configured(&self, _vehicles: &[VehicleEmitter<R>], _config: &Config) -> Result<(), String> {
+    Ok(())
+  }
+
+  // Called when a vehicle opens, with the ROV config its profile last saw.
+  fn opened(
+    &self,
+    _vehicle: &VehicleEmitter<R>,
+    _rov_config: Option<&RovConfig>,
+  ) -> Result<(), String> {
+    Ok(())
+  }
+
+  // Called when a vehicle closes, to let go of what was kept for it.
+  fn closed(&self, _vehicle: &str) -> Result<(), String> {
+    Ok(())
+  }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct HandlerId(u64);
+
+struct RegisteredHandler<R: Runtime> {
+  id: HandlerId,
+  name: String,
+  order: i32,
+  handler: Arc<dyn MessageHandler<R>>,
+}
+
+// The handlers of every vehicle's traffic, run by ascending order and then in
+// the order they were registered. A handler that fails or panics is logged
+// and skipped, the others still see the message.
+pub struct HandlerRegistry<R: Runtime> {
+  handlers: Mutex<Vec<RegisteredHandler<R>>>,
+  next_id: AtomicU64,
+}
+
+impl<R: Runtime> Default for HandlerRegistry<R> {
+  fn default() -> Self {
+    HandlerRegistry {
+      handlers: Mutex::new(Vec::new()),
+      next_id: AtomicU64::new(1),
+    }
+  }
+}
+
+impl<R: Runtime> HandlerRegistry<R> {
+  // With the handlers that pass inbound messages on to the frontend, telemetry
+  // through `telemetry_ui`.
+  pub fn with_builtin_handlers(telemetry_ui: Arc<TelemetryUi>) -> Self {
+    let registry = HandlerRegistry::default();
+    registry.register("log", FRONTEND_ORDER, LogHandler);
+    registry.register("toast", FRONTEND_ORDER, ToastHandler);
+    registry.register(
+      "telemetry",
+      FRONTEND_ORDER,
+      TelemetryHandler { ui: telemetry_ui },
+    );
+    registry.register("status", FRONTEND_ORDER, StatusHandler);
+    registry.register("config", FRONTEND_ORDER, ConfigHandler);
+    registry.register("reply", FRONTEND_ORDER, ReplyHandler);
+    registry.register("unknown", FRONTEND_ORDER, UnknownMessageHandler);
+    registry
+  }
+
+  pub fn register(
+    &self,
+    name: impl Into<String>,
+    order: i32,
+    handler: impl MessageHandler<R> + 'static,
+  ) -> HandlerId {
+    let id = HandlerId(self.next_id.fetch_add(1, Ordering::Relaxed));
+    let mut handlers = lock(&self.handlers);
+    let index = handlers.partition_point(|registered| registered.order <= order);
+    handlers.insert(
+      index,
+      RegisteredHandler {
+        id,
+        name: name.into(),
+        order,
+        handler: Arc::new(handler),
+      },
+    );
+    id
+  }
+
+  // Returns whether the handler was still registered.
+  pub fn unregister(&self, id: HandlerId) -> bool {
+    let mut handlers = lock(&self.handlers);
+    let before = handlers.len();
+    handlers.retain(|registered| registered.id != id);
+    handlers.len() != before
+  }
+
+  // Names of the registered handlers, in the order they run.
+  pub fn names(&self) -> Vec<String> {
+    lock(&self.handlers)
+      .iter()
+      .map(|registered| registered.name.clone())
+      .collect()
+  }
+
+  pub fn dispatch(
+    &self,
+    vehicle: &VehicleEmitter<R>,
+    envelope: &WebsocketEnvelope,
+  ) -> Vec<Message> {
+    let subscribed: Vec<_> = self
+      .snapshot()
+      .into_iter()
+      .filter(|(_, handler)| handler.subscribes_to(&envelope.message))
+      .collect();
+    if subscribed.is_empty() {
+      log_warn!("Received unhandled message type: {:?}", envelope.message);
+      return Vec::new();
+    }
+
+    subscribed
+      .into_iter()
+      .filter_map(|(name, handler)| isolate(&name, || handler.handle(vehicle, envelope)))
+      .flatten()
+      .collect()
+  }
+
+  pub fn observe_outbound(&self, vehicle: &VehicleEmitter<R>, envelope: &WebsocketEnvelope) {
+    for (name, handler) in self.snapshot() {
+      isolate(&name, || handler.observe_outbound(vehicle, envelope));
+    }
+  }
+
+  pub fn connected(&self, vehicle: &VehicleEmitter<R>, handshake: &Handshake) {
+    for (name, handler) in self.snapshot() {
+      isolate(&name, || handler.connected(vehicle, handshake));
+    }
+  }
+
+  pub fn configured(&self, vehicles: &[VehicleEmitter<R>], config: &Config) {
+    for (name, handler) in self.snapshot() {
+      isolate(&name, || handler.configured(vehicles, config));
+    }
+  }
+
+  pub fn opened(&self, vehicle: &VehicleEmitter<R>, rov_config: Option<&RovConfig>) {
+    for (name, handler) in self.snapshot() {
+      isolate(&name, || handler.opened(vehicle, rov_config));
+    }
+  }
+
+  pub fn closed(&self, vehicle: &str) {
+    for (name, handler) in self.snapshot() {
+      isolate(&name, || handler.closed(vehicle));
+    }
+  }
+
+  // Handlers run without the lock held, so they may register others.
+  fn snapshot(&self) -> Vec<(String, Arc<dyn MessageHandler<R>>)> {
+    lock(&self.handlers)
+      .iter()
+      .map(|registered| (registered.name.clone(), registered.handler.clone()))
+      .collect()
+  }
+}
+
+fn isolate<T>(name: &str, handler: impl FnOnce() -> Result<T, String>) -> Option<T> {
+  match catch_unwind(AssertUnwindSafe(handler)) {
+    Ok(Ok(value)) => Some(value),
+    Ok(Err(e)) => {
+      log_warn!("Message handler {} failed: {}", name, e);
+      None
+    }
+    Err(_) => {
+      log_error!("Message handler {} panicked", name);
+      None
+    }
+  }
+}
+
+pub async fn handle_message<R: Runtime>(
+  vehicle: &VehicleEmitter<R>,
+  handlers: &HandlerRegistry<R>,
+  requests: &PendingRequests,
+  link: &LinkMonitor,
+  message: Message,
+) -> Vec<Message> {
   if let Message::Text(text) = message {
-    match serde_json::from_str::<WebsocketMessage>(&text) {
-      Ok(incoming_message) => match incoming_message {
-        WebsocketMessage::LogMessage(payload) => handle_log_message(app_handle, &payload),
-        WebsocketMessage::ShowToast(payload) => handle_show_toast(app_handle, &payload),
-        WebsocketMessage::Telemetry(payload) => handle_telemetry(app_handle, &payload),
-        WebsocketMessage::StatusUpdate(payload) => handle_status_update(app_handle, &payload),
-        WebsocketMessage::Config(payload) => handle_config(app_handle, &payload),
-        WebsocketMessage::RegulatorSuggestions(payload) => {
-          handle_regulator_suggestions(app_handle, &payload)
+    match serde_json::from_str::<WebsocketEnvelope>(&text) {
+      Ok(envelope) => {
+        match envelope.id {
+          Some(id) => requests.resolve(id, &envelope.message),
+          None => requests.resolve_untagged(&envelope.message),
         }
-        WebsocketMessage::FirmwareVersion(payload) => handle_firmware_version(app_handle, &payload),
-        other => {
-          log_warn!("Received unhandled message type: {:?}", other);
-          None
+        if let WebsocketMessage::Telemetry(_) = envelope.message {
+          link.telemetry_received();
         }
-      },
+        handlers.dispatch(vehicle, &envelope)
+      }
       Err(e) => {
         log_warn!("Failed to deserialize message: {}", e);
-        None
+        Vec::new()
       }
     }
   } else {
-    None
+    Vec::new()
   }
 }
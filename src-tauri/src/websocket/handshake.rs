@@ -0,0 +1,131 @@
+use super::handler::{handle_message, HandlerRegistry};
+use super::link_quality::LinkMonitor;
+use super::message::{WebsocketEnvelope, WebsocketMessage};
+use super::request::PendingRequests;
+use super::vehicle::VehicleEmitter;
+use crate::log_warn;
+use crate::models::handshake::{Capability, Handshake, Hello};
+use crate::util::lock;
+use futures_util::{Sink, SinkExt, Stream, StreamExt};
+use std::collections::HashSet;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::Runtime;
+use tokio::time::sleep;
+use tokio_tungstenite::tungstenite::{Error, Message};
+
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(3);
+// How long firmware that didn't answer last time is given, so a firmware
+// upgrade is still noticed without holding back control for as long.
+const LEGACY_HANDSHAKE_TIMEOUT: Duration = Duration::from_millis(500);
+
+#[derive(Default)]
+pub struct HandshakeState {
+  current: Mutex<Option<Handshake>>,
+  // Endpoints whose firmware sent other messages but never answered the
+  // hello. Reconnecting to them waits `LEGACY_HANDSHAKE_TIMEOUT` instead.
+  legacy_endpoints: Mutex<HashSet<String>>,
+}
+
+impl HandshakeState {
+  pub fn get(&self) -> Option<Handshake> {
+    lock(&self.current).clone()
+  }
+
+  pub fn set(&self, handshake: Option<Handshake>) {
+    *lock(&self.current) = handshake;
+  }
+
+  // Commands are only refused once the ROV has said it lacks the capability,
+  // while disconnected they fail on their own.
+  pub fn require(&self, capability: Capability) -> Result<(), String> {
+    match &*lock(&self.current) {
+      Some(handshake) if !handshake.supports(capability) => {
+        Err(format!("The ROV does not support {:?}", capability))
+      }
+      _ => Ok(()),
+    }
+  }
+
+  fn is_legacy(&self, endpoint: &str) -> bool {
+    lock(&self.legacy_endpoints).contains(endpoint)
+  }
+
+  fn remember_legacy(&self, endpoint: &str, legacy: bool) {
+    let mut endpoints = lock(&self.legacy_endpoints);
+    if legacy {
+      endpoints.insert(endpoint.to_string());
+    } else {
+      endpoints.remove(endpoint);
+    }
+  }
+}
+
+// Messages that arrive before the ROV's hello are handled as usual and their
+// replies sent, so nothing sent right after connecting is lost. A ROV that
+// stays silent isn't taken for firmware without versioning on later attempts,
+// only one that answered with other messages.
+pub async fn perform_handshake<R, S>(
+  vehicle: &VehicleEmitter<R>,
+  handlers: &HandlerRegistry<R>,
+  requests: &PendingRequests,
+  link: &LinkMonitor,
+  stream: &mut S,
+  state: &HandshakeState,
+  endpoint: &str,
+) -> Result<Handshake, String>
+where
+  R: Runtime,
+  S: Stream<Item = Result<Message, Error>> + Sink<Message, Error = Error> + Unpin,
+{
+  let hello = WebsocketEnvelope::from(WebsocketMessage::Hello(Hello::app()));
+  let text = serde_json::to_string(&hello).map_err(|e| e.to_string())?;
+  stream
+    .send(Message::Text(text.into()))
+    .await
+    .map_err(|e| e.to_string())?;
+  handlers.observe_outbound(vehicle, &hello);
+
+  let deadline = sleep(if state.is_legacy(endpoint) {
+    LEGACY_HANDSHAKE_TIMEOUT
+  } else {
+    HANDSHAKE_TIMEOUT
+  });
+  tokio::pin!(deadline);
+  let mut answered = false;
+
+  loop {
+    tokio::select! {
+      _ = &mut deadline => {
+        if answered {
+          log_warn!("ROV did not answer the handshake, assuming firmware without versioning");
+          state.remember_legacy(endpoint, true);
+        } else {
+          log_warn!("ROV sent nothing during the handshake, assuming firmware without versioning");
+        }
+        return Ok(Handshake::legacy());
+      }
+      message = stream.next() => match message {
+        Some(Ok(Message::Text(text))) => {
+          if let Ok(WebsocketEnvelope { message: WebsocketMessage::Hello(rov), .. }) =
+            serde_json::from_str::<WebsocketEnvelope>(&text)
+          {
+            state.remember_legacy(endpoint, false);
+            return Handshake::negotiate(rov);
+          }
+          answered = true;
+          let replies = handle_message(vehicle, handlers, requests, link, Message::Text(text)).await;
+          for reply in replies {
+            link.record_sent(&reply);
+            stream.send(reply).await.map_err(|e| e.to_string())?;
+          }
+        }
+        Some(Ok(Message::Close(_))) | None => {
+          return Err("Connection closed during handshake".to_string());
+        }
+        Some(Ok(_)) => {}
+        Some(Err(e)) => return Err(e.to_string()),
+      }
+    }
+  }
+}
@@ -0,0 +1,192 @@
+use crate::models::link_quality::{LinkQuality, RttBucket};
+use crate::util::lock;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::Instant;
+use tokio_tungstenite::tungstenite::Message;
+
+pub const RTT_BUCKETS_MS: [u64; 7] = [10, 25, 50, 100, 250, 500, 1000];
+// Statistics cover the last 30 pings, a minute at the client's ping interval.
+const PING_WINDOW: usize = 30;
+// A ping only counts as missed once its pong is this late.
+const PONG_GRACE_MS: u128 = 2000;
+
+struct Ping {
+  sent_ms: u128,
+  rtt_ms: Option<u64>,
+}
+
+struct LinkCounters {
+  pings: VecDeque<Ping>,
+  bytes_in: u64,
+  bytes_out: u64,
+  telemetry: u64,
+  since: Instant,
+  has_connected: bool,
+  reconnect_count: u32,
+  latest: LinkQuality,
+}
+
+impl Default for LinkCounters {
+  fn default() -> Self {
+    LinkCounters {
+      pings: VecDeque::with_capacity(PING_WINDOW),
+      bytes_in: 0,
+      bytes_out: 0,
+      telemetry: 0,
+      since: Instant::now(),
+      has_connected: false,
+      reconnect_count: 0,
+      latest: LinkQuality::default(),
+    }
+  }
+}
+
+impl LinkCounters {
+  fn reset_link(&mut self) {
+    self.pings.clear();
+    self.bytes_in = 0;
+    self.bytes_out = 0;
+    self.telemetry = 0;
+    self.since = Instant::now();
+  }
+}
+
+#[derive(Default)]
+pub struct LinkMonitor {
+  counters: Mutex<LinkCounters>,
+}
+
+impl LinkMonitor {
+  pub fn get(&self) -> LinkQuality {
+    lock(&self.counters).latest.clone()
+  }
+
+  pub fn connected(&self) {
+    let mut counters = lock(&self.counters);
+    if counters.has_connected {
+      counters.reconnect_count += 1;
+    }
+    counters.has_connected = true;
+    counters.reset_link();
+  }
+
+  // Clears the statistics of the lost link and returns the emptied report.
+  pub fn disconnected(&self) -> LinkQuality {
+    let mut counters = lock(&self.counters);
+    counters.reset_link();
+    counters.latest = LinkQuality {
+      reconnect_count: counters.reconnect_count,
+      ..LinkQuality::default()
+    };
+    counters.latest.clone()
+  }
+
+  pub fn ping_sent(&self, sent_ms: u128) {
+    let mut counters = lock(&self.counters);
+    if counters.pings.len() == PING_WINDOW {
+      counters.pings.pop_front();
+    }
+    counters.pings.push_back(Ping {
+      sent_ms,
+      rtt_ms: None,
+    });
+  }
+
+  // Returns the round trip time, or None for pongs that do not answer a ping
+  // in the window.
+  pub fn pong_received(&self, sent_ms: u128, now_ms: u128) -> Option<u64> {
+    let mut counters = lock(&self.counters);
+    let ping = counters
+      .pings
+      .iter_mut()
+      .find(|ping| ping.sent_ms == sent_ms && ping.rtt_ms.is_none())?;
+    let rtt_ms = now_ms.saturating_sub(sent_ms) as u64;
+    ping.rtt_ms = Some(rtt_ms);
+    Some(rtt_ms)
+  }
+
+  pub fn record_sent(&self, message: &Message) {
+    lock(&self.counters).bytes_out += message.len() as u64;
+  }
+
+  pub fn record_received(&self, message: &Message) {
+    lock(&self.counters).bytes_in += message.len() as u64;
+  }
+
+  pub fn telemetry_received(&self) {
+    lock(&self.counters).telemetry += 1;
+  }
+
+  // Summarises the ping window and the traffic since the previous report.
+  pub fn report(&self, now_ms: u128) -> LinkQuality {
+    let mut counters = lock(&self.counters);
+
+    let rtts: Vec<u64> = counters
+      .pings
+      .iter()
+      .filter_map(|ping| ping.rtt_ms)
+      .collect();
+    let settled = counters
+      .pings
+      .iter()
+      .filter(|ping| ping.rtt_ms.is_some() || now_ms.saturating_sub(ping.sent_ms) >= PONG_GRACE_MS)
+      .count();
+    let missed_pong_ratio = if settled == 0 {
+      0.0
+    } else {
+      (settled - rtts.len()) as f64 / settled as f64
+    };
+
+    let elapsed = counters.since.elapsed().as_secs_f64().max(f64::EPSILON);
+    let quality = LinkQuality {
+      rtt_histogram: histogram(&rtts),
+      rtt_last_ms: rtts.last().copied(),
+      rtt_min_ms: rtts.iter().min().copied(),
+      rtt_mean_ms: mean(rtts.iter().map(|rtt| *rtt as f64)),
+      rtt_max_ms: rtts.iter().max().copied(),
+      // Mean difference between consecutive round trip times.
+      jitter_ms: mean(rtts.windows(2).map(|pair| pair[0].abs_diff(pair[1]) as f64)),
+      missed_pong_ratio,
+      telemetry_rate: counters.telemetry as f64 / elapsed,
+      bytes_in_per_second: counters.bytes_in as f64 / elapsed,
+      bytes_out_per_second: counters.bytes_out as f64 / elapsed,
+      reconnect_count: counters.reconnect_count,
+    };
+
+    counters.bytes_in = 0;
+    counters.bytes_out = 0;
+    counters.telemetry = 0;
+    counters.since = Instant::now();
+    counters.latest = quality.clone();
+    quality
+  }
+}
+
+fn histogram(rtts: &[u64]) -> Vec<RttBucket> {
+  let mut buckets: Vec<RttBucket> = RTT_BUCKETS_MS
+    .iter()
+    .map(|up_to_ms| RttBucket {
+      up_to_ms: Some(*up_to_ms),
+      count: 0,
+    })
+    .chain([RttBucket {
+      up_to_ms: None,
+      count: 0,
+    }])
+    .collect();
+
+  for rtt in rtts {
+    let index = RTT_BUCKETS_MS
+      .iter()
+      .position(|up_to_ms| rtt < up_to_ms)
+      .unwrap_or(RTT_BUCKETS_MS.len());
+    buckets[index].count += 1;
+  }
+  buckets
+}
+
+fn mean(values: impl Iterator<Item = f64>) -> Option<f64> {
+  let (sum, count) = values.fold((0.0, 0), |(sum, count), value| (sum + value, count + 1));
+  (count > 0).then(|| sum / count as f64)
+}
@@ -0,0 +1,339 @@
+use super::capture::TrafficRecorder;
+use super::client::{DirectionVectorSendChannelState, MessageSendChannelState, WebsocketClient};
+use super::connection::{ConnectionCommand, ConnectionControlState, SharedConnectionState};
+use super::direction_vector::{DirectionVectorCounters, NEUTRAL_DIRECTION_VECTOR};
+use super::handler::HandlerRegistry;
+use super::handshake::HandshakeState;
+use super::link_quality::LinkMonitor;
+use super::outbound::OutboundQueue;
+use super::request::PendingRequests;
+use super::telemetry_ui::TelemetryUi;
+use super::tls::TrustedCertificate;
+use super::vehicle::{PilotVehicle, ReplayedVehicle, VehicleEmitter};
+use crate::events::{ROV_CONNECTION_STATE_CHANGED, ROV_CONNECTION_STATUS_UPDATED};
+use crate::log_warn;
+use crate::models::config::Config;
+use crate::models::connection::{ConnectionPhase, ConnectionStatus};
+use crate::models::vehicle::{VehicleId, VehicleInfo};
+use crate::util::lock;
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::async_runtime::{spawn, JoinHandle};
+use tauri::{AppHandle, Manager, Runtime, Wry};
+use tokio::sync::mpsc::{channel, Sender};
+use tokio::sync::watch;
+use tokio::time::{sleep, timeout};
+
+// The vehicle started with the app. It follows the top level connection
+// settings of `Config` and can not be closed.
+pub const PRIMARY_VEHICLE: &str = "primary";
+// How long closing a vehicle waits for the ROV to see a clean close.
+const CLOSE_TIMEOUT: Duration = Duration::from_secs(1);
+
+// The handles of one websocket client, what a single client used to expose as
+// managed state.
+pub struct VehicleConnection {
+  pub id: VehicleId,
+  // The profile the endpoint comes from, None for the primary vehicle.
+  pub profile: Option<String>,
+  pub messages: MessageSendChannelState,
+  pub direction_vectors: DirectionVectorSendChannelState,
+  pub handshake: Arc<HandshakeState>,
+  pub control: ConnectionControlState,
+  pub link: Arc<LinkMonitor>,
+  pub config_tx: Sender<Config>,
+  endpoint: Mutex<(String, u16)>,
+  task: JoinHandle<()>,
+}
+
+impl Drop for VehicleConnection {
+  fn drop(&mut self) {
+    self.task.abort();
+  }
+}
+
+pub struct ConnectionManager<R: Runtime = Wry> {
+  app: AppHandle<R>,
+  pilot: Arc<PilotVehicle>,
+  replayed: Arc<ReplayedVehicle>,
+  vehicles: Mutex<BTreeMap<VehicleId, Arc<VehicleConnection>>>,
+  certificates: Sender<TrustedCertificate>,
+  handlers: Arc<HandlerRegistry<R>>,
+}
+
+impl<R: Runtime> ConnectionManager<R> {
+  // Certificates trusted on first use are reported on `certificates` to be
+  // pinned. How telemetry reached the frontend is managed as `Arc<TelemetryUi>`.
+  pub fn new(app: AppHandle<R>, certificates: Sender<TrustedCertificate>) -> Self {
+    let telemetry_ui = Arc::new(TelemetryUi::default());
+    let handlers = HandlerRegistry::with_builtin_handlers(telemetry_ui.clone());
+    app.manage(telemetry_ui);
+    ConnectionManager {
+      app,
+      pilot: Arc::new(PilotVehicle::default()),
+      replayed: Arc::new(ReplayedVehicle::default()),
+      vehicles: Mutex::new(BTreeMap::new()),
+      certificates,
+      handlers: Arc::new(handlers),
+    }
+  }
+
+  // Starts a client for the vehicle, connecting to the endpoint of `profile`
+  // or to the top level connection settings. The first vehicle gets the pilot
+  // input.
+  pub fn open(
+    &self,
+    id: VehicleId,
+    profile: Option<String>,
+    app_config: &Config,
+  ) -> Result<Arc<VehicleConnection>, String> {
+    let config = vehicle_config(app_config, profile.as_deref())?;
+    if profile.is_some() && id == PRIMARY_VEHICLE {
+      return Err(format!(
+        "Vehicle id {} is reserved for the vehicle started with the app",
+        id
+      ));
+    }
+    let mut vehicles = lock(&self.vehicles);
+    if vehicles.contains_key(&id) {
+      return Err(format!("Vehicle {} is already open", id));
+    }
+    // A second session with the same ROV would fight the first over it.
+    let endpoint = (config.ip_address.clone(), config.web_socket_port);
+    if let Some(other) = vehicles
+      .values()
+      .find(|vehicle| *lock(&vehicle.endpoint) == endpoint)
+    {
+      return Err(format!(
+        "Vehicle {} is already connected to {}:{}",
+        other.id, endpoint.0, endpoint.1
+      ));
+    }
+    let emitter = self.vehicle_emitter(&id);
+    let mut emitters: Vec<_> = vehicles.keys().map(|id| self.vehicle_emitter(id)).collect();
+    emitters.push(emitter.clone());
+    self.handlers.configured(&emitters, app_config);
+    // The ROV config the profile last saw, until the ROV reports it.
+    let profile_name = profile
+      .as_deref()
+      .or(app_config.active_rov_profile.as_deref());
+    let rov_config = app_config
+      .rov_profiles
+      .iter()
+      .find(|known| Some(known.name.as_str()) == profile_name)
+      .and_then(|known| known.last_known_rov_config.as_ref());
+    self.handlers.opened(&emitter, rov_config);
+
+    let (config_tx, config_rx) = channel(1);
+    let outbound = Arc::new(OutboundQueue::default());
+    let requests = Arc::new(PendingRequests::default());
+    let (direction_vector_tx, direction_vector_rx) = watch::channel(NEUTRAL_DIRECTION_VECTOR);
+    let direction_vector_stats = Arc::new(DirectionVectorCounters::default());
+    let handshake = Arc::new(HandshakeState::default());
+    let (control_tx, control_rx) = channel(1);
+    let connection = Arc::new(SharedConnectionState::default());
+    let link = Arc::new(LinkMonitor::default());
+    let client = WebsocketClient {
+      vehicle: emitter,
+      config,
+      config_rx,
+      outbound: outbound.clone(),
+      direction_vector_rx,
+      direction_vector_stats: direction_vector_stats.clone(),
+      control_rx,
+      requests: requests.clone(),
+      handshake: handshake.clone(),
+      connection: connection.clone(),
+      link: link.clone(),
+      certificates: self.certificates.clone(),
+      recorder: Arc::new(TrafficRecorder::default()),
+      handlers: self.handlers.clone(),
+    };
+
+    let vehicle = Arc::new(VehicleConnection {
+      id: id.clone(),
+      profile,
+      messages: MessageSendChannelState {
+        queue: outbound,
+        requests,
+      },
+      direction_vectors: DirectionVectorSendChannelState {
+        tx: direction_vector_tx,
+        stats: direction_vector_stats,
+      },
+      handshake,
+      control: ConnectionControlState {
+        tx: control_tx,
+        state: connection,
+      },
+      link,
+      config_tx,
+      endpoint: Mutex::new(endpoint),
+      task: spawn(client.run()),
+    });
+    vehicles.insert(id.clone(), vehicle.clone());
+    if self.pilot.get().is_none() {
+      self.pilot.set(Some(id));
+    }
+    Ok(vehicle)
+  }
+
+  pub async fn close(&self, id: &str) -> Result<(), String> {
+    if id == PRIMARY_VEHICLE {
+      return Err("The primary vehicle can not be closed, disconnect it instead".to_string());
+    }
+    let vehicle = lock(&self.vehicles)
+      .remove(id)
+      .ok_or_else(|| format!("No vehicle named {}", id))?;
+    self.handlers.closed(id);
+    if self.pilot.is(id) {
+      self.pilot.set(Some(PRIMARY_VEHICLE.to_string()));
+    }
+
+    // Let the client close the socket before its task is aborted on drop.
+    if vehicle
+      .control
+      .tx
+      .send(ConnectionCommand::Disconnect)
+      .await
+      .is_ok()
+    {
+      let closed = async {
+        while vehicle.control.state.get().phase != ConnectionPhase::Disconnected {
+          sleep(Duration::from_millis(20)).await;
+        }
+      };
+      let _ = timeout(CLOSE_TIMEOUT, closed).await;
+    }
+    Ok(())
+  }
+
+  // Looks up the vehicle a command targets, the pilot vehicle by default.
+  pub fn get(&self, id: Option<&str>) -> Result<Arc<VehicleConnection>, String> {
+    let id = match id {
+      Some(id) => id.to_string(),
+      None => self
+        .pilot
+        .get()
+        .ok_or_else(|| "No vehicle receives pilot input".to_string())?,
+    };
+    lock(&self.vehicles)
+      .get(&id)
+      .cloned()
+      .ok_or_else(|| format!("No vehicle named {}", id))
+  }
+
+  // Looks up the vehicle a control command targets, refusing one that plays a
+  // dive back so what the pilot does during the replay never reaches it.
+  pub fn control(&self, id: Option<&str>) -> Result<Arc<VehicleConnection>, String> {
+    let vehicle = self.get(id)?;
+    if self.replayed.is(&vehicle.id) {
+      return Err(format!(
+        "Vehicle {} is replaying a dive, stop the replay to control it",
+        vehicle.id
+      ));
+    }
+    Ok(vehicle)
+  }
+
+  // Hands the pilot input to another vehicle. The previous one is held neutral
+  // so it does not keep the last command it was given.
+  pub fn set_pilot(&self, id: &str) -> Result<(), String> {
+    let vehicle = self.get(Some(id))?;
+    if let Some(previous) = self.pilot.get().filter(|previous| previous != id) {
+      if let Ok(previous) = self.get(Some(&previous)) {
+        let _ = previous.direction_vectors.tx.send(NEUTRAL_DIRECTION_VECTOR);
+      }
+    }
+    self.pilot.set(Some(vehicle.id.clone()));
+    Ok(())
+  }
+
+  // Emits events as if they came from the vehicle, the pilot vehicle by
+  // default.
+  pub fn emitter(&self, id: Option<&str>) -> Result<VehicleEmitter<R>, String> {
+    Ok(self.vehicle_emitter(&self.get(id)?.id))
+  }
+
+  // Emits the vehicle's own connection state again, e.g. once a dive replay
+  // no longer stands in for it.
+  pub fn announce_connection(&self, id: &str) {
+    if let Ok(vehicle) = self.get(Some(id)) {
+      let emitter = self.vehicle_emitter(id);
+      let connection = vehicle.control.state.get();
+      let status = ConnectionStatus {
+        is_connected: connection.phase.is_connected(),
+        delay: None,
+      };
+      let _ = emitter.emit(ROV_CONNECTION_STATE_CHANGED, connection);
+      let _ = emitter.emit(ROV_CONNECTION_STATUS_UPDATED, status);
+    }
+  }
+
+  // The handlers every vehicle's traffic goes through, shared so modules can
+  // register with it once for all vehicles.
+  pub fn handlers(&self) -> Arc<HandlerRegistry<R>> {
+    self.handlers.clone()
+  }
+
+  pub fn list(&self) -> Vec<VehicleInfo> {
+    lock(&self.vehicles)
+      .values()
+      .map(|vehicle| {
+        let (ip_address, web_socket_port) = lock(&vehicle.endpoint).clone();
+        VehicleInfo {
+          id: vehicle.id.clone(),
+          profile: vehicle.profile.clone(),
+          ip_address,
+          web_socket_port,
+          pilot: self.pilot.is(&vehicle.id),
+          connection: vehicle.control.state.get(),
+        }
+      })
+      .collect()
+  }
+
+  // Passes a changed app config on to every client, each keeping the endpoint
+  // of its own profile.
+  pub async fn apply_config(&self, app_config: Config) {
+    let emitters: Vec<_> = lock(&self.vehicles)
+      .keys()
+      .map(|id| self.vehicle_emitter(id))
+      .collect();
+    self.handlers.configured(&emitters, &app_config);
+    let vehicles: Vec<_> = lock(&self.vehicles).values().cloned().collect();
+    for vehicle in vehicles {
+      let config = match vehicle_config(&app_config, vehicle.profile.as_deref()) {
+        Ok(config) => config,
+        Err(e) => {
+          log_warn!("Not updating config of vehicle {}: {}", vehicle.id, e);
+          continue;
+        }
+      };
+      *lock(&vehicle.endpoint) = (config.ip_address.clone(), config.web_socket_port);
+      if let Err(e) = vehicle.config_tx.send(config).await {
+        log_warn!("Failed to update config of vehicle {}: {}", vehicle.id, e);
+      }
+    }
+  }
+
+  fn vehicle_emitter(&self, id: &str) -> VehicleEmitter<R> {
+    VehicleEmitter {
+      app: self.app.clone(),
+      id: id.to_string(),
+      pilot: self.pilot.clone(),
+      replayed: self.replayed.clone(),
+      replay: false,
+    }
+  }
+}
+
+fn vehicle_config(app_config: &Config, profile: Option<&str>) -> Result<Config, String> {
+  let mut config = app_config.clone();
+  if let Some(name) = profile {
+    config.switch_rov_profile(name)?;
+    config.active_rov_profile = app_config.active_rov_profile.clone();
+  }
+  Ok(config)
+}
@@ -0,0 +1,279 @@
+use super::message::{WebsocketEnvelope, WebsocketMessage};
+use super::request::Reply;
+use crate::log_warn;
+use crate::models::outbound::{DeliveryPolicy, OutboundPriority, QueuedMessage};
+use crate::util::lock;
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::sync::{oneshot, Notify};
+use tokio::time::{timeout_at, Instant};
+
+const COMMAND_EXPIRY: DeliveryPolicy = DeliveryPolicy::ExpireAfter { seconds: 5 };
+// Held messages still give up eventually, so whoever waits on them isn't stuck
+// while the ROV stays away.
+const MAX_HOLD: Duration = Duration::from_secs(30);
+
+// Stopping something the ROV is doing jumps the queue, anything that moves the
+// ROV is only meaningful while the operator is watching the link, and
+// configuration survives a reconnect.
+pub fn classify(message: &WebsocketMessage) -> (OutboundPriority, DeliveryPolicy) {
+  match message {
+    WebsocketMessage::CancelThrusterTest(_) | WebsocketMessage::CancelRegulatorAutoTuning => {
+      (OutboundPriority::Emergency, COMMAND_EXPIRY)
+    }
+    WebsocketMessage::StartThrusterTest(_)
+    | WebsocketMessage::StartRegulatorAutoTuning
+    | WebsocketMessage::CustomAction(_)
+    | WebsocketMessage::TogglePitchStabilization
+    | WebsocketMessage::ToggleRollStabilization
+    | WebsocketMessage::ToggleDepthHold => {
+      (OutboundPriority::Control, DeliveryPolicy::DropWhenOffline)
+    }
+    WebsocketMessage::SetConfig(_) => (
+      OutboundPriority::Configuration,
+      DeliveryPolicy::HoldUntilReconnect,
+    ),
+    WebsocketMessage::FlashMicrocontrollerFirmware(_) => (
+      OutboundPriority::Configuration,
+      DeliveryPolicy::DropWhenOffline,
+    ),
+    _ => (OutboundPriority::Configuration, COMMAND_EXPIRY),
+  }
+}
+
+struct QueuedItem {
+  id: u64,
+  envelope: WebsocketEnvelope,
+  priority: OutboundPriority,
+  policy: DeliveryPolicy,
+  queued_at: Instant,
+  reply: Option<oneshot::Sender<Reply>>,
+  delivered: oneshot::Sender<Result<(), String>>,
+}
+
+impl QueuedItem {
+  fn expires_at(&self) -> Option<Instant> {
+    match self.policy {
+      DeliveryPolicy::ExpireAfter { seconds } => {
+        Some(self.queued_at + Duration::from_secs(seconds))
+      }
+      DeliveryPolicy::HoldUntilReconnect => Some(self.queued_at + MAX_HOLD),
+      DeliveryPolicy::DropWhenOffline => None,
+    }
+  }
+
+  fn fail(self, reason: String) {
+    if let Some(reply) = self.reply {
+      let _ = reply.send(Err(reason.clone()));
+    }
+    let _ = self.delivered.send(Err(reason));
+  }
+}
+
+// A message taken off the queue by the client loop. The reply slot has to be
+// registered before the message is written, the ROV may answer right away.
+pub struct Outgoing {
+  pub envelope: WebsocketEnvelope,
+  pub reply: Option<oneshot::Sender<Reply>>,
+  delivered: oneshot::Sender<Result<(), String>>,
+}
+
+impl Outgoing {
+  pub fn sent(self) {
+    let _ = self.delivered.send(Ok(()));
+  }
+}
+
+pub struct Delivery {
+  id: u64,
+  expires_at: Option<Instant>,
+  rx: oneshot::Receiver<Result<(), String>>,
+}
+
+#[derive(Default)]
+struct QueueState {
+  online: bool,
+  next_id: u64,
+  items: Vec<QueuedItem>,
+}
+
+#[derive(Default)]
+pub struct OutboundQueue {
+  state: Mutex<QueueState>,
+  notify: Notify,
+}
+
+impl OutboundQueue {
+  pub fn push(
+    &self,
+    envelope: WebsocketEnvelope,
+    reply: Option<oneshot::Sender<Reply>>,
+  ) -> Result<Delivery, String> {
+    let (priority, policy) = classify(&envelope.message);
+    let mut state = lock(&self.state);
+    purge_expired(&mut state);
+    if !state.online && policy == DeliveryPolicy::DropWhenOffline {
+      return Err("Not connected to ROV".to_string());
+    }
+
+    state.next_id += 1;
+    let (tx, rx) = oneshot::channel();
+    let item = QueuedItem {
+      id: state.next_id,
+      envelope,
+      priority,
+      policy,
+      queued_at: Instant::now(),
+      reply,
+      delivered: tx,
+    };
+    let delivery = Delivery {
+      id: item.id,
+      expires_at: item.expires_at(),
+      rx,
+    };
+    state.items.push(item);
+    drop(state);
+
+    self.notify.notify_one();
+    Ok(delivery)
+  }
+
+  // Resolves once the message is written to the socket, or with the reason it
+  // never will be.
+  pub async fn delivered(&self, delivery: Delivery) -> Result<(), String> {
+    let Delivery {
+      id,
+      expires_at,
+      mut rx,
+    } = delivery;
+
+    if let Some(deadline) = expires_at {
+      if let Ok(result) = timeout_at(deadline, &mut rx).await {
+        return result.unwrap_or_else(|_| Err(dropped()));
+      }
+      // Taken by the client loop just as it expired, let it finish.
+      if !self.remove(id, expired()) {
+        return rx.await.unwrap_or_else(|_| Err(dropped()));
+      }
+      return Err(expired());
+    }
+
+    rx.await.unwrap_or_else(|_| Err(dropped()))
+  }
+
+  // Waits for the most urgent message, oldest first within a priority.
+  pub async fn next(&self) -> Outgoing {
+    loop {
+      {
+        let mut state = lock(&self.state);
+        purge_expired(&mut state);
+        let most_urgent = state
+          .items
+          .iter()
+          .enumerate()
+          .min_by_key(|(_, item)| (item.priority, item.id))
+          .map(|(index, _)| index);
+        if let Some(index) = most_urgent {
+          let item = state.items.remove(index);
+          return Outgoing {
+            envelope: item.envelope,
+            reply: item.reply,
+            delivered: item.delivered,
+          };
+        }
+      }
+      self.notify.notified().await;
+    }
+  }
+
+  pub fn set_online(&self, online: bool) {
+    let mut state = lock(&self.state);
+    state.online = online;
+    if online {
+      return;
+    }
+
+    let (dropped, held): (Vec<_>, Vec<_>) = state
+      .items
+      .drain(..)
+      .partition(|item| item.policy == DeliveryPolicy::DropWhenOffline);
+    state.items = held;
+    drop(state);
+
+    for item in dropped {
+      log_warn!(
+        "Dropped queued {} after losing the ROV",
+        message_type(&item.envelope)
+      );
+      item.fail("Disconnected from ROV".to_string());
+    }
+  }
+
+  pub fn list(&self) -> Vec<QueuedMessage> {
+    let mut state = lock(&self.state);
+    purge_expired(&mut state);
+    let mut items: Vec<QueuedMessage> = state
+      .items
+      .iter()
+      .map(|item| QueuedMessage {
+        id: item.id,
+        message_type: message_type(&item.envelope),
+        priority: item.priority,
+        policy: item.policy,
+        queued_for_ms: item.queued_at.elapsed().as_millis() as u64,
+      })
+      .collect();
+    items.sort_by_key(|item| (item.priority, item.id));
+    items
+  }
+
+  pub fn cancel(&self, id: u64) -> Result<(), String> {
+    if self.remove(id, "Cancelled by operator".to_string()) {
+      Ok(())
+    } else {
+      Err(format!("No queued message with id {}", id))
+    }
+  }
+
+  fn remove(&self, id: u64, reason: String) -> bool {
+    let mut state = lock(&self.state);
+    let Some(index) = state.items.iter().position(|item| item.id == id) else {
+      return false;
+    };
+    let item = state.items.remove(index);
+    drop(state);
+
+    item.fail(reason);
+    true
+  }
+}
+
+fn purge_expired(state: &mut QueueState) {
+  let now = Instant::now();
+  let mut index = 0;
+  while index < state.items.len() {
+    match state.items[index].expires_at() {
+      Some(deadline) if deadline <= now => {
+        let item = state.items.remove(index);
+        item.fail(expired());
+      }
+      _ => index += 1,
+    }
+  }
+}
+
+fn message_type(envelope: &WebsocketEnvelope) -> String {
+  serde_json::to_value(&envelope.message)
+    .ok()
+    .and_then(|value| value["type"].as_str().map(str::to_string))
+    .unwrap_or_default()
+}
+
+fn expired() -> String {
+  "Expired before the ROV was reachable".to_string()
+}
+
+fn dropped() -> String {
+  "Message was dropped before it was sent".to_string()
+}
@@ -1,28 +1,37 @@
-use crate::models::rov_config::{FirmwareVersion, RegulatorSuggestions, RovConfig};
-use tauri::{AppHandle, Emitter};
-use tokio_tungstenite::tungstenite::Message;
+use crate::events::{
+  FIRMWARE_VERSION_RECEIVED, REGULATOR_SUGGESTIONS_RECEIVED, ROV_CONFIG_RECEIVED,
+};
+use crate::websocket::handler::{HandlerResult, MessageHandler};
+use crate::websocket::message::{WebsocketEnvelope, WebsocketMessage};
+use crate::websocket::vehicle::VehicleEmitter;
+use tauri::Runtime;
 
-pub fn handle_config(app_handle: &AppHandle, payload: &RovConfig) -> Option<Message> {
-  app_handle.emit("rov_config_received", payload).unwrap();
-  None
-}
+// Passes the ROV's config, regulator suggestions and firmware version on to
+// the frontend.
+pub struct ConfigHandler;
 
-pub fn handle_regulator_suggestions(
-  app_handle: &AppHandle,
-  payload: &RegulatorSuggestions,
-) -> Option<Message> {
-  app_handle
-    .emit("regulator_suggestions_received", payload)
-    .unwrap();
-  None
-}
+impl<R: Runtime> MessageHandler<R> for ConfigHandler {
+  fn subscribes_to(&self, message: &WebsocketMessage) -> bool {
+    matches!(
+      message,
+      WebsocketMessage::Config(_)
+        | WebsocketMessage::RegulatorSuggestions(_)
+        | WebsocketMessage::FirmwareVersion(_)
+    )
+  }
 
-pub fn handle_firmware_version(
-  app_handle: &AppHandle,
-  payload: &FirmwareVersion,
-) -> Option<Message> {
-  app_handle
-    .emit("firmware_version_recieved", payload)
-    .unwrap();
-  None
+  fn handle(&self, vehicle: &VehicleEmitter<R>, envelope: &WebsocketEnvelope) -> HandlerResult {
+    let emitted = match &envelope.message {
+      WebsocketMessage::Config(payload) => vehicle.emit(ROV_CONFIG_RECEIVED, payload),
+      WebsocketMessage::RegulatorSuggestions(payload) => {
+        vehicle.emit(REGULATOR_SUGGESTIONS_RECEIVED, payload)
+      }
+      WebsocketMessage::FirmwareVersion(payload) => {
+        vehicle.emit(FIRMWARE_VERSION_RECEIVED, payload)
+      }
+      _ => Ok(()),
+    };
+    emitted.map_err(|e| e.to_string())?;
+    Ok(Vec::new())
+  }
 }
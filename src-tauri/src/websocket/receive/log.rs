@@ -1,8 +1,23 @@
-use crate::models::log::LogEntry;
-use tauri::{AppHandle, Emitter};
-use tokio_tungstenite::tungstenite::Message;
+use crate::events::LOG_MESSAGE;
+use crate::websocket::handler::{HandlerResult, MessageHandler};
+use crate::websocket::message::{WebsocketEnvelope, WebsocketMessage};
+use crate::websocket::vehicle::VehicleEmitter;
+use tauri::Runtime;
 
-pub fn handle_log_message(app_handle: &AppHandle, payload: &LogEntry) -> Option<Message> {
-  app_handle.emit("log_message", payload).unwrap();
-  None
+// Passes firmware log messages on to the frontend.
+pub struct LogHandler;
+
+impl<R: Runtime> MessageHandler<R> for LogHandler {
+  fn subscribes_to(&self, message: &WebsocketMessage) -> bool {
+    matches!(message, WebsocketMessage::LogMessage(_))
+  }
+
+  fn handle(&self, vehicle: &VehicleEmitter<R>, envelope: &WebsocketEnvelope) -> HandlerResult {
+    if let WebsocketMessage::LogMessage(payload) = &envelope.message {
+      vehicle
+        .emit(LOG_MESSAGE, payload)
+        .map_err(|e| e.to_string())?;
+    }
+    Ok(Vec::new())
+  }
 }
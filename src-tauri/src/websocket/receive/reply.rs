@@ -0,0 +1,22 @@
+use crate::log_warn;
+use crate::websocket::handler::{HandlerResult, MessageHandler};
+use crate::websocket::message::{WebsocketEnvelope, WebsocketMessage};
+use crate::websocket::vehicle::VehicleEmitter;
+use tauri::Runtime;
+
+// Replies are resolved against their requests before any handler runs, this
+// only keeps them from being reported as unhandled.
+pub struct ReplyHandler;
+
+impl<R: Runtime> MessageHandler<R> for ReplyHandler {
+  fn subscribes_to(&self, message: &WebsocketMessage) -> bool {
+    matches!(message, WebsocketMessage::Ack | WebsocketMessage::Nack(_))
+  }
+
+  fn handle(&self, _vehicle: &VehicleEmitter<R>, envelope: &WebsocketEnvelope) -> HandlerResult {
+    if let WebsocketMessage::Nack(reason) = &envelope.message {
+      log_warn!("ROV rejected a request: {}", reason);
+    }
+    Ok(Vec::new())
+  }
+}
@@ -1,8 +1,23 @@
-use crate::models::rov_status::RovStatus;
-use tauri::{AppHandle, Emitter};
-use tokio_tungstenite::tungstenite::Message;
+use crate::events::ROV_STATUS_UPDATE;
+use crate::websocket::handler::{HandlerResult, MessageHandler};
+use crate::websocket::message::{WebsocketEnvelope, WebsocketMessage};
+use crate::websocket::vehicle::VehicleEmitter;
+use tauri::Runtime;
 
-pub fn handle_status_update(app_handle: &AppHandle, payload: &RovStatus) -> Option<Message> {
-  app_handle.emit("rov_status_update", payload).unwrap();
-  None
+// Passes status updates on to the frontend.
+pub struct StatusHandler;
+
+impl<R: Runtime> MessageHandler<R> for StatusHandler {
+  fn subscribes_to(&self, message: &WebsocketMessage) -> bool {
+    matches!(message, WebsocketMessage::StatusUpdate(_))
+  }
+
+  fn handle(&self, vehicle: &VehicleEmitter<R>, envelope: &WebsocketEnvelope) -> HandlerResult {
+    if let WebsocketMessage::StatusUpdate(payload) = &envelope.message {
+      vehicle
+        .emit(ROV_STATUS_UPDATE, payload)
+        .map_err(|e| e.to_string())?;
+    }
+    Ok(Vec::new())
+  }
 }
@@ -1,8 +1,38 @@
-use crate::models::rov_telemetry::RovTelemetry;
-use tauri::{AppHandle, Emitter};
-use tokio_tungstenite::tungstenite::Message;
+use crate::models::config::Config;
+use crate::websocket::handler::{HandlerResult, MessageHandler};
+use crate::websocket::message::{WebsocketEnvelope, WebsocketMessage};
+use crate::websocket::telemetry_ui::TelemetryUi;
+use crate::websocket::vehicle::VehicleEmitter;
+use std::sync::Arc;
+use tauri::Runtime;
 
-pub fn handle_telemetry(app_handle: &AppHandle, payload: &RovTelemetry) -> Option<Message> {
-  app_handle.emit("rov_telemetry", payload).unwrap();
-  None
+// Passes telemetry on to the frontend, at the rate it can keep up with.
+pub struct TelemetryHandler {
+  pub ui: Arc<TelemetryUi>,
+}
+
+impl<R: Runtime> MessageHandler<R> for TelemetryHandler {
+  fn subscribes_to(&self, message: &WebsocketMessage) -> bool {
+    matches!(message, WebsocketMessage::Telemetry(_))
+  }
+
+  fn handle(&self, vehicle: &VehicleEmitter<R>, envelope: &WebsocketEnvelope) -> HandlerResult {
+    if let WebsocketMessage::Telemetry(payload) = &envelope.message {
+      self
+        .ui
+        .forward(vehicle, payload)
+        .map_err(|e| e.to_string())?;
+    }
+    Ok(Vec::new())
+  }
+
+  fn configured(&self, _vehicles: &[VehicleEmitter<R>], config: &Config) -> Result<(), String> {
+    self.ui.configure(config);
+    Ok(())
+  }
+
+  fn closed(&self, vehicle: &str) -> Result<(), String> {
+    self.ui.remove(vehicle);
+    Ok(())
+  }
 }
@@ -1,8 +1,23 @@
-use crate::models::toast::Toast;
-use tauri::{AppHandle, Emitter};
-use tokio_tungstenite::tungstenite::Message;
+use crate::events::SHOW_TOAST;
+use crate::websocket::handler::{HandlerResult, MessageHandler};
+use crate::websocket::message::{WebsocketEnvelope, WebsocketMessage};
+use crate::websocket::vehicle::VehicleEmitter;
+use tauri::Runtime;
 
-pub fn handle_show_toast(app_handle: &AppHandle, payload: &Toast) -> Option<Message> {
-  app_handle.emit("show_toast", payload).unwrap();
-  None
+// Passes toasts from the ROV on to the frontend.
+pub struct ToastHandler;
+
+impl<R: Runtime> MessageHandler<R> for ToastHandler {
+  fn subscribes_to(&self, message: &WebsocketMessage) -> bool {
+    matches!(message, WebsocketMessage::ShowToast(_))
+  }
+
+  fn handle(&self, vehicle: &VehicleEmitter<R>, envelope: &WebsocketEnvelope) -> HandlerResult {
+    if let WebsocketMessage::ShowToast(payload) = &envelope.message {
+      vehicle
+        .emit(SHOW_TOAST, payload)
+        .map_err(|e| e.to_string())?;
+    }
+    Ok(Vec::new())
+  }
 }
@@ -0,0 +1,26 @@
+use crate::events::ROV_UNKNOWN_MESSAGE;
+use crate::log_info;
+use crate::websocket::handler::{HandlerResult, MessageHandler};
+use crate::websocket::message::{WebsocketEnvelope, WebsocketMessage};
+use crate::websocket::vehicle::VehicleEmitter;
+use tauri::Runtime;
+
+// Passes messages of types this app doesn't know on to the frontend, so newer
+// firmware can be inspected without an app update.
+pub struct UnknownMessageHandler;
+
+impl<R: Runtime> MessageHandler<R> for UnknownMessageHandler {
+  fn subscribes_to(&self, message: &WebsocketMessage) -> bool {
+    matches!(message, WebsocketMessage::Unknown(_))
+  }
+
+  fn handle(&self, vehicle: &VehicleEmitter<R>, envelope: &WebsocketEnvelope) -> HandlerResult {
+    if let WebsocketMessage::Unknown(payload) = &envelope.message {
+      log_info!("Received message of unknown type {}", payload.message_type);
+      vehicle
+        .emit(ROV_UNKNOWN_MESSAGE, payload)
+        .map_err(|e| e.to_string())?;
+    }
+    Ok(Vec::new())
+  }
+}
@@ -0,0 +1,89 @@
+use super::handler::{handle_message, HandlerRegistry};
+use super::link_quality::LinkMonitor;
+use super::request::PendingRequests;
+use super::telemetry_ui::TelemetryUi;
+use super::vehicle::VehicleEmitter;
+use crate::models::capture::{CapturedFrame, FrameDirection, FrameKind};
+use crate::util::lock;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::async_runtime::JoinHandle;
+use tauri::Runtime;
+use tokio::time::{sleep_until, Instant};
+use tokio_tungstenite::tungstenite::Message;
+
+// Feeds the inbound frames of a capture through the handlers that pass them on
+// to the frontend, at `speed` times the pace they arrived at, so the frontend
+// sees what the pilot saw. The events are flagged as replayed, and nothing
+// registered with the vehicle's own handlers, like its telemetry history or
+// alarms, takes them for live readings. Replies and pending requests stay
+// apart from the live connection.
+// Returns the number of frames replayed.
+pub async fn replay_capture<R: Runtime>(
+  vehicle: &VehicleEmitter<R>,
+  telemetry_ui: Arc<TelemetryUi>,
+  frames: &[CapturedFrame],
+  speed: f64,
+) -> usize {
+  let vehicle = vehicle.for_replay();
+  let handlers = HandlerRegistry::with_builtin_handlers(telemetry_ui);
+  let requests = PendingRequests::default();
+  let link = LinkMonitor::default();
+  let inbound: Vec<_> = frames
+    .iter()
+    .filter(|frame| frame.direction == FrameDirection::Inbound && frame.kind == FrameKind::Text)
+    .collect();
+  let Some(first) = inbound.first() else {
+    return 0;
+  };
+
+  let started = Instant::now();
+  for frame in &inbound {
+    let offset_ms = frame.elapsed_ms.saturating_sub(first.elapsed_ms) as f64 / speed;
+    sleep_until(started + Duration::from_secs_f64(offset_ms / 1000.0)).await;
+    handle_message(
+      &vehicle,
+      &handlers,
+      &requests,
+      &link,
+      Message::Text(frame.text.clone().into()),
+    )
+    .await;
+  }
+  inbound.len()
+}
+
+pub fn check_replay_speed(speed: f64) -> Result<f64, String> {
+  if !speed.is_finite() || speed <= 0.0 {
+    return Err(format!(
+      "Replay speed must be a positive number, got {}",
+      speed
+    ));
+  }
+  Ok(speed)
+}
+
+// The replay in progress, a new one replaces it.
+#[derive(Default)]
+pub struct CaptureReplay {
+  task: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl CaptureReplay {
+  pub fn start(&self, task: JoinHandle<()>) {
+    if let Some(previous) = lock(&self.task).replace(task) {
+      previous.abort();
+    }
+  }
+
+  // Returns whether a replay was stopped.
+  pub fn stop(&self) -> bool {
+    match lock(&self.task).take() {
+      Some(task) => {
+        task.abort();
+        true
+      }
+      None => false,
+    }
+  }
+}
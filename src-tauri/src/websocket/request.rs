@@ -0,0 +1,154 @@
+use super::client::MessageSendChannelState;
+use super::message::{WebsocketEnvelope, WebsocketMessage};
+use crate::util::lock;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::sync::oneshot;
+use tokio::time::timeout;
+
+pub const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+pub type Reply = Result<WebsocketMessage, String>;
+
+struct Pending {
+  tx: oneshot::Sender<Reply>,
+  // Whether the ROV answers it with its config, which firmware without
+  // request ids sends back untagged.
+  answered_with_config: bool,
+}
+
+// Requests carry an id the ROV echoes in its reply. Firmware from before the
+// handshake never echoes one, so once the handshake found such firmware the
+// config requests take the first untagged config instead and the others
+// count as done once sent.
+#[derive(Default)]
+pub struct PendingRequests {
+  next_id: AtomicU64,
+  pending: Mutex<HashMap<u64, Pending>>,
+  legacy: AtomicBool,
+}
+
+impl PendingRequests {
+  pub fn next_id(&self) -> u64 {
+    self.next_id.fetch_add(1, Ordering::Relaxed) + 1
+  }
+
+  // Called by the client loop right before the request is written, so
+  // requests still waiting in the outbound queue survive a disconnect.
+  pub fn insert(&self, id: u64, tx: oneshot::Sender<Reply>, request: &WebsocketMessage) {
+    let pending = Pending {
+      tx,
+      answered_with_config: answered_with_config(request),
+    };
+    lock(&self.pending).insert(id, pending);
+  }
+
+  // Set from the handshake of each connection.
+  pub fn set_legacy(&self, legacy: bool) {
+    self.legacy.store(legacy, Ordering::Relaxed);
+  }
+
+  pub fn legacy(&self) -> bool {
+    self.legacy.load(Ordering::Relaxed)
+  }
+
+  pub fn resolve(&self, id: u64, message: &WebsocketMessage) {
+    let Some(pending) = lock(&self.pending).remove(&id) else {
+      return;
+    };
+    let reply = match message {
+      WebsocketMessage::Nack(reason) => Err(format!("ROV rejected the request: {}", reason)),
+      other => Ok(other.clone()),
+    };
+    let _ = pending.tx.send(reply);
+  }
+
+  // An untagged config from legacy firmware answers the oldest config request.
+  pub fn resolve_untagged(&self, message: &WebsocketMessage) {
+    if !self.legacy() || !matches!(message, WebsocketMessage::Config(_)) {
+      return;
+    }
+    let mut pending = lock(&self.pending);
+    let oldest = pending
+      .iter()
+      .filter(|(_, pending)| pending.answered_with_config)
+      .map(|(&id, _)| id)
+      .min();
+    if let Some(pending) = oldest.and_then(|id| pending.remove(&id)) {
+      let _ = pending.tx.send(Ok(message.clone()));
+    }
+  }
+
+  pub fn forget(&self, id: u64) {
+    lock(&self.pending).remove(&id);
+  }
+
+  pub fn fail_all(&self, reason: &str) {
+    for (_, pending) in lock(&self.pending).drain() {
+      let _ = pending.tx.send(Err(reason.to_string()));
+    }
+  }
+}
+
+pub async fn send_request(
+  state: &MessageSendChannelState,
+  message: WebsocketMessage,
+  reply_timeout: Duration,
+) -> Result<WebsocketMessage, String> {
+  let id = state.requests.next_id();
+  let (tx, rx) = oneshot::channel();
+
+  let answered_with_config = answered_with_config(&message);
+  let envelope = WebsocketEnvelope {
+    id: Some(id),
+    message,
+  };
+  let delivery = state.queue.push(envelope, Some(tx))?;
+  state.queue.delivered(delivery).await?;
+  if state.requests.legacy() && !answered_with_config {
+    state.requests.forget(id);
+    return Ok(WebsocketMessage::Ack);
+  }
+
+  // The timeout starts once the request is on the wire, held requests first
+  // wait out the reconnect.
+  let reply = timeout(reply_timeout, rx).await;
+  state.requests.forget(id);
+
+  match reply {
+    Ok(reply) => {
+      reply.unwrap_or_else(|_| Err("Request was dropped before the ROV replied".to_string()))
+    }
+    Err(_) => Err(format!(
+      "ROV did not reply within {} seconds",
+      reply_timeout.as_secs()
+    )),
+  }
+}
+
+fn answered_with_config(request: &WebsocketMessage) -> bool {
+  matches!(
+    request,
+    WebsocketMessage::GetConfig | WebsocketMessage::SetConfig(_)
+  )
+}
+
+pub fn send_message(
+  state: &MessageSendChannelState,
+  message: WebsocketMessage,
+) -> Result<(), String> {
+  state.queue.push(message.into(), None).map(|_| ())
+}
+
+pub fn expect_ack(reply: WebsocketMessage) -> Result<(), String> {
+  match reply {
+    WebsocketMessage::Ack => Ok(()),
+    other => Err(unexpected_reply(&other)),
+  }
+}
+
+pub fn unexpected_reply(reply: &WebsocketMessage) -> String {
+  format!("Unexpected reply from ROV: {:?}", reply)
+}
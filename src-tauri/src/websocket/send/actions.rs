@@ -3,15 +3,15 @@ use crate::models::actions::{CustomAction, DirectionVector};
 use crate::websocket::{
   client::{DirectionVectorSendChannelState, MessageSendChannelState},
   message::WebsocketMessage,
+  request::send_message,
 };
-use tauri::State;
 
 pub async fn handle_send_direction_vector(
-  state: &State<'_, DirectionVectorSendChannelState>,
+  state: &DirectionVectorSendChannelState,
   payload: DirectionVector,
 ) -> Result<(), String> {
-  let message = WebsocketMessage::DirectionVector(payload);
-  if let Err(e) = state.tx.send(message).await {
+  state.stats.received();
+  if let Err(e) = state.tx.send(payload) {
     log_error!("Failed to send DirectionVector: {}", e);
     return Err(e.to_string());
   }
@@ -19,46 +19,44 @@ pub async fn handle_send_direction_vector(
 }
 
 pub async fn handle_send_custom_action(
-  state: &State<'_, MessageSendChannelState>,
+  state: &MessageSendChannelState,
   payload: CustomAction,
 ) -> Result<(), String> {
   let message = WebsocketMessage::CustomAction(payload);
-  if let Err(e) = state.tx.send(message).await {
+  if let Err(e) = send_message(state, message) {
     log_error!("Failed to send CustomAction: {}", e);
-    return Err(e.to_string());
+    return Err(e);
   }
   Ok(())
 }
 
 pub async fn handle_toggle_pitch_stabilization(
-  state: &State<'_, MessageSendChannelState>,
+  state: &MessageSendChannelState,
 ) -> Result<(), String> {
   let message = WebsocketMessage::TogglePitchStabilization;
-  if let Err(e) = state.tx.send(message).await {
+  if let Err(e) = send_message(state, message) {
     log_error!("Failed to send TogglePitchStabilization: {}", e);
-    return Err(e.to_string());
+    return Err(e);
   }
   Ok(())
 }
 
 pub async fn handle_toggle_roll_stabilization(
-  state: &State<'_, MessageSendChannelState>,
+  state: &MessageSendChannelState,
 ) -> Result<(), String> {
   let message = WebsocketMessage::ToggleRollStabilization;
-  if let Err(e) = state.tx.send(message).await {
+  if let Err(e) = send_message(state, message) {
     log_error!("Failed to send ToggleRollStabilization: {}", e);
-    return Err(e.to_string());
+    return Err(e);
   }
   Ok(())
 }
 
-pub async fn handle_toggle_depth_hold(
-  state: &State<'_, MessageSendChannelState>,
-) -> Result<(), String> {
+pub async fn handle_toggle_depth_hold(state: &MessageSendChannelState) -> Result<(), String> {
   let message = WebsocketMessage::ToggleDepthHold;
-  if let Err(e) = state.tx.send(message).await {
+  if let Err(e) = send_message(state, message) {
     log_error!("Failed to send ToggleDepthHold: {}", e);
-    return Err(e.to_string());
+    return Err(e);
   }
   Ok(())
 }
@@ -1,85 +1,110 @@
 use crate::log_error;
-use crate::models::rov_config::{RovConfig, ThrusterTest, MicrocontrollerFirmwareVariant};
-use crate::websocket::{client::MessageSendChannelState, message::WebsocketMessage};
-use tauri::State;
+use crate::models::rov_config::{MicrocontrollerFirmwareVariant, RovConfig, ThrusterTest};
+use crate::websocket::{
+  client::MessageSendChannelState,
+  message::WebsocketMessage,
+  request::{expect_ack, send_request, unexpected_reply, REQUEST_TIMEOUT},
+};
+use std::time::Duration;
+
+const FLASH_FIRMWARE_TIMEOUT: Duration = Duration::from_secs(60);
 
 pub async fn handle_request_rov_config(
-  state: &State<'_, MessageSendChannelState>,
-) -> Result<(), String> {
+  state: &MessageSendChannelState,
+) -> Result<RovConfig, String> {
   let message = WebsocketMessage::GetConfig;
-  if let Err(e) = state.tx.send(message).await {
-    log_error!("Failed to send GetConfig: {}", e);
-    return Err(e.to_string());
+  match send_request(state, message, REQUEST_TIMEOUT).await {
+    Ok(WebsocketMessage::Config(config)) => Ok(config),
+    Ok(other) => Err(unexpected_reply(&other)),
+    Err(e) => {
+      log_error!("Failed to send GetConfig: {}", e);
+      Err(e)
+    }
   }
-  Ok(())
 }
 
 pub async fn handle_set_rov_config(
-  state: &State<'_, MessageSendChannelState>,
+  state: &MessageSendChannelState,
   payload: RovConfig,
-) -> Result<(), String> {
+) -> Result<RovConfig, String> {
   let message = WebsocketMessage::SetConfig(payload);
-  if let Err(e) = state.tx.send(message).await {
-    log_error!("Failed to send SetConfig: {}", e);
-    return Err(e.to_string());
+  match send_request(state, message, REQUEST_TIMEOUT).await {
+    Ok(WebsocketMessage::Config(config)) => Ok(config),
+    Ok(other) => Err(unexpected_reply(&other)),
+    Err(e) => {
+      log_error!("Failed to send SetConfig: {}", e);
+      Err(e)
+    }
   }
-  Ok(())
 }
 
 pub async fn handle_start_thruster_test(
-  state: &State<'_, MessageSendChannelState>,
+  state: &MessageSendChannelState,
   payload: ThrusterTest,
 ) -> Result<(), String> {
   let message = WebsocketMessage::StartThrusterTest(payload);
-  if let Err(e) = state.tx.send(message).await {
+  if let Err(e) = send_request(state, message, REQUEST_TIMEOUT)
+    .await
+    .and_then(expect_ack)
+  {
     log_error!("Failed to send StartThrusterTest: {}", e);
-    return Err(e.to_string());
+    return Err(e);
   }
   Ok(())
 }
 
 pub async fn handle_cancel_thruster_test(
-  state: &State<'_, MessageSendChannelState>,
+  state: &MessageSendChannelState,
   payload: ThrusterTest,
 ) -> Result<(), String> {
   let message = WebsocketMessage::CancelThrusterTest(payload);
-  if let Err(e) = state.tx.send(message).await {
+  if let Err(e) = send_request(state, message, REQUEST_TIMEOUT)
+    .await
+    .and_then(expect_ack)
+  {
     log_error!("Failed to send CancelThrusterTest: {}", e);
-    return Err(e.to_string());
+    return Err(e);
   }
   Ok(())
 }
 
 pub async fn handle_start_regulator_auto_tuning(
-  state: &State<'_, MessageSendChannelState>,
+  state: &MessageSendChannelState,
 ) -> Result<(), String> {
   let message = WebsocketMessage::StartRegulatorAutoTuning;
-  if let Err(e) = state.tx.send(message).await {
+  if let Err(e) = send_request(state, message, REQUEST_TIMEOUT)
+    .await
+    .and_then(expect_ack)
+  {
     log_error!("Failed to send StartRegulatorAutoTuning: {}", e);
-    return Err(e.to_string());
+    return Err(e);
   }
   Ok(())
 }
 
 pub async fn handle_cancel_regulator_auto_tuning(
-  state: &State<'_, MessageSendChannelState>,
+  state: &MessageSendChannelState,
 ) -> Result<(), String> {
   let message = WebsocketMessage::CancelRegulatorAutoTuning;
-  if let Err(e) = state.tx.send(message).await {
+  if let Err(e) = send_request(state, message, REQUEST_TIMEOUT)
+    .await
+    .and_then(expect_ack)
+  {
     log_error!("Failed to send CancelRegulatorAutoTuning: {}", e);
-    return Err(e.to_string());
+    return Err(e);
   }
   Ok(())
 }
 
 pub async fn handle_flash_microcontroller_firmware(
-  state: &State<'_, MessageSendChannelState>,
+  state: &MessageSendChannelState,
   payload: MicrocontrollerFirmwareVariant,
 ) -> Result<(), String> {
   let message = WebsocketMessage::FlashMicrocontrollerFirmware(payload);
-  if let Err(e) = state.tx.send(message).await {
+  let reply = send_request(state, message, FLASH_FIRMWARE_TIMEOUT).await;
+  if let Err(e) = reply.and_then(expect_ack) {
     log_error!("Failed to flash MicrocontrollerFirmware: {}", e);
-    return Err(e.to_string());
+    return Err(e);
   }
   Ok(())
 }
@@ -0,0 +1,285 @@
+use super::handler::{HandlerRegistry, HandlerResult, MessageHandler, FRONTEND_ORDER};
+use super::message::{WebsocketEnvelope, WebsocketMessage};
+use super::vehicle::VehicleEmitter;
+use crate::models::config::Config;
+use crate::models::rov_telemetry::RovTelemetry;
+use crate::models::telemetry_history::{TelemetryBucket, TelemetryField, TelemetrySeries};
+use crate::models::vehicle::VehicleId;
+use crate::util::{lock, now_ms};
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::{AppHandle, Manager, Runtime};
+
+#[derive(Clone, Copy)]
+struct Reading {
+  time_ms: u64,
+  value: f32,
+}
+
+// The readings of one vehicle, oldest first.
+type Series = BTreeMap<TelemetryField, VecDeque<Reading>>;
+
+// Keeps the last `telemetry_history_minutes` of every numeric reading of each
+// vehicle, so the frontend can draw graphs without a history of its own.
+pub struct TelemetryHistory {
+  retention: Mutex<Duration>,
+  vehicles: Mutex<BTreeMap<VehicleId, Series>>,
+}
+
+impl Default for TelemetryHistory {
+  fn default() -> Self {
+    TelemetryHistory {
+      retention: Mutex::new(retention(&Config::default())),
+      vehicles: Mutex::new(BTreeMap::new()),
+    }
+  }
+}
+
+impl TelemetryHistory {
+  pub fn configure(&self, config: &Config) {
+    *lock(&self.retention) = retention(config);
+  }
+
+  pub fn record(&self, vehicle: &str, readings: impl IntoIterator<Item = (TelemetryField, f32)>) {
+    let retention = *lock(&self.retention);
+    if retention.is_zero() {
+      return;
+    }
+    // Readings are kept in order, which the queries rely on.
+    let time_ms = now_ms();
+    let oldest_ms = time_ms.saturating_sub(retention.as_millis() as u64);
+    let mut vehicles = lock(&self.vehicles);
+    let series = vehicles.entry(vehicle.to_string()).or_default();
+    for (field, value) in readings {
+      let readings = series.entry(field).or_default();
+      while readings
+        .front()
+        .is_some_and(|reading| reading.time_ms < oldest_ms)
+      {
+        readings.pop_front();
+      }
+      readings.push_back(Reading { time_ms, value });
+    }
+  }
+
+  // Downsamples the readings of `fields` between `from_ms` and `to_ms` into at
+  // most `max_points` buckets of the same width each, for all of them to line
+  // up on one time axis. Without bounds everything kept is covered.
+  pub fn query(
+    &self,
+    vehicle: &str,
+    fields: &[TelemetryField],
+    from_ms: Option<u64>,
+    to_ms: Option<u64>,
+    max_points: u32,
+  ) -> Result<Vec<TelemetrySeries>, String> {
+    if max_points == 0 {
+      return Err("max_points must be at least 1".to_string());
+    }
+    let vehicles = lock(&self.vehicles);
+    let empty = Series::new();
+    let series = vehicles.get(vehicle).unwrap_or(&empty);
+    let kept = || fields.iter().filter_map(|field| series.get(field));
+    let from_ms = from_ms
+      .or_else(|| {
+        kept()
+          .filter_map(VecDeque::front)
+          .map(|reading| reading.time_ms)
+          .min()
+      })
+      .unwrap_or_default();
+    let to_ms = to_ms
+      .or_else(|| {
+        kept()
+          .filter_map(VecDeque::back)
+          .map(|reading| reading.time_ms)
+          .max()
+      })
+      .unwrap_or(from_ms);
+    if from_ms > to_ms {
+      return Err(format!("from {} is after to {}", from_ms, to_ms));
+    }
+
+    let width_ms = (to_ms - from_ms) / u64::from(max_points) + 1;
+    Ok(
+      fields
+        .iter()
+        .map(|&field| TelemetrySeries {
+          field,
+          buckets: series
+            .get(&field)
+            .map(|readings| buckets(readings, from_ms, to_ms, width_ms))
+            .unwrap_or_default(),
+        })
+        .collect(),
+    )
+  }
+
+  pub fn remove(&self, vehicle: &str) {
+    lock(&self.vehicles).remove(vehicle);
+  }
+}
+
+struct Bucket {
+  index: u64,
+  from_ms: u64,
+  to_ms: u64,
+  min: f32,
+  max: f32,
+  sum: f64,
+  count: u32,
+}
+
+impl Bucket {
+  fn new(index: u64, reading: &Reading) -> Self {
+    Bucket {
+      index,
+      from_ms: reading.time_ms,
+      to_ms: reading.time_ms,
+      min: reading.value,
+      max: reading.value,
+      sum: f64::from(reading.value),
+      count: 1,
+    }
+  }
+
+  fn add(&mut self, reading: &Reading) {
+    self.to_ms = reading.time_ms;
+    self.min = self.min.min(reading.value);
+    self.max = self.max.max(reading.value);
+    self.sum += f64::from(reading.value);
+    self.count += 1;
+  }
+
+  fn finish(self) -> TelemetryBucket {
+    TelemetryBucket {
+      from_ms: self.from_ms,
+      to_ms: self.to_ms,
+      min: self.min,
+      max: self.max,
+      mean: (self.sum / f64::from(self.count)) as f32,
+      count: self.count,
+    }
+  }
+}
+
+fn buckets(
+  readings: &VecDeque<Reading>,
+  from_ms: u64,
+  to_ms: u64,
+  width_ms: u64,
+) -> Vec<TelemetryBucket> {
+  let first = readings.partition_point(|reading| reading.time_ms < from_ms);
+  let mut buckets = Vec::new();
+  let mut current: Option<Bucket> = None;
+  for reading in readings
+    .range(first..)
+    .take_while(|reading| reading.time_ms <= to_ms)
+  {
+    let index = reading.time_ms.saturating_sub(from_ms) / width_ms;
+    match &mut current {
+      Some(bucket) if bucket.index == index => bucket.add(reading),
+      _ => {
+        if let Some(bucket) = current.replace(Bucket::new(index, reading)) {
+          buckets.push(bucket.finish());
+        }
+      }
+    }
+  }
+  buckets.extend(current.map(Bucket::finish));
+  buckets
+}
+
+pub fn telemetry_readings(telemetry: &RovTelemetry) -> Vec<(TelemetryField, f32)> {
+  let mut readings = vec![
+    (TelemetryField::Pitch, telemetry.pitch),
+    (TelemetryField::Roll, telemetry.roll),
+    (TelemetryField::DesiredPitch, telemetry.desired_pitch),
+    (TelemetryField::DesiredRoll, telemetry.desired_roll),
+    (TelemetryField::Depth, telemetry.depth),
+    (
+      TelemetryField::WorkIndicatorPercentage,
+      f32::from(telemetry.work_indicator_percentage),
+    ),
+  ];
+  let temperatures = [
+    (
+      TelemetryField::WaterTemperature,
+      telemetry.water_temperature,
+    ),
+    (
+      TelemetryField::ElectronicsTemperature,
+      telemetry.electronics_temperature,
+    ),
+  ];
+  readings.extend(
+    temperatures
+      .into_iter()
+      .filter_map(|(field, reading)| Some((field, reading?))),
+  );
+  readings.extend(
+    TelemetryField::THRUSTER_RPMS
+      .into_iter()
+      .zip(telemetry.thruster_rpms),
+  );
+  readings
+}
+
+pub struct TelemetryHistoryHandler {
+  pub history: Arc<TelemetryHistory>,
+}
+
+impl<R: Runtime> MessageHandler<R> for TelemetryHistoryHandler {
+  fn subscribes_to(&self, message: &WebsocketMessage) -> bool {
+    matches!(
+      message,
+      WebsocketMessage::Telemetry(_) | WebsocketMessage::StatusUpdate(_)
+    )
+  }
+
+  fn handle(&self, vehicle: &VehicleEmitter<R>, envelope: &WebsocketEnvelope) -> HandlerResult {
+    match &envelope.message {
+      WebsocketMessage::Telemetry(telemetry) => {
+        self
+          .history
+          .record(&vehicle.id, telemetry_readings(telemetry));
+      }
+      WebsocketMessage::StatusUpdate(status) => {
+        let battery = f32::from(status.battery_percentage);
+        self
+          .history
+          .record(&vehicle.id, [(TelemetryField::BatteryPercentage, battery)]);
+      }
+      _ => {}
+    }
+    Ok(Vec::new())
+  }
+
+  fn configured(&self, _vehicles: &[VehicleEmitter<R>], config: &Config) -> Result<(), String> {
+    self.history.configure(config);
+    Ok(())
+  }
+
+  fn closed(&self, vehicle: &str) -> Result<(), String> {
+    self.history.remove(vehicle);
+    Ok(())
+  }
+}
+
+// Keeps the recent readings of every vehicle, managed as `Arc<TelemetryHistory>`.
+pub fn register<R: Runtime>(app: &AppHandle<R>, handlers: &HandlerRegistry<R>) {
+  let history = Arc::new(TelemetryHistory::default());
+  handlers.register(
+    "telemetry_history",
+    FRONTEND_ORDER,
+    TelemetryHistoryHandler {
+      history: history.clone(),
+    },
+  );
+  app.manage(history);
+}
+
+fn retention(config: &Config) -> Duration {
+  Duration::from_secs(u64::from(config.telemetry_history_minutes) * 60)
+}
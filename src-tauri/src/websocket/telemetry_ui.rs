@@ -0,0 +1,187 @@
+use super::vehicle::VehicleEmitter;
+use crate::events::{ROV_TELEMETRY, ROV_TELEMETRY_BATCH};
+use crate::log_warn;
+use crate::models::config::Config;
+use crate::models::rov_telemetry::RovTelemetry;
+use crate::models::telemetry_ui::{TelemetryUiMode, TelemetryUiStats};
+use crate::models::vehicle::VehicleId;
+use crate::util::lock;
+use std::collections::BTreeMap;
+use std::mem;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::async_runtime::spawn;
+use tauri::Runtime;
+use tokio::time::sleep_until;
+
+#[derive(Clone, Copy)]
+struct Settings {
+  rate_hz: u32,
+  mode: TelemetryUiMode,
+}
+
+impl Default for Settings {
+  fn default() -> Self {
+    let config = Config::default();
+    Settings {
+      rate_hz: config.telemetry_ui_rate_hz,
+      mode: config.telemetry_ui_mode,
+    }
+  }
+}
+
+impl Settings {
+  fn interval(&self) -> Option<Duration> {
+    (self.rate_hz > 0).then(|| Duration::from_secs(1) / self.rate_hz)
+  }
+}
+
+enum Update {
+  Single(RovTelemetry),
+  Batch(Vec<RovTelemetry>),
+}
+
+// The frames of one vehicle waiting for the end of the interval.
+#[derive(Default)]
+struct Window {
+  pending: Vec<RovTelemetry>,
+  last_emitted: Option<Instant>,
+  flush_scheduled: bool,
+  stats: TelemetryUiStats,
+}
+
+impl Window {
+  fn take(&mut self, mode: TelemetryUiMode) -> Option<Update> {
+    let frames = mem::take(&mut self.pending);
+    let count = frames.len() as u64;
+    let latest = frames.last()?.clone();
+    self.last_emitted = Some(Instant::now());
+    self.stats.emitted += 1;
+    Some(match mode {
+      TelemetryUiMode::Latest => {
+        self.stats.dropped += count - 1;
+        Update::Single(latest)
+      }
+      TelemetryUiMode::Average => {
+        self.stats.dropped += count - 1;
+        Update::Single(average(&frames))
+      }
+      TelemetryUiMode::Batch => Update::Batch(frames),
+    })
+  }
+}
+
+// Passes telemetry on to the frontend at `telemetry_ui_rate_hz`, as a fast ROV
+// would otherwise flood the webview with events. A frame arriving after a
+// quiet interval is passed on at once, the ones following it within the
+// interval are held until it ends.
+#[derive(Default)]
+pub struct TelemetryUi {
+  settings: Mutex<Settings>,
+  vehicles: Mutex<BTreeMap<VehicleId, Arc<Mutex<Window>>>>,
+}
+
+impl TelemetryUi {
+  pub fn configure(&self, config: &Config) {
+    *lock(&self.settings) = Settings {
+      rate_hz: config.telemetry_ui_rate_hz,
+      mode: config.telemetry_ui_mode,
+    };
+  }
+
+  pub fn forward<R: Runtime>(
+    &self,
+    vehicle: &VehicleEmitter<R>,
+    telemetry: &RovTelemetry,
+  ) -> tauri::Result<()> {
+    let settings = *lock(&self.settings);
+    let window = self.window(&vehicle.id);
+    let mut state = lock(&window);
+    state.stats.received += 1;
+    state.pending.push(telemetry.clone());
+    if state.flush_scheduled {
+      return Ok(());
+    }
+
+    let due = match (settings.interval(), state.last_emitted) {
+      (Some(interval), Some(last_emitted)) => last_emitted + interval,
+      _ => Instant::now(),
+    };
+    if due <= Instant::now() {
+      let update = state.take(settings.mode);
+      drop(state);
+      return update.map_or(Ok(()), |update| emit(vehicle, update));
+    }
+
+    state.flush_scheduled = true;
+    drop(state);
+    let vehicle = vehicle.clone();
+    spawn(async move {
+      sleep_until(due.into()).await;
+      let update = {
+        let mut state = lock(&window);
+        state.flush_scheduled = false;
+        state.take(settings.mode)
+      };
+      if let Some(Err(e)) = update.map(|update| emit(&vehicle, update)) {
+        log_warn!("Failed to emit telemetry: {}", e);
+      }
+    });
+    Ok(())
+  }
+
+  pub fn stats(&self, vehicle: &str) -> TelemetryUiStats {
+    lock(&self.vehicles)
+      .get(vehicle)
+      .map(|window| lock(window).stats.clone())
+      .unwrap_or_default()
+  }
+
+  pub fn remove(&self, vehicle: &str) {
+    lock(&self.vehicles).remove(vehicle);
+  }
+
+  fn window(&self, vehicle: &str) -> Arc<Mutex<Window>> {
+    lock(&self.vehicles)
+      .entry(vehicle.to_string())
+      .or_default()
+      .clone()
+  }
+}
+
+fn emit<R: Runtime>(vehicle: &VehicleEmitter<R>, update: Update) -> tauri::Result<()> {
+  match update {
+    Update::Single(telemetry) => vehicle.emit(ROV_TELEMETRY, telemetry),
+    Update::Batch(frames) => vehicle.emit(ROV_TELEMETRY_BATCH, frames),
+  }
+}
+
+// Fields this app doesn't know can't be averaged, the latest frame's are kept.
+fn average(frames: &[RovTelemetry]) -> RovTelemetry {
+  let count = frames.len() as f32;
+  let mean = |field: &dyn Fn(&RovTelemetry) -> f32| frames.iter().map(field).sum::<f32>() / count;
+  let mean_reported = |field: &dyn Fn(&RovTelemetry) -> Option<f32>| {
+    let reported: Vec<f32> = frames.iter().filter_map(field).collect();
+    (!reported.is_empty()).then(|| reported.iter().sum::<f32>() / reported.len() as f32)
+  };
+  let mut thruster_rpms = [0.0; 8];
+  for (index, rpm) in thruster_rpms.iter_mut().enumerate() {
+    *rpm = mean(&|telemetry| telemetry.thruster_rpms[index]);
+  }
+  RovTelemetry {
+    pitch: mean(&|telemetry| telemetry.pitch),
+    roll: mean(&|telemetry| telemetry.roll),
+    desired_pitch: mean(&|telemetry| telemetry.desired_pitch),
+    desired_roll: mean(&|telemetry| telemetry.desired_roll),
+    depth: mean(&|telemetry| telemetry.depth),
+    water_temperature: mean_reported(&|telemetry| telemetry.water_temperature),
+    electronics_temperature: mean_reported(&|telemetry| telemetry.electronics_temperature),
+    thruster_rpms,
+    work_indicator_percentage: mean(&|telemetry| telemetry.work_indicator_percentage as f32).round()
+      as u8,
+    unknown_fields: frames
+      .last()
+      .map(|telemetry| telemetry.unknown_fields.clone())
+      .unwrap_or_default(),
+  }
+}
@@ -0,0 +1,137 @@
+use crate::models::vehicle::VehicleId;
+use crate::util::lock;
+use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+use rustls::crypto::{ring, verify_tls12_signature, verify_tls13_signature, CryptoProvider};
+use rustls::pki_types::{CertificateDer, ServerName, UnixTime};
+use rustls::{ClientConfig, DigitallySignedStruct, SignatureScheme};
+use sha2::{Digest, Sha256};
+use std::sync::{Arc, Mutex};
+use tokio_tungstenite::Connector;
+
+// Reported by a client that trusted a ROV's certificate on first use, so the
+// fingerprint can be pinned in the config.
+#[derive(Clone, Debug)]
+pub struct TrustedCertificate {
+  pub vehicle_id: VehicleId,
+  pub fingerprint: String,
+}
+
+// The fingerprint of the certificate the ROV presented on the last attempt.
+#[derive(Default, Debug)]
+pub struct PresentedCertificate {
+  fingerprint: Mutex<Option<String>>,
+}
+
+impl PresentedCertificate {
+  pub fn get(&self) -> Option<String> {
+    lock(&self.fingerprint).clone()
+  }
+
+  fn set(&self, fingerprint: String) {
+    *lock(&self.fingerprint) = Some(fingerprint);
+  }
+}
+
+// ROVs serve self-signed certificates, so instead of a CA chain the
+// certificate itself is trusted by its fingerprint. Without a pinned
+// fingerprint any certificate is accepted and recorded for pinning.
+#[derive(Debug)]
+struct PinnedCertificateVerifier {
+  pinned: Option<String>,
+  presented: Arc<PresentedCertificate>,
+  provider: Arc<CryptoProvider>,
+}
+
+impl ServerCertVerifier for PinnedCertificateVerifier {
+  fn verify_server_cert(
+    &self,
+    end_entity: &CertificateDer<'_>,
+    _intermediates: &[CertificateDer<'_>],
+    _server_name: &ServerName<'_>,
+    _ocsp_response: &[u8],
+    _now: UnixTime,
+  ) -> Result<ServerCertVerified, rustls::Error> {
+    let presented = fingerprint(end_entity);
+    self.presented.set(presented.clone());
+    match &self.pinned {
+      Some(pinned) if !same_fingerprint(pinned, &presented) => {
+        Err(rustls::Error::General(format!(
+          "ROV certificate {} does not match the pinned {}",
+          presented, pinned
+        )))
+      }
+      _ => Ok(ServerCertVerified::assertion()),
+    }
+  }
+
+  fn verify_tls12_signature(
+    &self,
+    message: &[u8],
+    cert: &CertificateDer<'_>,
+    dss: &DigitallySignedStruct,
+  ) -> Result<HandshakeSignatureValid, rustls::Error> {
+    verify_tls12_signature(
+      message,
+      cert,
+      dss,
+      &self.provider.signature_verification_algorithms,
+    )
+  }
+
+  fn verify_tls13_signature(
+    &self,
+    message: &[u8],
+    cert: &CertificateDer<'_>,
+    dss: &DigitallySignedStruct,
+  ) -> Result<HandshakeSignatureValid, rustls::Error> {
+    verify_tls13_signature(
+      message,
+      cert,
+      dss,
+      &self.provider.signature_verification_algorithms,
+    )
+  }
+
+  fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+    self
+      .provider
+      .signature_verification_algorithms
+      .supported_schemes()
+  }
+}
+
+// A connector trusting only the certificate with the `pinned` fingerprint, or
+// the first one presented when nothing is pinned yet.
+pub fn pinned_connector(
+  pinned: Option<&str>,
+  presented: Arc<PresentedCertificate>,
+) -> Result<Connector, String> {
+  let provider = Arc::new(ring::default_provider());
+  let verifier = PinnedCertificateVerifier {
+    pinned: pinned.map(str::to_string),
+    presented,
+    provider: provider.clone(),
+  };
+  let config = ClientConfig::builder_with_provider(provider)
+    .with_safe_default_protocol_versions()
+    .map_err(|e| e.to_string())?
+    .dangerous()
+    .with_custom_certificate_verifier(Arc::new(verifier))
+    .with_no_client_auth();
+  Ok(Connector::Rustls(Arc::new(config)))
+}
+
+// The SHA-256 fingerprint in the colon separated form `openssl x509
+// -fingerprint -sha256` prints, so it can be checked against the ROV.
+pub fn fingerprint(certificate: &[u8]) -> String {
+  Sha256::digest(certificate)
+    .iter()
+    .map(|byte| format!("{:02X}", byte))
+    .collect::<Vec<_>>()
+    .join(":")
+}
+
+pub fn same_fingerprint(a: &str, b: &str) -> bool {
+  let normalize = |fingerprint: &str| fingerprint.replace(':', "").to_ascii_uppercase();
+  normalize(a) == normalize(b)
+}
@@ -0,0 +1,119 @@
+use crate::models::vehicle::{VehicleEvent, VehicleId};
+use crate::util::lock;
+use serde::Serialize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Emitter, Runtime};
+
+#[derive(Default)]
+pub struct PilotVehicle {
+  current: Mutex<Option<VehicleId>>,
+}
+
+impl PilotVehicle {
+  pub fn get(&self) -> Option<VehicleId> {
+    lock(&self.current).clone()
+  }
+
+  pub fn set(&self, vehicle: Option<VehicleId>) {
+    *lock(&self.current) = vehicle;
+  }
+
+  pub fn is(&self, vehicle: &str) -> bool {
+    lock(&self.current).as_deref() == Some(vehicle)
+  }
+}
+
+// The vehicle a dive replay plays back as, if any, with the claim of that
+// replay. Its own events don't reach the frontend meanwhile.
+#[derive(Default)]
+pub struct ReplayedVehicle {
+  current: Mutex<Option<(VehicleId, u64)>>,
+  last_claim: AtomicU64,
+}
+
+impl ReplayedVehicle {
+  // Returns the claim to release the vehicle with once the replay ends.
+  pub fn claim(&self, vehicle: VehicleId) -> u64 {
+    let claim = self.last_claim.fetch_add(1, Ordering::Relaxed) + 1;
+    *lock(&self.current) = Some((vehicle, claim));
+    claim
+  }
+
+  // Leaves the vehicle replayed if another replay claimed it since.
+  pub fn release(&self, claim: u64) {
+    let mut current = lock(&self.current);
+    if current
+      .as_ref()
+      .is_some_and(|&(_, current)| current == claim)
+    {
+      *current = None;
+    }
+  }
+
+  pub fn is(&self, vehicle: &str) -> bool {
+    lock(&self.current)
+      .as_ref()
+      .is_some_and(|(current, _)| current == vehicle)
+  }
+}
+
+// Emits the events of one vehicle, tagged with its id. With `replay` set every
+// event is flagged as replayed.
+pub struct VehicleEmitter<R: Runtime> {
+  pub app: AppHandle<R>,
+  pub id: VehicleId,
+  pub pilot: Arc<PilotVehicle>,
+  pub replayed: Arc<ReplayedVehicle>,
+  pub replay: bool,
+}
+
+impl<R: Runtime> Clone for VehicleEmitter<R> {
+  fn clone(&self) -> Self {
+    VehicleEmitter {
+      app: self.app.clone(),
+      id: self.id.clone(),
+      pilot: self.pilot.clone(),
+      replayed: self.replayed.clone(),
+      replay: self.replay,
+    }
+  }
+}
+
+impl<R: Runtime> VehicleEmitter<R> {
+  pub fn emit<S: Serialize + Clone>(&self, event: &str, payload: S) -> tauri::Result<()> {
+    self.send(event, payload, self.replay)
+  }
+
+  // Emits an event replayed from a recording, flagged so it isn't taken for
+  // the vehicle's own.
+  pub fn emit_replay<S: Serialize + Clone>(&self, event: &str, payload: S) -> tauri::Result<()> {
+    self.send(event, payload, true)
+  }
+
+  // The same vehicle, for handlers that should flag what they emit as
+  // replayed.
+  pub fn for_replay(&self) -> Self {
+    VehicleEmitter {
+      replay: true,
+      ..self.clone()
+    }
+  }
+
+  // While a dive replay plays back as the vehicle, its own events are held
+  // back so the frontend never mixes them with the replayed ones.
+  fn send<S: Serialize + Clone>(&self, event: &str, payload: S, replay: bool) -> tauri::Result<()> {
+    if !replay && self.replayed.is(&self.id) {
+      return Ok(());
+    }
+    self.app.emit(
+      event,
+      VehicleEvent {
+        vehicle_id: Some(self.id.clone()),
+        pilot: self.pilot.is(&self.id),
+        replay,
+        payload,
+      },
+    )
+  }
+}
@@ -0,0 +1,61 @@
+use super::direction_vector::NEUTRAL_DIRECTION_VECTOR;
+use crate::models::actions::DirectionVector;
+use std::time::Duration;
+use tokio::time::{sleep_until, Instant};
+
+// Deadman for the direction vector input. It is only armed while the latest
+// vector asks for thrust, so an idle frontend does not trip it. Once tripped,
+// and on a fresh link, the ROV is held neutral until new input arrives.
+pub struct ControlWatchdog {
+  timeout: Duration,
+  deadline: Option<Instant>,
+  holding_neutral: bool,
+}
+
+impl ControlWatchdog {
+  pub fn new(timeout: Duration) -> Self {
+    ControlWatchdog {
+      timeout,
+      deadline: None,
+      holding_neutral: true,
+    }
+  }
+
+  pub fn set_timeout(&mut self, timeout: Duration) {
+    self.timeout = timeout;
+  }
+
+  pub fn feed(&mut self, direction_vector: &DirectionVector) {
+    self.holding_neutral = false;
+    self.deadline = if *direction_vector == NEUTRAL_DIRECTION_VECTOR {
+      None
+    } else {
+      Some(Instant::now() + self.timeout)
+    };
+  }
+
+  pub fn trip(&mut self) {
+    self.deadline = None;
+    self.holding_neutral = true;
+  }
+
+  pub fn deadline(&self) -> Option<Instant> {
+    self.deadline
+  }
+
+  pub fn apply(&self, direction_vector: DirectionVector) -> DirectionVector {
+    if self.holding_neutral {
+      NEUTRAL_DIRECTION_VECTOR
+    } else {
+      direction_vector
+    }
+  }
+}
+
+// Resolves at the deadline, never when the watchdog is disarmed.
+pub async fn expired(deadline: Option<Instant>) {
+  match deadline {
+    Some(deadline) => sleep_until(deadline).await,
+    None => std::future::pending().await,
+  }
+}
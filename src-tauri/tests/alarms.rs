@@ -0,0 +1,91 @@
+mod common;
+
+use common::{Client, MockRov};
+use manafish_lib::alarms::AlarmEngine;
+use manafish_lib::models::alarm::{AlarmCondition, AlarmField, AlarmRule, AlarmSeverity};
+use manafish_lib::models::config::Config;
+use manafish_lib::models::telemetry_history::TelemetryField;
+use std::sync::Arc;
+use std::time::Duration;
+
+fn warm_electronics(threshold: f32) -> AlarmRule {
+  AlarmRule {
+    duration_ms: 200,
+    hysteresis: 1.0,
+    ..AlarmRule::new(
+      "Electronics warm",
+      AlarmField::Reading(TelemetryField::ElectronicsTemperature),
+      AlarmCondition::Above,
+      threshold,
+      AlarmSeverity::Warning,
+    )
+  }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn alarm_is_raised_acknowledged_and_cleared() {
+  let rov = MockRov::start(r#"{ "events": [] }"#);
+  // The mock runs its electronics at 35 degrees and up.
+  let client = Client::connect_with(rov.port, |config| {
+    config.alarm_rules = vec![warm_electronics(30.0)];
+  });
+  let raised = client
+    .wait_for_event(Duration::from_secs(5), "alarm_changed")
+    .await;
+  assert_eq!(raised["rule"], "Electronics warm");
+  assert_eq!(raised["severity"], "warning");
+  assert!(raised["clearedMs"].is_null());
+
+  let toasts = client.events("show_toast");
+  assert!(
+    toasts
+      .iter()
+      .any(|toast| toast["id"] == format!("alarm_{}", raised["id"])),
+    "{:?}",
+    toasts
+  );
+  let logged = client.events("log_message");
+  assert!(
+    logged.iter().any(|entry| entry["message"]
+      .as_str()
+      .unwrap()
+      .contains("Electronics warm")),
+    "{:?}",
+    logged
+  );
+
+  let engine: Arc<AlarmEngine> = client.state();
+  let alarms = engine.list();
+  assert_eq!(alarms.len(), 1);
+  let acknowledged = engine.acknowledge(alarms[0].id).unwrap();
+  assert!(acknowledged.acknowledged_ms.is_some());
+  assert_eq!(engine.list().len(), 1);
+  assert!(engine.acknowledge(alarms[0].id + 1).is_err());
+
+  // Changing the rule clears its alarm, which being acknowledged is gone.
+  client
+    .manager
+    .apply_config(Config {
+      alarm_rules: vec![warm_electronics(90.0)],
+      ..client.config.clone()
+    })
+    .await;
+  let changes = client
+    .wait_for_events(Duration::from_secs(5), "alarm_changed", |changes| {
+      changes.iter().any(|change| !change["clearedMs"].is_null())
+    })
+    .await;
+  assert!(changes.iter().all(|change| change["id"] == raised["id"]));
+  assert!(engine.list().is_empty());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn default_rules_stay_quiet_on_a_healthy_vehicle() {
+  let rov = MockRov::start_with_args(r#"{ "events": [] }"#, &["--status-rate", "10"]);
+  let client = Client::connect(rov.port);
+  client.wait_connected().await;
+  tokio::time::sleep(Duration::from_millis(1000)).await;
+
+  assert!(client.state::<Arc<AlarmEngine>>().list().is_empty());
+  assert!(client.events("alarm_changed").is_empty());
+}
@@ -0,0 +1,77 @@
+mod common;
+
+use common::{Client, MockRov};
+use manafish_lib::alarms::AlarmEngine;
+use manafish_lib::battery::BatteryEstimator;
+use manafish_lib::websocket::manager::PRIMARY_VEHICLE;
+use manafish_lib::websocket::send::config::handle_request_rov_config;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::sleep;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn estimate_forecasts_the_dive_and_calls_it_back_up() {
+  // Idling drains 2 percent a second, gone within a minute and so short of
+  // the 2 minute reserve.
+  let rov = MockRov::start_with_args(
+    r#"{ "events": [] }"#,
+    &["--status-rate", "10", "--battery-drain", "400"],
+  );
+  let client = Client::connect(rov.port);
+  client.wait_connected().await;
+  let rov_config = handle_request_rov_config(&client.messages).await.unwrap();
+  let estimates = client
+    .wait_for_events(Duration::from_secs(5), "battery_estimate", |estimates| {
+      estimates.last().is_some_and(|estimate| {
+        !estimate["drainPerMinute"].is_null() && !estimate["voltage"].is_null()
+      })
+    })
+    .await;
+
+  let estimate = estimates.last().unwrap();
+  let drain = estimate["drainPerMinute"].as_f64().unwrap();
+  assert!((60.0..240.0).contains(&drain), "{}", estimate);
+  let percentage = estimate["batteryPercentage"].as_f64().unwrap();
+  let (min, max) = (
+    f64::from(rov_config.power.battery_min_voltage),
+    f64::from(rov_config.power.battery_max_voltage),
+  );
+  let voltage = estimate["voltage"].as_f64().unwrap();
+  assert!((voltage - (min + (max - min) * percentage / 100.0)).abs() < 0.01);
+  let minimum_voltage_in_ms = estimate["minimumVoltageInMs"].as_f64().unwrap();
+  assert!((minimum_voltage_in_ms - percentage / drain * 60_000.0).abs() < 100.0);
+  // Ascending from the surface takes next to nothing on top of the reserve.
+  let return_to_surface_ms = estimate["returnToSurfaceMs"].as_u64().unwrap();
+  assert!((120_000..121_000).contains(&return_to_surface_ms));
+  assert_eq!(estimate["remainingDiveMs"], 0);
+
+  let alarms = client.state::<Arc<AlarmEngine>>().list();
+  assert!(
+    alarms.iter().any(|alarm| alarm.rule == "Return to surface"),
+    "{:?}",
+    alarms
+  );
+  assert!(client
+    .state::<Arc<BatteryEstimator>>()
+    .get(PRIMARY_VEHICLE)
+    .is_some());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn no_forecast_before_the_battery_drained() {
+  let rov = MockRov::start_with_args(r#"{ "events": [] }"#, &["--status-rate", "10"]);
+  let client = Client::connect(rov.port);
+  client
+    .wait_for_event(Duration::from_secs(5), "battery_estimate")
+    .await;
+  sleep(Duration::from_millis(500)).await;
+
+  let estimator: Arc<BatteryEstimator> = client.state();
+  let estimate = estimator.get(PRIMARY_VEHICLE).unwrap();
+  assert_eq!(estimate.battery_percentage, 100);
+  assert_eq!(estimate.voltage, None);
+  assert_eq!(estimate.drain_per_minute, None);
+  assert_eq!(estimate.remaining_dive_ms, None);
+  assert!(client.state::<Arc<AlarmEngine>>().list().is_empty());
+  assert!(estimator.get("missing").is_none());
+}
@@ -0,0 +1,156 @@
+use manafish_protocol::bindings::{command_signatures, typescript, without_spaces, BINDINGS_PATH};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+
+// Argument names and types, and what a command returns on success.
+type Signature = (Vec<(String, String)>, String);
+
+#[test]
+fn checked_in_bindings_match_the_models() {
+  let checked_in = fs::read_to_string(BINDINGS_PATH).expect("missing src/lib/bindings.ts");
+  assert!(
+    checked_in == typescript(),
+    "src/lib/bindings.ts is out of date, run `bun run bindings`"
+  );
+}
+
+#[test]
+fn bindings_match_the_registered_commands() {
+  let bindings: BTreeMap<_, _> = command_signatures()
+    .into_iter()
+    .map(|command| {
+      let mut args = command.args;
+      args.sort();
+      (command.name, (args, command.returns))
+    })
+    .collect();
+
+  let registered = registered_commands();
+  let bound: BTreeSet<_> = bindings.keys().cloned().collect();
+  assert_eq!(
+    bound, registered,
+    "the bindings list other commands than `generate_handler!`"
+  );
+
+  let declared: BTreeMap<_, _> = declared_commands()
+    .into_iter()
+    .filter(|(name, _)| registered.contains(name))
+    .collect();
+  assert_eq!(
+    bindings, declared,
+    "the bindings differ from the commands' signatures"
+  );
+}
+
+// The commands `run` hands to `generate_handler!`.
+fn registered_commands() -> BTreeSet<String> {
+  let lib = fs::read_to_string("src/lib.rs").expect("missing src/lib.rs");
+  let (_, list) = lib
+    .split_once("generate_handler![")
+    .expect("no generate_handler! in src/lib.rs");
+  let (list, _) = list
+    .split_once(']')
+    .expect("unterminated generate_handler!");
+  list
+    .split(',')
+    .map(str::trim)
+    .filter(|name| !name.is_empty())
+    .map(str::to_string)
+    .collect()
+}
+
+// The `#[command]` functions of src/commands, by name.
+fn declared_commands() -> BTreeMap<String, Signature> {
+  let mut commands = BTreeMap::new();
+  for entry in fs::read_dir("src/commands").expect("missing src/commands") {
+    let source = fs::read_to_string(entry.expect("unreadable src/commands").path())
+      .expect("unreadable command module");
+    let source: String = source
+      .lines()
+      .map(|line| line.split("//").next().unwrap_or_default())
+      .collect::<Vec<_>>()
+      .join("\n");
+    for command in source.split("#[command]").skip(1) {
+      let (name, signature) = parse_command(command);
+      commands.insert(name, signature);
+    }
+  }
+  commands
+}
+
+fn parse_command(source: &str) -> (String, Signature) {
+  let (_, rest) = source
+    .split_once("fn ")
+    .expect("#[command] without a function");
+  let name_end = rest.find(['<', '(']).expect("function without arguments");
+  let name = rest[..name_end].trim().to_string();
+
+  let open = rest.find('(').expect("function without arguments");
+  let mut depth = 0;
+  let close = open
+    + rest[open..]
+      .find(|c| {
+        match c {
+          '(' => depth += 1,
+          ')' => depth -= 1,
+          _ => {}
+        }
+        depth == 0
+      })
+      .expect("unterminated arguments");
+
+  let mut args: Vec<_> = split_top_level(&rest[open + 1..close])
+    .into_iter()
+    .filter_map(|arg| {
+      let (name, ty) = arg.split_once(':')?;
+      let ty = without_spaces(ty);
+      let injected = ["State<", "AppHandle", "Window", "Webview"];
+      if injected.iter().any(|prefix| ty.starts_with(prefix)) {
+        return None;
+      }
+      Some((name.trim().trim_start_matches("mut ").to_string(), ty))
+    })
+    .collect();
+  args.sort();
+
+  let returns = rest[close + 1..]
+    .split('{')
+    .next()
+    .unwrap_or_default()
+    .trim()
+    .trim_start_matches("->");
+  let returns = without_spaces(returns);
+  let returns = match returns
+    .strip_prefix("Result<")
+    .and_then(|ok| ok.strip_suffix(",String>"))
+  {
+    Some(ok) => ok.to_string(),
+    None if returns.is_empty() => "()".to_string(),
+    None => returns,
+  };
+  (name, (args, returns))
+}
+
+// Splits arguments on the commas outside of generic arguments and tuples.
+fn split_top_level(args: &str) -> Vec<&str> {
+  let mut parts = Vec::new();
+  let mut depth = 0;
+  let mut start = 0;
+  for (i, c) in args.char_indices() {
+    match c {
+      '<' | '(' => depth += 1,
+      '>' | ')' => depth -= 1,
+      ',' if depth == 0 => {
+        parts.push(&args[start..i]);
+        start = i + 1;
+      }
+      _ => {}
+    }
+  }
+  parts.push(&args[start..]);
+  parts
+    .into_iter()
+    .map(str::trim)
+    .filter(|part| !part.is_empty())
+    .collect()
+}
@@ -0,0 +1,129 @@
+mod common;
+
+use common::{temp_directory, Client, MockRov};
+use manafish_lib::models::capture::{CapturedFrame, FrameDirection, FrameKind};
+use manafish_lib::models::telemetry_history::TelemetryField;
+use manafish_lib::websocket::capture::{read_capture, TrafficCapture};
+use manafish_lib::websocket::manager::PRIMARY_VEHICLE;
+use manafish_lib::websocket::replay::replay_capture;
+use manafish_lib::websocket::telemetry_history::TelemetryHistory;
+use manafish_lib::websocket::telemetry_ui::TelemetryUi;
+use std::fs;
+use std::net::TcpListener;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::time::sleep;
+use tokio_tungstenite::tungstenite::Message;
+
+fn capture_files(directory: &PathBuf) -> Vec<PathBuf> {
+  let mut files: Vec<_> = fs::read_dir(directory)
+    .expect("no capture directory")
+    .map(|entry| entry.unwrap().path())
+    .collect();
+  files.sort();
+  files
+}
+
+fn is_telemetry(frame: &CapturedFrame) -> bool {
+  frame.direction == FrameDirection::Inbound
+    && frame.kind == FrameKind::Text
+    && frame.text.contains(r#""type":"telemetry""#)
+}
+
+// Captures a short session with the mock ROV.
+async fn capture_session(directory: &PathBuf) -> Vec<CapturedFrame> {
+  let rov = MockRov::start(r#"{ "events": [] }"#);
+  let client = Client::connect_with(rov.port, |config| {
+    config.capture_traffic = true;
+    config.capture_directory = directory.to_string_lossy().into_owned();
+  });
+  client
+    .wait_for_events(Duration::from_secs(3), "rov_telemetry", |telemetry| {
+      telemetry.len() >= 10
+    })
+    .await;
+  drop(client);
+  read_capture(&capture_files(directory)).expect("unreadable capture")
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn capture_records_both_directions() {
+  let directory = temp_directory("capture", "session");
+  let frames = capture_session(&directory).await;
+
+  assert!(frames.iter().filter(|frame| is_telemetry(frame)).count() >= 10);
+  assert!(frames
+    .iter()
+    .any(|frame| frame.direction == FrameDirection::Outbound && frame.kind == FrameKind::Text));
+  assert!(frames
+    .windows(2)
+    .all(|pair| pair[0].elapsed_ms <= pair[1].elapsed_ms));
+  let _ = fs::remove_dir_all(&directory);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn capture_rotates_files() {
+  let directory = temp_directory("capture", "rotation");
+  let mut capture = TrafficCapture::open(&directory, "rov", 200, 3).unwrap();
+  for index in 0..20 {
+    let text = format!(r#"{{"type":"logMessage","payload":"{:0>60}"}}"#, index);
+    capture
+      .record(FrameDirection::Inbound, &Message::Text(text.into()))
+      .unwrap();
+  }
+
+  let files = capture.files();
+  assert_eq!(files.len(), 3);
+  let mut sorted = files.clone();
+  sorted.sort();
+  assert_eq!(capture_files(&directory), sorted);
+  for file in &files {
+    assert!(fs::metadata(file).unwrap().len() <= 200);
+  }
+  // Only the newest frames survive, still in order.
+  let frames = read_capture(&files).unwrap();
+  assert!(frames
+    .last()
+    .unwrap()
+    .text
+    .contains(&format!("{:0>60}", 19)));
+  let _ = fs::remove_dir_all(&directory);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn replay_emits_captured_telemetry() {
+  let directory = temp_directory("capture", "replay");
+  let frames = capture_session(&directory).await;
+  let telemetry = frames.iter().filter(|frame| is_telemetry(frame)).count();
+  let span = frames.last().unwrap().elapsed_ms - frames.first().unwrap().elapsed_ms;
+
+  // A client without a ROV to talk to, so all telemetry comes from the replay.
+  let port = TcpListener::bind("127.0.0.1:0")
+    .unwrap()
+    .local_addr()
+    .unwrap()
+    .port();
+  // Replayed at ten times the pace, every frame should still reach the frontend.
+  let client = Client::connect_with(port, |config| config.telemetry_ui_rate_hz = 0);
+  let emitter = client.manager.emitter(None).unwrap();
+  let telemetry_ui = Arc::new(TelemetryUi::default());
+  telemetry_ui.configure(&client.config);
+
+  let started = Instant::now();
+  let replayed = replay_capture(&emitter, telemetry_ui, &frames, 10.0).await;
+  assert!(started.elapsed() < Duration::from_millis(span / 10 + 500));
+  sleep(Duration::from_millis(100)).await;
+
+  assert!(replayed >= telemetry);
+  let events = client.vehicle_events("rov_telemetry");
+  assert_eq!(events.len(), telemetry);
+  assert!(events.iter().all(|event| event.replay));
+  // The vehicle's own handlers never see the replayed frames.
+  let history = client
+    .state::<Arc<TelemetryHistory>>()
+    .query(PRIMARY_VEHICLE, &[TelemetryField::Depth], None, None, 10)
+    .unwrap();
+  assert!(history[0].buckets.is_empty());
+  let _ = fs::remove_dir_all(&directory);
+}
@@ -0,0 +1,343 @@
+// Each test binary only uses part of the harness.
+#![allow(dead_code)]
+
+use manafish_lib::models::config::Config;
+use manafish_lib::models::vehicle::VehicleEvent;
+use manafish_lib::register_subsystems;
+use manafish_lib::websocket::manager::{ConnectionManager, VehicleConnection, PRIMARY_VEHICLE};
+use manafish_lib::websocket::tls::TrustedCertificate;
+use serde::Deserialize;
+use std::fs;
+use std::io::{BufRead, BufReader};
+use std::ops::Deref;
+use std::path::PathBuf;
+use std::process::{Child, Command, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::async_runtime::spawn;
+use tauri::test::{mock_app, MockRuntime};
+use tauri::{App, Listener, Manager};
+use tokio::sync::mpsc::channel;
+use tokio::time::{sleep, Instant};
+
+// Events recorded besides the connection status, see `Client::events`.
+const RECORDED_EVENTS: [&str; 11] = [
+  "rov_handshake_completed",
+  "rov_connection_state_changed",
+  "link_quality",
+  "rov_telemetry",
+  "rov_telemetry_batch",
+  "rov_unknown_message",
+  "log_message",
+  "dive_replay_state",
+  "alarm_changed",
+  "battery_estimate",
+  "show_toast",
+];
+
+static SCENARIO_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+pub struct MockRov {
+  child: Child,
+  scenario_path: PathBuf,
+  pub port: u16,
+  pub discovery_port: Option<u16>,
+}
+
+impl MockRov {
+  pub fn start(scenario: &str) -> Self {
+    Self::start_with_args(scenario, &[])
+  }
+
+  pub fn start_with_args(scenario: &str, args: &[&str]) -> Self {
+    let scenario_path = std::env::temp_dir().join(format!(
+      "manafish-scenario-{}-{}.json",
+      std::process::id(),
+      SCENARIO_COUNTER.fetch_add(1, Ordering::SeqCst)
+    ));
+    fs::write(&scenario_path, scenario).expect("failed to write scenario");
+
+    let mut child = Command::new(mock_rov_binary())
+      .args(["--port", "0", "--scenario"])
+      .arg(&scenario_path)
+      .args(args)
+      .stdout(Stdio::piped())
+      .stderr(Stdio::null())
+      .spawn()
+      .expect("failed to start mock ROV");
+
+    let mut stdout = BufReader::new(child.stdout.take().expect("mock ROV has no stdout"));
+    let port = read_port(&mut stdout);
+    let discovery_port = args
+      .contains(&"--discovery-port")
+      .then(|| read_port(&mut stdout));
+
+    MockRov {
+      child,
+      scenario_path,
+      port,
+      discovery_port,
+    }
+  }
+}
+
+// A mock ROV sending status updates ten times a second, and a client that
+// completed the handshake with it.
+pub async fn connected_rov(configure: impl FnOnce(&mut Config)) -> (MockRov, Client) {
+  let rov = MockRov::start_with_args(r#"{ "events": [] }"#, &["--status-rate", "10"]);
+  let client = Client::connect_with(rov.port, configure);
+  client.wait_connected().await;
+  (rov, client)
+}
+
+// An empty directory under the system's temporary one, named after the test
+// and this process so test binaries running at once don't share it.
+pub fn temp_directory(prefix: &str, name: &str) -> PathBuf {
+  let directory = std::env::temp_dir().join(format!(
+    "manafish-{}-{}-{}",
+    prefix,
+    name,
+    std::process::id()
+  ));
+  let _ = fs::remove_dir_all(&directory);
+  directory
+}
+
+// The mock ROV belongs to the tools crate, which `cargo build --workspace`
+// builds into the same directory as the test binaries' `deps`.
+fn mock_rov_binary() -> PathBuf {
+  let test_binary = std::env::current_exe().expect("no test binary path");
+  let path = test_binary
+    .ancestors()
+    .nth(2)
+    .expect("test binary outside a target directory")
+    .join(format!("manafish-mock-rov{}", std::env::consts::EXE_SUFFIX));
+  assert!(
+    path.exists(),
+    "{} is missing, build it with `cargo build -p manafish-tools`",
+    path.display()
+  );
+  path
+}
+
+// Reads the port from the next address line the mock ROV prints.
+fn read_port(stdout: &mut impl BufRead) -> u16 {
+  let mut line = String::new();
+  stdout
+    .read_line(&mut line)
+    .expect("failed to read mock ROV address");
+  line
+    .trim()
+    .rsplit(':')
+    .next()
+    .and_then(|port| port.parse().ok())
+    .unwrap_or_else(|| panic!("unexpected mock ROV output: {}", line))
+}
+
+impl Drop for MockRov {
+  fn drop(&mut self) {
+    let _ = self.child.kill();
+    let _ = self.child.wait();
+    let _ = fs::remove_file(&self.scenario_path);
+  }
+}
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ConnectionStatus {
+  pub is_connected: bool,
+  pub delay: Option<u64>,
+}
+
+type RecordedEvent = (&'static str, VehicleEvent<serde_json::Value>);
+
+// The primary vehicle of a connection manager, which the tests use like the
+// single client it used to be.
+pub struct Client {
+  app: App<MockRuntime>,
+  pub config: Config,
+  pub manager: Arc<ConnectionManager<MockRuntime>>,
+  vehicle: Arc<VehicleConnection>,
+  statuses: Arc<Mutex<Vec<ConnectionStatus>>>,
+  events: Arc<Mutex<Vec<RecordedEvent>>>,
+  certificates: Arc<Mutex<Vec<TrustedCertificate>>>,
+}
+
+impl Deref for Client {
+  type Target = VehicleConnection;
+
+  fn deref(&self) -> &VehicleConnection {
+    &self.vehicle
+  }
+}
+
+impl Client {
+  pub fn connect(port: u16) -> Self {
+    Self::connect_with(port, |_| {})
+  }
+
+  pub fn connect_with(port: u16, configure: impl FnOnce(&mut Config)) -> Self {
+    let app = mock_app();
+    let handle = app.handle().clone();
+
+    let statuses = Arc::new(Mutex::new(Vec::new()));
+    let recorded = statuses.clone();
+    handle.listen_any("rov_connection_status_updated", move |event| {
+      let status: VehicleEvent<ConnectionStatus> =
+        serde_json::from_str(event.payload()).expect("invalid connection status");
+      if status.vehicle_id.as_deref() == Some(PRIMARY_VEHICLE) {
+        recorded.lock().unwrap().push(status.payload);
+      }
+    });
+
+    let events = Arc::new(Mutex::new(Vec::new()));
+    for name in RECORDED_EVENTS {
+      let recorded = events.clone();
+      handle.listen_any(name, move |event| {
+        let event = serde_json::from_str(event.payload()).expect("invalid event payload");
+        recorded.lock().unwrap().push((name, event));
+      });
+    }
+
+    let mut config = Config {
+      ip_address: "127.0.0.1".to_string(),
+      web_socket_port: port,
+      ..Config::default()
+    };
+    configure(&mut config);
+    let certificates = Arc::new(Mutex::new(Vec::new()));
+    let recorded = certificates.clone();
+    let (certificates_tx, mut certificates_rx) = channel(1);
+    spawn(async move {
+      while let Some(trusted) = certificates_rx.recv().await {
+        recorded.lock().unwrap().push(trusted);
+      }
+    });
+
+    let manager = Arc::new(ConnectionManager::new(handle.clone(), certificates_tx));
+    register_subsystems(&handle, &manager.handlers());
+    let vehicle = manager
+      .open(PRIMARY_VEHICLE.to_string(), None, &config)
+      .expect("failed to open the primary vehicle");
+
+    Client {
+      app,
+      config,
+      manager,
+      vehicle,
+      statuses,
+      events,
+      certificates,
+    }
+  }
+
+  // What the app keeps as managed state, e.g. `Arc<AlarmEngine>`.
+  pub fn state<T: Clone + Send + Sync + 'static>(&self) -> T {
+    self.app.state::<T>().inner().clone()
+  }
+
+  // Events of the primary vehicle.
+  pub fn events(&self, name: &str) -> Vec<serde_json::Value> {
+    self
+      .vehicle_events(name)
+      .into_iter()
+      .filter(|event| event.vehicle_id.as_deref() == Some(PRIMARY_VEHICLE))
+      .map(|event| event.payload)
+      .collect()
+  }
+
+  pub fn vehicle_events(&self, name: &str) -> Vec<VehicleEvent<serde_json::Value>> {
+    self
+      .events
+      .lock()
+      .unwrap()
+      .iter()
+      .filter(|(event, _)| *event == name)
+      .map(|(_, event)| event.clone())
+      .collect()
+  }
+
+  // Waits for the first handshake of the primary vehicle and returns it. Long
+  // enough for a legacy ROV, which is only taken as connected once the
+  // handshake timed out.
+  pub async fn wait_connected(&self) -> serde_json::Value {
+    self
+      .wait_for_event(Duration::from_secs(6), "rov_handshake_completed")
+      .await
+  }
+
+  pub async fn wait_for_event(&self, timeout: Duration, name: &str) -> serde_json::Value {
+    self
+      .wait_for_events(timeout, name, |events| !events.is_empty())
+      .await
+      .remove(0)
+  }
+
+  pub async fn wait_for_events(
+    &self,
+    timeout: Duration,
+    name: &str,
+    condition: impl Fn(&[serde_json::Value]) -> bool,
+  ) -> Vec<serde_json::Value> {
+    let deadline = Instant::now() + timeout;
+    loop {
+      let events = self.events(name);
+      if condition(&events) {
+        return events;
+      }
+      if Instant::now() >= deadline {
+        panic!(
+          "{} events not as expected within {:?}, got {:?}",
+          name, timeout, events
+        );
+      }
+      sleep(Duration::from_millis(50)).await;
+    }
+  }
+
+  // Certificates the clients trusted on first use.
+  pub fn trusted_certificates(&self) -> Vec<TrustedCertificate> {
+    self.certificates.lock().unwrap().clone()
+  }
+
+  pub fn statuses(&self) -> Vec<ConnectionStatus> {
+    self.statuses.lock().unwrap().clone()
+  }
+
+  pub async fn wait_for(
+    &self,
+    timeout: Duration,
+    condition: impl Fn(&[ConnectionStatus]) -> bool,
+  ) -> Vec<ConnectionStatus> {
+    let deadline = Instant::now() + timeout;
+    loop {
+      let statuses = self.statuses();
+      if condition(&statuses) {
+        return statuses;
+      }
+      if Instant::now() >= deadline {
+        panic!("condition not met within {:?}, got {:?}", timeout, statuses);
+      }
+      sleep(Duration::from_millis(50)).await;
+    }
+  }
+}
+
+pub fn connected_count(statuses: &[ConnectionStatus]) -> usize {
+  statuses.iter().filter(|status| status.is_connected).count()
+}
+
+pub fn disconnected_count(statuses: &[ConnectionStatus]) -> usize {
+  statuses
+    .iter()
+    .filter(|status| !status.is_connected)
+    .count()
+}
+
+pub fn phases(states: &[serde_json::Value]) -> Vec<&str> {
+  states
+    .iter()
+    .filter_map(|state| state["phase"].as_str())
+    .collect()
+}
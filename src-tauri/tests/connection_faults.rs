@@ -0,0 +1,199 @@
+mod common;
+
+use common::{connected_count, disconnected_count, phases, Client, MockRov};
+use std::time::Duration;
+use tokio::time::sleep;
+
+// The client pings every 2 seconds and only reports `isConnected: true` once a
+// pong arrives, so the first connected status shows up roughly 2 seconds in.
+const FIRST_PONG: Duration = Duration::from_secs(5);
+
+#[tokio::test(flavor = "multi_thread")]
+async fn healthy_link_reports_round_trip_time() {
+  let rov = MockRov::start(r#"{ "events": [] }"#);
+  let client = Client::connect(rov.port);
+
+  let statuses = client
+    .wait_for(FIRST_PONG, |statuses| connected_count(statuses) >= 1)
+    .await;
+
+  let delay = statuses[0].delay.expect("connected status without delay");
+  assert!(delay < 200, "unexpected round trip time {}", delay);
+  assert_eq!(disconnected_count(&statuses), 0);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn latency_is_reported_as_delay() {
+  let rov = MockRov::start(r#"{ "events": [{ "at": 0, "fault": "latency", "millis": 400 }] }"#);
+  let client = Client::connect(rov.port);
+
+  let statuses = client
+    .wait_for(FIRST_PONG, |statuses| connected_count(statuses) >= 1)
+    .await;
+
+  let delay = statuses[0].delay.expect("connected status without delay");
+  assert!((400..1000).contains(&delay), "unexpected delay {}", delay);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn jitter_spreads_reported_delay() {
+  let rov = MockRov::start(
+    r#"{ "events": [
+      { "at": 0, "fault": "latency", "millis": 100 },
+      { "at": 0, "fault": "jitter", "millis": 300 }
+    ] }"#,
+  );
+  let client = Client::connect(rov.port);
+
+  let statuses = client
+    .wait_for(Duration::from_secs(10), |statuses| {
+      connected_count(statuses) >= 3
+    })
+    .await;
+
+  for status in statuses.iter().filter(|status| status.is_connected) {
+    let delay = status.delay.expect("connected status without delay");
+    assert!((100..600).contains(&delay), "unexpected delay {}", delay);
+  }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn dropped_frames_keep_connection_up() {
+  let rov = MockRov::start(r#"{ "events": [{ "at": 0, "fault": "dropFrames", "ratio": 1.0 }] }"#);
+  let client = Client::connect(rov.port);
+
+  client
+    .wait_for(FIRST_PONG, |statuses| connected_count(statuses) >= 1)
+    .await;
+  sleep(Duration::from_secs(3)).await;
+
+  assert_eq!(disconnected_count(&client.statuses()), 0);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn malformed_json_is_ignored() {
+  let rov = MockRov::start(
+    r#"{ "events": [
+      { "at": 500, "fault": "malformedJson" },
+      { "at": 1000, "fault": "malformedJson" },
+      { "at": 2500, "fault": "malformedJson" }
+    ] }"#,
+  );
+  let client = Client::connect(rov.port);
+
+  client
+    .wait_for(FIRST_PONG, |statuses| connected_count(statuses) >= 2)
+    .await;
+
+  assert_eq!(disconnected_count(&client.statuses()), 0);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn unknown_message_types_are_ignored() {
+  let rov = MockRov::start(
+    r#"{ "events": [
+      { "at": 500, "fault": "unknownMessage" },
+      { "at": 2500, "fault": "unknownMessage" }
+    ] }"#,
+  );
+  let client = Client::connect(rov.port);
+
+  client
+    .wait_for(FIRST_PONG, |statuses| connected_count(statuses) >= 2)
+    .await;
+
+  assert_eq!(disconnected_count(&client.statuses()), 0);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn close_frame_reports_disconnect_and_reconnects() {
+  let rov = MockRov::start(
+    r#"{ "events": [
+      { "at": 3000, "connection": 1, "fault": "close", "code": 1011, "reason": "overheated" }
+    ] }"#,
+  );
+  let client = Client::connect(rov.port);
+
+  let statuses = client
+    .wait_for(Duration::from_secs(15), |statuses| {
+      let Some(first_disconnect) = statuses.iter().position(|status| !status.is_connected) else {
+        return false;
+      };
+      connected_count(&statuses[first_disconnect..]) >= 1
+    })
+    .await;
+
+  assert!(statuses[0].is_connected);
+  assert!(statuses
+    .iter()
+    .all(|status| status.is_connected == status.delay.is_some()));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn abrupt_disconnect_reports_disconnect_and_reconnects() {
+  let rov =
+    MockRov::start(r#"{ "events": [{ "at": 3000, "connection": 1, "fault": "disconnect" }] }"#);
+  let client = Client::connect(rov.port);
+
+  let statuses = client
+    .wait_for(Duration::from_secs(15), |statuses| {
+      let Some(first_disconnect) = statuses.iter().position(|status| !status.is_connected) else {
+        return false;
+      };
+      connected_count(&statuses[first_disconnect..]) >= 1
+    })
+    .await;
+
+  assert!(statuses[0].is_connected);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn unanswered_pings_degrade_then_drop_the_link() {
+  let rov = MockRov::start(
+    r#"{ "events": [{ "at": 2500, "connection": 1, "fault": "unansweredPings" }] }"#,
+  );
+  let client = Client::connect(rov.port);
+
+  let states = client
+    .wait_for_events(
+      Duration::from_secs(20),
+      "rov_connection_state_changed",
+      |states| {
+        phases(states).ends_with(&[
+          "degraded",
+          "reconnecting",
+          "connecting",
+          "handshaking",
+          "connected",
+        ])
+      },
+    )
+    .await;
+
+  assert!(phases(&states).starts_with(&["connecting", "handshaking", "connected", "degraded"]));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn half_open_socket_degrades_then_drops_the_link() {
+  let rov =
+    MockRov::start(r#"{ "events": [{ "at": 2500, "connection": 1, "fault": "halfOpen" }] }"#);
+  let client = Client::connect(rov.port);
+
+  let states = client
+    .wait_for_events(
+      Duration::from_secs(20),
+      "rov_connection_state_changed",
+      |states| phases(states).contains(&"reconnecting"),
+    )
+    .await;
+
+  let reconnecting = states
+    .iter()
+    .find(|state| state["phase"] == "reconnecting")
+    .unwrap();
+  assert!(phases(&states).contains(&"degraded"));
+  assert!(reconnecting["reason"]
+    .as_str()
+    .unwrap()
+    .starts_with("No pong from ROV"));
+}
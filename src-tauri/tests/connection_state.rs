@@ -0,0 +1,110 @@
+mod common;
+
+use common::{phases, Client, MockRov};
+use manafish_lib::models::connection::ConnectionPhase;
+use manafish_lib::websocket::connection::{Backoff, ConnectionCommand};
+use std::net::TcpListener;
+use std::time::Duration;
+use tokio::time::sleep;
+
+const EVENT: &str = "rov_connection_state_changed";
+
+#[tokio::test(flavor = "multi_thread")]
+async fn connect_goes_through_handshake() {
+  let rov = MockRov::start(r#"{ "events": [] }"#);
+  let client = Client::connect(rov.port);
+
+  let states = client
+    .wait_for_events(Duration::from_secs(3), EVENT, |states| {
+      phases(states).contains(&"connected")
+    })
+    .await;
+
+  assert_eq!(phases(&states), ["connecting", "handshaking", "connected"]);
+  assert!(states.iter().all(|state| state["attempt"] == 1));
+  assert_eq!(client.control.state.get().phase, ConnectionPhase::Connected);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn operator_can_go_offline_and_back() {
+  let rov = MockRov::start(r#"{ "events": [] }"#);
+  let client = Client::connect(rov.port);
+  client
+    .wait_for_events(Duration::from_secs(3), EVENT, |states| {
+      phases(states).contains(&"connected")
+    })
+    .await;
+
+  client
+    .control
+    .tx
+    .send(ConnectionCommand::Disconnect)
+    .await
+    .unwrap();
+  client
+    .wait_for_events(Duration::from_secs(3), EVENT, |states| {
+      phases(states).ends_with(&["disconnected"])
+    })
+    .await;
+  sleep(Duration::from_secs(2)).await;
+
+  assert_eq!(phases(&client.events(EVENT)).last(), Some(&"disconnected"));
+  assert!(client.handshake.get().is_none());
+
+  client
+    .control
+    .tx
+    .send(ConnectionCommand::Connect)
+    .await
+    .unwrap();
+  client
+    .wait_for_events(Duration::from_secs(3), EVENT, |states| {
+      phases(states).ends_with(&["connecting", "handshaking", "connected"])
+    })
+    .await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn retries_back_off_exponentially() {
+  let port = TcpListener::bind("127.0.0.1:0")
+    .unwrap()
+    .local_addr()
+    .unwrap()
+    .port();
+  let client = Client::connect(port);
+
+  let states = client
+    .wait_for_events(Duration::from_secs(15), EVENT, |states| {
+      phases(states)
+        .iter()
+        .filter(|phase| **phase == "reconnecting")
+        .count()
+        >= 4
+    })
+    .await;
+
+  let retries: Vec<_> = states
+    .iter()
+    .filter(|state| state["phase"] == "reconnecting")
+    .collect();
+  for (index, retry) in retries.iter().enumerate() {
+    assert_eq!(retry["attempt"], index + 1);
+  }
+  let first = retries[0]["retryInMs"].as_u64().unwrap();
+  let fourth = retries[3]["retryInMs"].as_u64().unwrap();
+  assert!(first <= 500, "first retry after {} ms", first);
+  assert!(fourth >= 2000, "fourth retry after {} ms", fourth);
+}
+
+#[test]
+fn backoff_is_capped_and_resets() {
+  let mut backoff = Backoff::new();
+
+  let delays: Vec<_> = (0..20).map(|_| backoff.next_delay()).collect();
+  assert!(delays.iter().all(|delay| *delay <= Duration::from_secs(30)));
+  assert!(delays[19] >= Duration::from_secs(15));
+  assert_eq!(backoff.attempt(), 20);
+
+  backoff.reset();
+  assert!(backoff.next_delay() <= Duration::from_millis(500));
+}
@@ -0,0 +1,94 @@
+mod common;
+
+use common::{Client, MockRov};
+use manafish_lib::models::actions::DirectionVector;
+use manafish_lib::websocket::send::actions::handle_send_direction_vector;
+use std::time::Duration;
+use tokio::time::sleep;
+
+const SURGE: DirectionVector = [1.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0];
+
+fn is_thrusting(telemetry: &serde_json::Value) -> bool {
+  telemetry["thrusterRpms"]
+    .as_array()
+    .unwrap()
+    .iter()
+    .any(|rpm| rpm.as_f64() != Some(0.0))
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn stalled_input_neutralises_thrust() {
+  let rov = MockRov::start(r#"{ "events": [] }"#);
+  let client = Client::connect_with(rov.port, |config| {
+    config.control_watchdog_timeout_ms = 500;
+  });
+  client.wait_connected().await;
+
+  handle_send_direction_vector(&client.direction_vectors, SURGE)
+    .await
+    .unwrap();
+  client
+    .wait_for_events(Duration::from_secs(3), "rov_telemetry", |telemetry| {
+      telemetry.last().is_some_and(is_thrusting)
+    })
+    .await;
+
+  client
+    .wait_for_events(Duration::from_secs(3), "rov_telemetry", |telemetry| {
+      telemetry.last().is_some_and(|last| !is_thrusting(last))
+    })
+    .await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn steady_input_keeps_thrust() {
+  let rov = MockRov::start(r#"{ "events": [] }"#);
+  let client = Client::connect_with(rov.port, |config| {
+    config.control_watchdog_timeout_ms = 500;
+  });
+  client.wait_connected().await;
+
+  for _ in 0..10 {
+    handle_send_direction_vector(&client.direction_vectors, SURGE)
+      .await
+      .unwrap();
+    sleep(Duration::from_millis(100)).await;
+  }
+  let before = client.events("rov_telemetry").len();
+  for _ in 0..20 {
+    handle_send_direction_vector(&client.direction_vectors, SURGE)
+      .await
+      .unwrap();
+    sleep(Duration::from_millis(100)).await;
+  }
+
+  let telemetry = client.events("rov_telemetry");
+  assert!(telemetry[before..].iter().all(is_thrusting));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn reconnect_starts_from_neutral() {
+  let rov =
+    MockRov::start(r#"{ "events": [{ "at": 3000, "connection": 1, "fault": "disconnect" }] }"#);
+  // Long enough that only the reconnect can stop the thrusters.
+  let client = Client::connect_with(rov.port, |config| {
+    config.control_watchdog_timeout_ms = 60_000;
+  });
+  client.wait_connected().await;
+
+  handle_send_direction_vector(&client.direction_vectors, SURGE)
+    .await
+    .unwrap();
+  client
+    .wait_for_events(Duration::from_secs(3), "rov_telemetry", |telemetry| {
+      telemetry.last().is_some_and(is_thrusting)
+    })
+    .await;
+
+  client
+    .wait_for_events(Duration::from_secs(15), "rov_telemetry", |telemetry| {
+      telemetry.last().is_some_and(|last| !is_thrusting(last))
+    })
+    .await;
+  assert_eq!(client.events("rov_handshake_completed").len(), 2);
+}
@@ -0,0 +1,108 @@
+mod common;
+
+use common::{Client, MockRov};
+use manafish_lib::models::actions::DirectionVector;
+use manafish_lib::websocket::connection::ConnectionCommand;
+use manafish_lib::websocket::send::actions::handle_send_direction_vector;
+use std::time::Duration;
+use tokio::time::sleep;
+
+const SURGE: DirectionVector = [1.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0];
+const REVERSE: DirectionVector = [-1.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0];
+
+fn latest_rpms(client: &Client) -> Vec<f64> {
+  client.events("rov_telemetry").last().unwrap()["thrusterRpms"]
+    .as_array()
+    .unwrap()
+    .iter()
+    .map(|rpm| rpm.as_f64().unwrap())
+    .collect()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn vectors_are_sent_at_the_configured_rate() {
+  let rov = MockRov::start(r#"{ "events": [] }"#);
+  let client = Client::connect_with(rov.port, |config| {
+    config.direction_vector_rate_hz = 10;
+  });
+  client.wait_connected().await;
+
+  let before = client.direction_vectors.stats.get().sent;
+  sleep(Duration::from_secs(2)).await;
+  let sent = client.direction_vectors.stats.get().sent - before;
+
+  assert!(
+    (15..=25).contains(&sent),
+    "sent {} vectors in 2 seconds",
+    sent
+  );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn bursts_are_coalesced_into_the_latest_vector() {
+  let rov = MockRov::start(r#"{ "events": [] }"#);
+  let client = Client::connect_with(rov.port, |config| {
+    config.direction_vector_rate_hz = 10;
+  });
+  client.wait_connected().await;
+
+  handle_send_direction_vector(&client.direction_vectors, SURGE)
+    .await
+    .unwrap();
+  sleep(Duration::from_millis(400)).await;
+  let surge_rpms = latest_rpms(&client);
+  assert!(surge_rpms.iter().any(|rpm| *rpm != 0.0));
+
+  for _ in 0..50 {
+    handle_send_direction_vector(&client.direction_vectors, SURGE)
+      .await
+      .unwrap();
+  }
+  handle_send_direction_vector(&client.direction_vectors, REVERSE)
+    .await
+    .unwrap();
+  sleep(Duration::from_millis(400)).await;
+
+  let reverse_rpms: Vec<f64> = surge_rpms.iter().map(|rpm| -rpm).collect();
+  assert_eq!(latest_rpms(&client), reverse_rpms);
+  let stats = client.direction_vectors.stats.get();
+  assert_eq!(stats.received, 52);
+  assert!(stats.coalesced >= 49, "{:?}", stats);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn input_while_offline_is_not_replayed() {
+  let rov = MockRov::start(r#"{ "events": [] }"#);
+  let client = Client::connect(rov.port);
+  client.wait_connected().await;
+
+  client
+    .control
+    .tx
+    .send(ConnectionCommand::Disconnect)
+    .await
+    .unwrap();
+  sleep(Duration::from_millis(500)).await;
+  for _ in 0..20 {
+    handle_send_direction_vector(&client.direction_vectors, SURGE)
+      .await
+      .unwrap();
+  }
+  client
+    .control
+    .tx
+    .send(ConnectionCommand::Connect)
+    .await
+    .unwrap();
+  client
+    .wait_for_events(
+      Duration::from_secs(5),
+      "rov_handshake_completed",
+      |events| events.len() >= 2,
+    )
+    .await;
+  sleep(Duration::from_secs(1)).await;
+
+  assert!(latest_rpms(&client).iter().all(|rpm| *rpm == 0.0));
+  assert_eq!(client.direction_vectors.stats.get().coalesced, 20);
+}
@@ -0,0 +1,137 @@
+mod common;
+
+use common::MockRov;
+use manafish_lib::discovery::{DiscoveredRovs, DiscoveryScanner};
+use manafish_lib::models::discovery::DiscoveredRov;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::test::{mock_app, MockRuntime};
+use tauri::{App, Listener};
+use tokio::time::{sleep, Instant};
+
+struct Scanner {
+  _app: App<MockRuntime>,
+  rovs: Arc<DiscoveredRovs>,
+  events: Arc<Mutex<Vec<Vec<DiscoveredRov>>>>,
+}
+
+impl Scanner {
+  // Probes the mock ROVs directly, loopback does not carry broadcasts.
+  fn start(rovs: &[&MockRov]) -> Self {
+    let app = mock_app();
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let recorded = events.clone();
+    app.handle().listen_any("discovered_rovs", move |event| {
+      let rovs = serde_json::from_str(event.payload()).expect("invalid discovered ROVs");
+      recorded.lock().unwrap().push(rovs);
+    });
+
+    let discovered = Arc::new(DiscoveredRovs::default());
+    let scanner = DiscoveryScanner {
+      app: app.handle().clone(),
+      targets: rovs
+        .iter()
+        .map(|rov| {
+          let port = rov.discovery_port.expect("mock ROV without discovery");
+          SocketAddr::from(([127, 0, 0, 1], port))
+        })
+        .collect(),
+      rovs: discovered.clone(),
+    };
+    tokio::spawn(scanner.run());
+
+    Scanner {
+      _app: app,
+      rovs: discovered,
+      events,
+    }
+  }
+
+  async fn wait_for(
+    &self,
+    timeout: Duration,
+    condition: impl Fn(&[DiscoveredRov]) -> bool,
+  ) -> Vec<DiscoveredRov> {
+    let deadline = Instant::now() + timeout;
+    loop {
+      if let Some(rovs) = self.events.lock().unwrap().last() {
+        if condition(rovs) {
+          return rovs.clone();
+        }
+      }
+      if Instant::now() >= deadline {
+        panic!(
+          "timed out waiting for discovered ROVs, got {:?}",
+          self.events.lock().unwrap()
+        );
+      }
+      sleep(Duration::from_millis(50)).await;
+    }
+  }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn rov_answering_probes_is_listed() {
+  let rov = MockRov::start_with_args(
+    r#"{ "events": [] }"#,
+    &[
+      "--discovery-port",
+      "0",
+      "--name",
+      "Test ROV",
+      "--firmware-version",
+      "1.2.3",
+    ],
+  );
+  let scanner = Scanner::start(&[&rov]);
+
+  let rovs = scanner
+    .wait_for(Duration::from_secs(3), |rovs| !rovs.is_empty())
+    .await;
+
+  assert_eq!(
+    rovs,
+    [DiscoveredRov {
+      name: "Test ROV".to_string(),
+      ip_address: "127.0.0.1".to_string(),
+      firmware_version: "1.2.3".to_string(),
+      web_socket_port: rov.port,
+      webrtc_signaling_api_port: 1984,
+    }]
+  );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn discover_lists_every_rov() {
+  let first = MockRov::start_with_args(
+    r#"{ "events": [] }"#,
+    &["--discovery-port", "0", "--name", "Alpha"],
+  );
+  let second = MockRov::start_with_args(
+    r#"{ "events": [] }"#,
+    &["--discovery-port", "0", "--name", "Bravo"],
+  );
+  let scanner = Scanner::start(&[&first, &second]);
+
+  let rovs = scanner.rovs.discover().await;
+
+  let names: Vec<_> = rovs.iter().map(|rov| rov.name.as_str()).collect();
+  assert_eq!(names, ["Alpha", "Bravo"]);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn silent_rov_is_forgotten() {
+  let rov = MockRov::start_with_args(r#"{ "events": [] }"#, &["--discovery-port", "0"]);
+  let scanner = Scanner::start(&[&rov]);
+  scanner
+    .wait_for(Duration::from_secs(3), |rovs| rovs.len() == 1)
+    .await;
+
+  drop(rov);
+
+  scanner
+    .wait_for(Duration::from_secs(10), |rovs| rovs.is_empty())
+    .await;
+  assert!(scanner.rovs.get().is_empty());
+}
@@ -0,0 +1,140 @@
+mod common;
+
+use common::{connected_rov, temp_directory};
+use manafish_lib::dive::recorder::DiveRecorder;
+use manafish_lib::models::dive::{DiveRecord, DiveRecordingFormat, DiveSample};
+use manafish_lib::websocket::manager::PRIMARY_VEHICLE;
+use manafish_lib::websocket::send::actions::handle_send_custom_action;
+use std::fs;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::sleep;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn csv_recording_holds_every_kind_of_frame() {
+  let directory = temp_directory("dive", "csv");
+  let (_rov, client) = connected_rov(|_| {}).await;
+
+  let started = client
+    .state::<Arc<DiveRecorder>>()
+    .start(PRIMARY_VEHICLE, &directory, DiveRecordingFormat::Csv)
+    .unwrap();
+  assert!(started.recording);
+  assert!(started.path.ends_with(".csv"));
+  handle_send_custom_action(&client.messages, "lights".to_string())
+    .await
+    .unwrap();
+  sleep(Duration::from_millis(500)).await;
+  let stopped = client
+    .state::<Arc<DiveRecorder>>()
+    .stop(PRIMARY_VEHICLE)
+    .unwrap();
+
+  let text = fs::read_to_string(&stopped.path).unwrap();
+  assert!(!stopped.recording);
+  assert_eq!(stopped.bytes, text.len() as u64);
+  let mut lines = text.lines();
+  let columns = lines.next().unwrap().split(',').count();
+  assert_eq!(columns, 27);
+  let rows: Vec<_> = lines.collect();
+  for kind in ["telemetry", "status", "log", "control"] {
+    let prefix = format!(",{},", kind);
+    assert!(
+      rows.iter().any(|row| row.contains(&prefix)),
+      "no {} row",
+      kind
+    );
+  }
+  assert!(rows
+    .iter()
+    .any(|row| row.contains(",log,") && row.ends_with("Received custom action: lights")));
+  assert!(rows
+    .iter()
+    .any(|row| row.contains(",control,") && row.contains(r#"""type"":""customAction"""#)));
+  assert!(rows
+    .iter()
+    .filter(|row| row.contains(",telemetry,"))
+    .all(|row| row.split(',').count() == columns));
+
+  let recordings = client
+    .state::<Arc<DiveRecorder>>()
+    .list(&directory)
+    .unwrap();
+  assert_eq!(recordings.len(), 1);
+  assert_eq!(recordings[0].path, stopped.path);
+  assert_eq!(recordings[0].bytes, stopped.bytes);
+  assert!(!recordings[0].recording);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn jsonl_lines_are_complete_while_recording() {
+  let directory = temp_directory("dive", "jsonl");
+  let (_rov, client) = connected_rov(|_| {}).await;
+
+  let started = client
+    .state::<Arc<DiveRecorder>>()
+    .start(PRIMARY_VEHICLE, &directory, DiveRecordingFormat::Jsonl)
+    .unwrap();
+  sleep(Duration::from_millis(500)).await;
+
+  // Read as a crash would leave it, before the recording is stopped.
+  let text = fs::read_to_string(&started.path).unwrap();
+  assert!(text.ends_with('\n'));
+  let records: Vec<DiveRecord> = text
+    .lines()
+    .map(|line| serde_json::from_str(line).expect("incomplete line"))
+    .collect();
+  assert!(records
+    .iter()
+    .any(|record| matches!(record.sample, DiveSample::Telemetry(_))));
+  assert!(records
+    .iter()
+    .any(|record| matches!(record.sample, DiveSample::Control(_))));
+  assert!(records
+    .windows(2)
+    .all(|pair| pair[0].time_ms <= pair[1].time_ms));
+
+  let recordings = client
+    .state::<Arc<DiveRecorder>>()
+    .list(&directory)
+    .unwrap();
+  assert_eq!(recordings.len(), 1);
+  assert_eq!(recordings[0].path, started.path);
+  assert!(recordings[0].recording);
+  client
+    .state::<Arc<DiveRecorder>>()
+    .stop(PRIMARY_VEHICLE)
+    .unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn a_vehicle_records_one_dive_at_a_time() {
+  let directory = temp_directory("dive", "single");
+  let (_rov, client) = connected_rov(|_| {}).await;
+
+  assert!(client
+    .state::<Arc<DiveRecorder>>()
+    .stop(PRIMARY_VEHICLE)
+    .is_err());
+  assert!(client
+    .state::<Arc<DiveRecorder>>()
+    .list(&directory)
+    .unwrap()
+    .is_empty());
+  client
+    .state::<Arc<DiveRecorder>>()
+    .start(PRIMARY_VEHICLE, &directory, DiveRecordingFormat::Csv)
+    .unwrap();
+  assert!(client
+    .state::<Arc<DiveRecorder>>()
+    .start(PRIMARY_VEHICLE, &directory, DiveRecordingFormat::Jsonl)
+    .is_err());
+  client
+    .state::<Arc<DiveRecorder>>()
+    .stop(PRIMARY_VEHICLE)
+    .unwrap();
+  assert!(client
+    .state::<Arc<DiveRecorder>>()
+    .stop(PRIMARY_VEHICLE)
+    .is_err());
+}
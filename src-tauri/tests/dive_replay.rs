@@ -0,0 +1,167 @@
+mod common;
+
+use common::{connected_rov, temp_directory, Client};
+use manafish_lib::dive::recorder::DiveRecorder;
+use manafish_lib::dive::replay::ActiveDiveReplay;
+use manafish_lib::models::dive::{DiveRecording, DiveRecordingFormat};
+use manafish_lib::websocket::manager::PRIMARY_VEHICLE;
+use manafish_lib::websocket::send::actions::handle_send_custom_action;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::test::MockRuntime;
+use tokio::time::sleep;
+
+async fn recorded_dive(client: &Client, name: &str, format: DiveRecordingFormat) -> DiveRecording {
+  let directory = temp_directory("replay", name);
+  client
+    .state::<Arc<DiveRecorder>>()
+    .start(PRIMARY_VEHICLE, &directory, format)
+    .unwrap();
+  handle_send_custom_action(&client.messages, "lights".to_string())
+    .await
+    .unwrap();
+  sleep(Duration::from_millis(800)).await;
+  client
+    .state::<Arc<DiveRecorder>>()
+    .stop(PRIMARY_VEHICLE)
+    .unwrap()
+}
+
+fn replay(client: &Client) -> Arc<ActiveDiveReplay<MockRuntime>> {
+  client.state()
+}
+
+fn replayed(client: &Client, name: &str) -> Vec<serde_json::Value> {
+  client
+    .vehicle_events(name)
+    .into_iter()
+    .filter(|event| event.replay)
+    .map(|event| event.payload)
+    .collect()
+}
+
+fn live(client: &Client, name: &str) -> usize {
+  client
+    .vehicle_events(name)
+    .iter()
+    .filter(|event| !event.replay)
+    .count()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn replayed_events_are_flagged_and_the_vehicle_takes_no_control() {
+  let (_rov, client) = connected_rov(|_| {}).await;
+  let recording = recorded_dive(&client, "flagged", DiveRecordingFormat::Jsonl).await;
+  assert!(replayed(&client, "rov_telemetry").is_empty());
+
+  let started = replay(&client)
+    .start(&client.manager, None, Path::new(&recording.path), 8.0)
+    .unwrap();
+  assert_eq!(started.vehicle_id, "primary");
+  assert!(started.duration_ms > 0);
+  assert!(client.manager.control(None).is_err());
+  assert!(client.manager.get(None).is_ok());
+  // A reading already on its way may still land.
+  sleep(Duration::from_millis(50)).await;
+  let live_telemetry = live(&client, "rov_telemetry");
+  client
+    .wait_for_events(Duration::from_secs(5), "dive_replay_state", |states| {
+      states.iter().any(|state| state["finished"] == true)
+    })
+    .await;
+  // The ROV kept sending, but none of it was mixed into the replay.
+  assert_eq!(live(&client, "rov_telemetry"), live_telemetry);
+
+  assert!(!replayed(&client, "rov_telemetry").is_empty());
+  assert!(replayed(&client, "log_message")
+    .iter()
+    .any(|entry| entry["message"] == "Received custom action: lights"));
+  let phases: Vec<_> = replayed(&client, "rov_connection_state_changed")
+    .iter()
+    .map(|state| (state["phase"].clone(), state["reason"].clone()))
+    .collect();
+  assert_eq!(phases[0].0, "connected");
+  assert!(phases[0].1.as_str().unwrap().starts_with("Replaying "));
+  assert_eq!(phases.last().unwrap().0, "disconnected");
+
+  let live_states = client.events("rov_connection_state_changed").len();
+  let stopped = replay(&client).stop(&client.manager).unwrap();
+  assert!(stopped.finished);
+  assert!(client.manager.control(None).is_ok());
+  assert!(replay(&client).stop(&client.manager).is_err());
+  // The vehicle's own state is passed on again, unflagged.
+  let states = client.vehicle_events("rov_connection_state_changed");
+  let last = states.last().unwrap();
+  assert!(!last.replay);
+  assert_eq!(last.payload["phase"], "connected");
+  assert!(client.events("rov_connection_state_changed").len() > live_states);
+  sleep(Duration::from_millis(300)).await;
+  assert!(live(&client, "rov_telemetry") > live_telemetry);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn csv_replays_pause_seek_and_change_speed() {
+  let (_rov, client) = connected_rov(|_| {}).await;
+  let recording = recorded_dive(&client, "controls", DiveRecordingFormat::Csv).await;
+
+  assert!(replay(&client).pause(true).is_err());
+  assert!(replay(&client)
+    .start(&client.manager, None, Path::new(&recording.path), 10.0)
+    .is_err());
+  let started = replay(&client)
+    .start(&client.manager, None, Path::new(&recording.path), 0.25)
+    .unwrap();
+  let paused = replay(&client).pause(true).unwrap();
+  assert!(paused.paused);
+  let count = replayed(&client, "rov_telemetry").len();
+  sleep(Duration::from_millis(300)).await;
+  assert_eq!(replayed(&client, "rov_telemetry").len(), count);
+  assert_eq!(
+    replay(&client).state().unwrap().position_ms,
+    paused.position_ms
+  );
+
+  let half = started.duration_ms / 2;
+  let seeked = replay(&client).seek(half).unwrap();
+  assert_eq!(seeked.position_ms, half);
+  assert!(replay(&client).set_speed(0.1).is_err());
+  let sped_up = replay(&client).set_speed(8.0).unwrap();
+  assert_eq!(sped_up.speed, 8.0);
+  // The readings at the seeked position are passed on while still paused.
+  sleep(Duration::from_millis(200)).await;
+  assert!(replayed(&client, "rov_telemetry").len() > count);
+
+  replay(&client).pause(false).unwrap();
+  client
+    .wait_for_events(Duration::from_secs(5), "dive_replay_state", |states| {
+      states.iter().any(|state| state["finished"] == true)
+    })
+    .await;
+  let finished = replay(&client).state().unwrap();
+  assert_eq!(finished.position_ms, finished.duration_ms);
+  replay(&client).stop(&client.manager).unwrap();
+  assert!(replay(&client).state().is_none());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn replays_started_together_leave_one_holding_the_vehicle() {
+  let (_rov, client) = connected_rov(|_| {}).await;
+  let recording = recorded_dive(&client, "together", DiveRecordingFormat::Jsonl).await;
+  let (active, manager) = (replay(&client), client.manager.clone());
+  std::thread::scope(|scope| {
+    for _ in 0..4 {
+      scope.spawn(|| {
+        active
+          .start(&manager, None, Path::new(&recording.path), 0.25)
+          .unwrap()
+      });
+    }
+  });
+
+  // The replays that were replaced don't let the vehicle go.
+  assert!(active.state().is_some());
+  assert!(manager.control(None).is_err());
+  active.stop(&manager).unwrap();
+  assert!(manager.control(None).is_ok());
+}
@@ -0,0 +1,129 @@
+mod common;
+
+use common::{connected_rov, temp_directory};
+use manafish_lib::dive::recorder::DiveRecorder;
+use manafish_lib::dive::session::DiveSessions;
+use manafish_lib::models::config::Config;
+use manafish_lib::models::dive::{DiveRecordingFormat, DiveSession};
+use manafish_lib::websocket::manager::PRIMARY_VEHICLE;
+use manafish_lib::websocket::send::actions::handle_toggle_depth_hold;
+use manafish_lib::websocket::send::config::handle_request_rov_config;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::sleep;
+
+// Points the config's videos, and so the dive directory, at a fresh
+// directory.
+fn video_directory(config: &mut Config, name: &str) -> PathBuf {
+  let directory = temp_directory("sessions", name);
+  config.video_directory = directory.join("Manafish").to_string_lossy().into_owned();
+  config.dive_directory()
+}
+
+fn saved(session: &DiveSession, dive_directory: &Path) -> DiveSession {
+  let path = dive_directory.join(format!("{}.json", session.id));
+  serde_json::from_str(&fs::read_to_string(path).unwrap()).unwrap()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn a_session_opens_on_connect_and_sums_up_the_dive() {
+  let mut dive_directory = PathBuf::new();
+  let (_rov, client) = connected_rov(|config| {
+    dive_directory = video_directory(config, "connect");
+    config.dive_session_on_connect = true;
+    config.dive_operator = "Ada".to_string();
+    config.dive_site = "Harbour".to_string();
+  })
+  .await;
+
+  let sessions = client.state::<Arc<DiveSessions>>().list().unwrap();
+  assert_eq!(sessions.len(), 1);
+  let session = &sessions[0];
+  assert_eq!(session.vehicle_id, "primary");
+  assert_eq!(
+    (session.operator.as_str(), session.site.as_str()),
+    ("Ada", "Harbour")
+  );
+  assert!(session.firmware_version.is_some());
+  assert!(session.ended_ms.is_none());
+
+  handle_toggle_depth_hold(&client.messages).await.unwrap();
+  sleep(Duration::from_millis(1000)).await;
+  let stopped = client
+    .state::<Arc<DiveSessions>>()
+    .stop(PRIMARY_VEHICLE)
+    .unwrap();
+
+  assert_eq!(stopped.id, session.id);
+  assert!(stopped.ended_ms.unwrap() >= stopped.started_ms);
+  let summary = stopped.summary.clone().unwrap();
+  assert!(summary.duration_ms >= 1000, "{:?}", summary);
+  let max_depth = summary.max_depth.unwrap();
+  assert!(summary.average_depth.unwrap() <= max_depth);
+  assert!(summary.min_water_temperature.unwrap() <= summary.max_water_temperature.unwrap());
+  assert!(summary.battery_used.is_some());
+  assert!(summary.depth_hold_ms > 0, "{:?}", summary);
+  assert!(summary.depth_hold_ms <= summary.duration_ms);
+  assert_eq!(saved(&stopped, &dive_directory).summary, Some(summary));
+  assert!(client
+    .state::<Arc<DiveSessions>>()
+    .stop(PRIMARY_VEHICLE)
+    .is_err());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn sessions_link_what_was_recorded_during_them() {
+  let mut dive_directory = PathBuf::new();
+  let (_rov, client) = connected_rov(|config| {
+    dive_directory = video_directory(config, "links");
+  })
+  .await;
+  assert!(client
+    .state::<Arc<DiveSessions>>()
+    .list()
+    .unwrap()
+    .is_empty());
+
+  let session = client
+    .state::<Arc<DiveSessions>>()
+    .start(PRIMARY_VEHICLE, Some("Grace".to_string()), None)
+    .unwrap();
+  assert_eq!(session.operator, "Grace");
+  assert!(client
+    .state::<Arc<DiveSessions>>()
+    .start(PRIMARY_VEHICLE, None, None)
+    .is_err());
+  assert_eq!(saved(&session, &dive_directory).id, session.id);
+
+  let recording = client
+    .state::<Arc<DiveRecorder>>()
+    .start(PRIMARY_VEHICLE, &dive_directory, DiveRecordingFormat::Csv)
+    .unwrap();
+  client
+    .state::<Arc<DiveSessions>>()
+    .link_recording("/videos/dive.mp4");
+  let rov_config = handle_request_rov_config(&client.messages).await.unwrap();
+  // The reply resolves the request before the handlers see it.
+  sleep(Duration::from_millis(200)).await;
+  client
+    .state::<Arc<DiveRecorder>>()
+    .stop(PRIMARY_VEHICLE)
+    .unwrap();
+  let stopped = client
+    .state::<Arc<DiveSessions>>()
+    .stop(PRIMARY_VEHICLE)
+    .unwrap();
+
+  assert_eq!(stopped.data_files, [recording.path]);
+  assert_eq!(stopped.recordings, ["/videos/dive.mp4"]);
+  assert_eq!(
+    serde_json::to_value(stopped.rov_config.as_ref().unwrap()).unwrap(),
+    serde_json::to_value(&rov_config).unwrap()
+  );
+  let sessions = client.state::<Arc<DiveSessions>>().list().unwrap();
+  assert_eq!(sessions.len(), 1);
+  assert_eq!(sessions[0].data_files, stopped.data_files);
+  assert!(sessions[0].summary.is_some());
+}
@@ -0,0 +1,153 @@
+mod common;
+
+use common::{disconnected_count, Client, MockRov};
+use manafish_lib::websocket::message::{WebsocketEnvelope, WebsocketMessage};
+use serde_json::{json, Value};
+use std::time::Duration;
+
+const OLDER_FIRMWARE: &str = include_str!("fixtures/messages/older-firmware.jsonl");
+const NEWER_FIRMWARE: &str = include_str!("fixtures/messages/newer-firmware.jsonl");
+
+fn frames(fixture: &str) -> Vec<(Value, WebsocketEnvelope)> {
+  fixture
+    .lines()
+    .map(|line| {
+      let envelope = serde_json::from_str(line)
+        .unwrap_or_else(|e| panic!("failed to deserialize {}: {}", line, e));
+      (serde_json::from_str(line).unwrap(), envelope)
+    })
+    .collect()
+}
+
+#[test]
+fn newer_firmware_messages_round_trip_unchanged() {
+  for (raw, envelope) in frames(NEWER_FIRMWARE) {
+    assert_eq!(serde_json::to_value(&envelope).unwrap(), raw);
+  }
+}
+
+#[test]
+fn fields_newer_firmware_added_are_kept() {
+  for (_, envelope) in frames(NEWER_FIRMWARE) {
+    match envelope.message {
+      WebsocketMessage::Hello(hello) => {
+        assert_eq!(hello.unknown_fields["serialNumber"], "MF-0042");
+      }
+      WebsocketMessage::Config(config) => {
+        assert_eq!(config.unknown_fields["lights"]["mode"], "strobe");
+        assert_eq!(config.power.unknown_fields["batteryCapacityWh"], 96.5);
+        assert_eq!(config.regulator.unknown_fields["yaw"]["kp"], 2.0);
+        assert_eq!(config.regulator.depth.unknown_fields["integralLimit"], 4.0);
+
+        // A config sent back to the ROV keeps what the app doesn't know.
+        let sent = serde_json::to_value(WebsocketMessage::SetConfig(config)).unwrap();
+        assert_eq!(sent["payload"]["lights"]["brightness"], 0.75);
+      }
+      WebsocketMessage::Telemetry(telemetry) => {
+        assert_eq!(telemetry.work_indicator_percentage, 40);
+        assert_eq!(telemetry.unknown_fields["heading"], 271.5);
+      }
+      WebsocketMessage::StatusUpdate(status) => {
+        assert_eq!(status.unknown_fields["headingHold"], true);
+        assert_eq!(status.health.unknown_fields["leakSensorOk"], false);
+      }
+      WebsocketMessage::LogMessage(entry) => {
+        assert_eq!(entry.unknown_fields["subsystem"], "hull");
+      }
+      WebsocketMessage::ShowToast(toast) => {
+        assert_eq!(toast.unknown_fields["durationMs"], 8000);
+      }
+      _ => {}
+    }
+  }
+}
+
+#[test]
+fn fields_older_firmware_omits_get_defaults() {
+  for (raw, envelope) in frames(OLDER_FIRMWARE) {
+    match envelope.message {
+      WebsocketMessage::Hello(hello) => {
+        assert_eq!(hello.software_version, "0.9.0");
+        assert!(hello.capabilities.is_empty());
+      }
+      WebsocketMessage::Telemetry(telemetry) => {
+        assert_eq!(telemetry.depth, 3.5);
+        assert_eq!(telemetry.water_temperature, None);
+        assert_eq!(telemetry.electronics_temperature, None);
+        assert_eq!(telemetry.work_indicator_percentage, 0);
+      }
+      message => {
+        assert!(!matches!(message, WebsocketMessage::Unknown(_)), "{}", raw);
+        let envelope = WebsocketEnvelope {
+          id: envelope.id,
+          message,
+        };
+        assert_eq!(serde_json::to_value(&envelope).unwrap(), raw);
+      }
+    }
+  }
+}
+
+#[test]
+fn unknown_message_types_keep_their_raw_json() {
+  let unknown: Vec<_> = frames(NEWER_FIRMWARE)
+    .into_iter()
+    .filter_map(|(raw, envelope)| match &envelope.message {
+      WebsocketMessage::Unknown(message) => Some((raw, envelope.id, message.clone())),
+      _ => None,
+    })
+    .collect();
+
+  let types: Vec<_> = unknown
+    .iter()
+    .map(|(_, _, message)| message.message_type.as_str())
+    .collect();
+  assert_eq!(types, ["sonarPing", "cameraTilt", "batteryReport"]);
+  for (raw, id, message) in unknown {
+    assert_eq!(message.raw, raw);
+    assert_eq!(id, raw["id"].as_u64());
+  }
+}
+
+#[test]
+fn malformed_known_messages_still_fail() {
+  for text in [
+    r#"{"type":"telemetry","payload":{"pitch":"level"}}"#,
+    // A core reading is never taken to be zero.
+    r#"{"type":"telemetry","payload":{"pitch":1.5,"roll":0.0,"desiredPitch":0.0,"desiredRoll":0.0,"thrusterRpms":[0.0,0.0,0.0,0.0,0.0,0.0,0.0,0.0]}}"#,
+    r#"{"type":"startThrusterTest","payload":300}"#,
+    r#"{"type":"config"}"#,
+    r#"{"type":7}"#,
+    r#"{"payload":{}}"#,
+  ] {
+    assert!(
+      serde_json::from_str::<WebsocketEnvelope>(text).is_err(),
+      "{} deserialized",
+      text
+    );
+  }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn unknown_messages_reach_the_frontend() {
+  let rov = MockRov::start(
+    r#"{ "events": [
+      { "at": 500, "fault": "unknownMessage" }
+    ] }"#,
+  );
+  let client = Client::connect(rov.port);
+
+  let events = client
+    .wait_for_events(Duration::from_secs(5), "rov_unknown_message", |events| {
+      !events.is_empty()
+    })
+    .await;
+  assert_eq!(
+    events[0],
+    json!({
+      "messageType": "mockUnknownMessage",
+      "raw": { "type": "mockUnknownMessage", "payload": { "value": 42 } },
+    })
+  );
+  assert_eq!(disconnected_count(&client.statuses()), 0);
+}
@@ -0,0 +1,88 @@
+mod common;
+
+use common::{connected_count, disconnected_count, Client, MockRov};
+use manafish_lib::models::handshake::Capability;
+use serde_json::json;
+use std::time::Duration;
+use tokio::time::sleep;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn handshake_negotiates_capabilities() {
+  let rov = MockRov::start_with_args(
+    r#"{ "events": [] }"#,
+    &[
+      "--firmware-version",
+      "1.2.3",
+      "--capabilities",
+      "depthHold,dshot,somethingFromTheFuture",
+    ],
+  );
+  let client = Client::connect(rov.port);
+
+  let handshake = client.wait_connected().await;
+
+  assert_eq!(
+    handshake,
+    json!({
+      "protocolVersion": 1,
+      "firmwareVersion": "1.2.3",
+      "capabilities": ["depthHold", "dshot"],
+    })
+  );
+  assert!(client.handshake.require(Capability::DepthHold).is_ok());
+  assert!(client.handshake.require(Capability::ThrusterTest).is_err());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn incompatible_rov_is_refused() {
+  let rov = MockRov::start_with_args(
+    r#"{ "events": [] }"#,
+    &["--protocol-version", "7", "--min-protocol-version", "7"],
+  );
+  let client = Client::connect(rov.port);
+
+  let statuses = client
+    .wait_for(Duration::from_secs(8), |statuses| {
+      disconnected_count(statuses) >= 2
+    })
+    .await;
+
+  assert_eq!(connected_count(&statuses), 0);
+  assert!(client.events("rov_handshake_completed").is_empty());
+  assert!(client.handshake.get().is_none());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn legacy_rov_connects_with_all_capabilities() {
+  let rov = MockRov::start_with_args(r#"{ "events": [] }"#, &["--legacy"]);
+  let client = Client::connect(rov.port);
+
+  let handshake = client.wait_connected().await;
+  client
+    .wait_for(Duration::from_secs(5), |statuses| {
+      connected_count(statuses) >= 1
+    })
+    .await;
+
+  assert_eq!(handshake["protocolVersion"], json!(null));
+  for capability in Capability::ALL {
+    assert!(client.handshake.require(capability).is_ok());
+  }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn handshake_is_cleared_on_disconnect() {
+  let rov = MockRov::start(r#"{ "events": [] }"#);
+  let client = Client::connect(rov.port);
+
+  client.wait_connected().await;
+  drop(rov);
+  client
+    .wait_for(Duration::from_secs(8), |statuses| {
+      disconnected_count(statuses) >= 1
+    })
+    .await;
+  sleep(Duration::from_millis(100)).await;
+
+  assert!(client.handshake.get().is_none());
+}
@@ -0,0 +1,108 @@
+mod common;
+
+use common::{Client, MockRov};
+use std::time::Duration;
+
+const EVENT: &str = "link_quality";
+
+fn rtt_samples(quality: &serde_json::Value) -> u64 {
+  quality["rttHistogram"]
+    .as_array()
+    .map(|buckets| {
+      buckets
+        .iter()
+        .filter_map(|bucket| bucket["count"].as_u64())
+        .sum()
+    })
+    .unwrap_or(0)
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn healthy_link_reports_rtt_and_throughput() {
+  let rov = MockRov::start(r#"{ "events": [] }"#);
+  let client = Client::connect(rov.port);
+
+  let reports = client
+    .wait_for_events(Duration::from_secs(10), EVENT, |reports| {
+      reports
+        .last()
+        .is_some_and(|quality| rtt_samples(quality) >= 2)
+    })
+    .await;
+  let quality = reports.last().unwrap();
+
+  assert!(quality["rttMaxMs"].as_u64().unwrap() < 200, "{}", quality);
+  assert!(quality["jitterMs"].as_f64().is_some(), "{}", quality);
+  assert_eq!(quality["missedPongRatio"], 0.0);
+  assert_eq!(quality["reconnectCount"], 0);
+  // The mock sends telemetry at 20 Hz by default.
+  let telemetry_rate = quality["telemetryRate"].as_f64().unwrap();
+  assert!((10.0..30.0).contains(&telemetry_rate), "{}", quality);
+  assert!(quality["bytesInPerSecond"].as_f64().unwrap() > 0.0);
+  assert!(quality["bytesOutPerSecond"].as_f64().unwrap() > 0.0);
+  assert_eq!(client.link.get().reconnect_count, 0);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn latency_lands_in_the_matching_bucket() {
+  let rov = MockRov::start(r#"{ "events": [{ "at": 0, "fault": "latency", "millis": 300 }] }"#);
+  let client = Client::connect(rov.port);
+
+  let reports = client
+    .wait_for_events(Duration::from_secs(10), EVENT, |reports| {
+      reports
+        .last()
+        .is_some_and(|quality| rtt_samples(quality) >= 1)
+    })
+    .await;
+  let buckets = reports.last().unwrap()["rttHistogram"]
+    .as_array()
+    .unwrap()
+    .clone();
+
+  let bucket = buckets
+    .iter()
+    .find(|bucket| bucket["count"].as_u64() > Some(0))
+    .unwrap();
+  assert_eq!(bucket["upToMs"], 500);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn unanswered_pings_raise_missed_pong_ratio() {
+  let rov = MockRov::start(
+    r#"{ "events": [{ "at": 2500, "connection": 1, "fault": "unansweredPings" }] }"#,
+  );
+  let client = Client::connect(rov.port);
+
+  let reports = client
+    .wait_for_events(Duration::from_secs(10), EVENT, |reports| {
+      reports
+        .last()
+        .is_some_and(|quality| quality["missedPongRatio"].as_f64() > Some(0.0))
+    })
+    .await;
+
+  // Telemetry keeps flowing while the ROV ignores pings.
+  assert!(reports.last().unwrap()["telemetryRate"].as_f64().unwrap() > 0.0);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn reconnects_are_counted() {
+  let rov =
+    MockRov::start(r#"{ "events": [{ "at": 3000, "connection": 1, "fault": "disconnect" }] }"#);
+  let client = Client::connect(rov.port);
+
+  let reports = client
+    .wait_for_events(Duration::from_secs(15), EVENT, |reports| {
+      reports
+        .last()
+        .is_some_and(|quality| quality["reconnectCount"] == 1 && rtt_samples(quality) >= 1)
+    })
+    .await;
+
+  // The lost link is reported once without any statistics.
+  assert!(reports.iter().any(|quality| {
+    quality["reconnectCount"] == 0 && quality["rttHistogram"].as_array().unwrap().is_empty()
+  }));
+  assert_eq!(client.link.get().reconnect_count, 1);
+}
@@ -0,0 +1,218 @@
+mod common;
+
+use common::{Client, MockRov};
+use manafish_lib::websocket::handler::{HandlerResult, MessageHandler, FRONTEND_ORDER};
+use manafish_lib::websocket::message::{WebsocketEnvelope, WebsocketMessage};
+use manafish_lib::websocket::request::{send_request, REQUEST_TIMEOUT};
+use manafish_lib::websocket::vehicle::VehicleEmitter;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::test::MockRuntime;
+use tokio::time::sleep;
+
+type Seen = Arc<Mutex<Vec<String>>>;
+
+enum Behaviour {
+  Record,
+  Fail,
+  Panic,
+}
+
+// Records the telemetry and outbound messages it sees under its name.
+struct Recorder {
+  name: &'static str,
+  seen: Seen,
+  behaviour: Behaviour,
+}
+
+impl Recorder {
+  fn new(name: &'static str, seen: &Seen, behaviour: Behaviour) -> Self {
+    Recorder {
+      name,
+      seen: seen.clone(),
+      behaviour,
+    }
+  }
+}
+
+impl MessageHandler<MockRuntime> for Recorder {
+  fn subscribes_to(&self, message: &WebsocketMessage) -> bool {
+    matches!(message, WebsocketMessage::Telemetry(_))
+  }
+
+  fn handle(
+    &self,
+    _vehicle: &VehicleEmitter<MockRuntime>,
+    _envelope: &WebsocketEnvelope,
+  ) -> HandlerResult {
+    self.seen.lock().unwrap().push(self.name.to_string());
+    match self.behaviour {
+      Behaviour::Record => Ok(Vec::new()),
+      Behaviour::Fail => Err("refusing telemetry".to_string()),
+      Behaviour::Panic => panic!("handler {} panicked on purpose", self.name),
+    }
+  }
+
+  fn observe_outbound(
+    &self,
+    _vehicle: &VehicleEmitter<MockRuntime>,
+    envelope: &WebsocketEnvelope,
+  ) -> Result<(), String> {
+    let text = serde_json::to_string(envelope).map_err(|e| e.to_string())?;
+    self
+      .seen
+      .lock()
+      .unwrap()
+      .push(format!("{} sent {}", self.name, text));
+    Ok(())
+  }
+}
+
+fn recorded(seen: &Seen, name: &str) -> usize {
+  seen
+    .lock()
+    .unwrap()
+    .iter()
+    .filter(|entry| *entry == name)
+    .count()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn handlers_run_by_order_then_registration() {
+  let rov = MockRov::start(r#"{ "events": [] }"#);
+  let client = Client::connect(rov.port);
+  let handlers = client.manager.handlers();
+  let seen = Seen::default();
+
+  handlers.register(
+    "late",
+    FRONTEND_ORDER + 1,
+    Recorder::new("late", &seen, Behaviour::Record),
+  );
+  handlers.register(
+    "first",
+    FRONTEND_ORDER - 1,
+    Recorder::new("first", &seen, Behaviour::Record),
+  );
+  handlers.register(
+    "second",
+    FRONTEND_ORDER - 1,
+    Recorder::new("second", &seen, Behaviour::Record),
+  );
+  let names = handlers.names();
+  assert_eq!(names.first().map(String::as_str), Some("first"));
+  assert_eq!(names.get(1).map(String::as_str), Some("second"));
+  assert_eq!(names.last().map(String::as_str), Some("late"));
+
+  // Telemetry arriving while the handlers were registered saw only some.
+  sleep(Duration::from_millis(100)).await;
+  seen.lock().unwrap().clear();
+  let before = client.events("rov_telemetry").len();
+  client
+    .wait_for_events(Duration::from_secs(3), "rov_telemetry", |telemetry| {
+      telemetry.len() >= before + 5
+    })
+    .await;
+  let telemetry: Vec<_> = seen
+    .lock()
+    .unwrap()
+    .iter()
+    .filter(|entry| !entry.contains(" sent "))
+    .cloned()
+    .collect();
+  let start = telemetry
+    .iter()
+    .position(|name| name == "first")
+    .unwrap_or(0);
+  let complete = (telemetry.len() - start) / 3 * 3;
+  assert!(complete >= 3, "{:?}", telemetry);
+  for round in telemetry[start..start + complete].chunks(3) {
+    assert_eq!(round, ["first", "second", "late"]);
+  }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn failing_handlers_do_not_stop_the_others() {
+  let rov = MockRov::start(r#"{ "events": [] }"#);
+  let client = Client::connect(rov.port);
+  let handlers = client.manager.handlers();
+  let seen = Seen::default();
+
+  handlers.register(
+    "panics",
+    FRONTEND_ORDER - 2,
+    Recorder::new("panics", &seen, Behaviour::Panic),
+  );
+  handlers.register(
+    "fails",
+    FRONTEND_ORDER - 1,
+    Recorder::new("fails", &seen, Behaviour::Fail),
+  );
+  handlers.register(
+    "records",
+    FRONTEND_ORDER + 1,
+    Recorder::new("records", &seen, Behaviour::Record),
+  );
+  let before = client.events("rov_telemetry").len();
+
+  client
+    .wait_for_events(Duration::from_secs(3), "rov_telemetry", |telemetry| {
+      telemetry.len() >= before + 5
+    })
+    .await;
+  sleep(Duration::from_millis(100)).await;
+  assert!(recorded(&seen, "panics") >= 5);
+  assert!(recorded(&seen, "fails") >= 5);
+  assert!(recorded(&seen, "records") >= 5);
+
+  let reply = send_request(
+    &client.messages,
+    WebsocketMessage::GetConfig,
+    REQUEST_TIMEOUT,
+  )
+  .await;
+  assert!(
+    matches!(reply, Ok(WebsocketMessage::Config(_))),
+    "{:?}",
+    reply
+  );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn handlers_observe_outbound_messages_until_unregistered() {
+  let rov = MockRov::start(r#"{ "events": [] }"#);
+  let client = Client::connect(rov.port);
+  let handlers = client.manager.handlers();
+  let seen = Seen::default();
+  let id = handlers.register(
+    "observer",
+    FRONTEND_ORDER,
+    Recorder::new("observer", &seen, Behaviour::Record),
+  );
+
+  send_request(
+    &client.messages,
+    WebsocketMessage::GetConfig,
+    REQUEST_TIMEOUT,
+  )
+  .await
+  .expect("no config from ROV");
+  sleep(Duration::from_millis(300)).await;
+  {
+    let seen = seen.lock().unwrap();
+    assert!(seen
+      .iter()
+      .any(|entry| entry.starts_with(r#"observer sent {"id":"#)
+        && entry.contains(r#""type":"getConfig""#)));
+    assert!(seen
+      .iter()
+      .any(|entry| entry.contains(r#""type":"directionVector""#)));
+  }
+
+  assert!(handlers.unregister(id));
+  assert!(!handlers.unregister(id));
+  sleep(Duration::from_millis(100)).await;
+  let count = seen.lock().unwrap().len();
+  sleep(Duration::from_millis(300)).await;
+  assert_eq!(seen.lock().unwrap().len(), count);
+}
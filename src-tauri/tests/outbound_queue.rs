@@ -0,0 +1,165 @@
+mod common;
+
+use common::{phases, Client, MockRov};
+use manafish_lib::models::outbound::{DeliveryPolicy, OutboundPriority};
+use manafish_lib::websocket::connection::ConnectionCommand;
+use manafish_lib::websocket::message::WebsocketMessage;
+use manafish_lib::websocket::request::{send_message, send_request, REQUEST_TIMEOUT};
+use std::time::{Duration, Instant};
+use tokio::time::sleep;
+
+async fn go_offline(client: &Client) {
+  client.wait_connected().await;
+  client
+    .control
+    .tx
+    .send(ConnectionCommand::Disconnect)
+    .await
+    .unwrap();
+  client
+    .wait_for_events(
+      Duration::from_secs(3),
+      "rov_connection_state_changed",
+      |states| phases(states).ends_with(&["disconnected"]),
+    )
+    .await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn control_commands_are_refused_while_offline() {
+  let rov = MockRov::start(r#"{ "events": [] }"#);
+  let client = Client::connect(rov.port);
+  go_offline(&client).await;
+
+  let error = send_message(&client.messages, WebsocketMessage::ToggleDepthHold)
+    .expect_err("toggle was queued while offline");
+
+  assert!(
+    error.contains("Not connected"),
+    "unexpected error {}",
+    error
+  );
+  assert!(client.messages.queue.list().is_empty());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn actuating_commands_are_refused_while_offline() {
+  let rov = MockRov::start(r#"{ "events": [] }"#);
+  let client = Client::connect(rov.port);
+  go_offline(&client).await;
+
+  let error = send_request(
+    &client.messages,
+    WebsocketMessage::StartRegulatorAutoTuning,
+    REQUEST_TIMEOUT,
+  )
+  .await
+  .expect_err("auto tuning was queued while offline");
+
+  assert!(
+    error.contains("Not connected"),
+    "unexpected error {}",
+    error
+  );
+  assert!(client.messages.queue.list().is_empty());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn config_is_held_until_reconnect() {
+  let rov = MockRov::start(r#"{ "events": [] }"#);
+  let client = Client::connect(rov.port);
+  let Ok(WebsocketMessage::Config(mut config)) = send_request(
+    &client.messages,
+    WebsocketMessage::GetConfig,
+    REQUEST_TIMEOUT,
+  )
+  .await
+  else {
+    panic!("no config from ROV");
+  };
+  go_offline(&client).await;
+
+  config.power.user_max_power = 0.5;
+  let request = send_request(
+    &client.messages,
+    WebsocketMessage::SetConfig(config),
+    REQUEST_TIMEOUT,
+  );
+  let reconnect = async {
+    // Longer than the reply timeout, held requests only start it once sent.
+    sleep(Duration::from_secs(6)).await;
+    let queued = client.messages.queue.list();
+    assert_eq!(queued.len(), 1);
+    assert_eq!(queued[0].message_type, "setConfig");
+    assert_eq!(queued[0].policy, DeliveryPolicy::HoldUntilReconnect);
+    client
+      .control
+      .tx
+      .send(ConnectionCommand::Connect)
+      .await
+      .unwrap();
+  };
+  let (reply, ()) = tokio::join!(request, reconnect);
+
+  match reply {
+    Ok(WebsocketMessage::Config(applied)) => assert_eq!(applied.power.user_max_power, 0.5),
+    other => panic!("unexpected reply {:?}", other),
+  }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn queued_requests_expire() {
+  let rov = MockRov::start(r#"{ "events": [] }"#);
+  let client = Client::connect(rov.port);
+  go_offline(&client).await;
+
+  let started = Instant::now();
+  let reply = send_request(
+    &client.messages,
+    WebsocketMessage::GetConfig,
+    REQUEST_TIMEOUT,
+  )
+  .await;
+
+  let error = reply.expect_err("request resolved while offline");
+  assert!(error.contains("Expired"), "unexpected error {}", error);
+  assert!(started.elapsed() >= Duration::from_secs(4));
+  assert!(client.messages.queue.list().is_empty());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn queue_is_ordered_by_priority_and_cancellable() {
+  let rov = MockRov::start(r#"{ "events": [] }"#);
+  let client = Client::connect(rov.port);
+  go_offline(&client).await;
+
+  let get_config = send_request(
+    &client.messages,
+    WebsocketMessage::GetConfig,
+    REQUEST_TIMEOUT,
+  );
+  let cancel_tuning = send_request(
+    &client.messages,
+    WebsocketMessage::CancelRegulatorAutoTuning,
+    REQUEST_TIMEOUT,
+  );
+  let operator = async {
+    sleep(Duration::from_millis(200)).await;
+    let queued = client.messages.queue.list();
+    let priorities: Vec<_> = queued.iter().map(|message| message.priority).collect();
+    assert_eq!(
+      priorities,
+      [OutboundPriority::Emergency, OutboundPriority::Configuration]
+    );
+    for message in &queued {
+      client.messages.queue.cancel(message.id).unwrap();
+    }
+    assert!(client.messages.queue.cancel(queued[0].id).is_err());
+  };
+  let (get_config, cancel_tuning, ()) = tokio::join!(get_config, cancel_tuning, operator);
+
+  for reply in [get_config, cancel_tuning] {
+    let error = reply.expect_err("cancelled request resolved");
+    assert!(error.contains("Cancelled"), "unexpected error {}", error);
+  }
+}
@@ -0,0 +1,147 @@
+mod common;
+
+use common::{disconnected_count, temp_directory, Client, MockRov};
+use futures_util::{SinkExt, StreamExt};
+use manafish_lib::models::capture::{FrameDirection, FrameKind};
+use manafish_lib::websocket::capture::read_capture;
+use manafish_lib::websocket::message::WebsocketMessage;
+use manafish_lib::websocket::request::{send_message, send_request, REQUEST_TIMEOUT};
+use manafish_protocol::schema::{schema_bundle, ProtocolValidator, PROTOCOL_SCHEMA_PATH};
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+use tokio::time::timeout;
+use tokio_tungstenite::connect_async;
+use tokio_tungstenite::tungstenite::protocol::frame::coding::CloseCode;
+use tokio_tungstenite::tungstenite::Message;
+
+#[test]
+fn checked_in_protocol_schema_matches_the_models() {
+  for (name, schema) in schema_bundle() {
+    let path = Path::new(PROTOCOL_SCHEMA_PATH).join(&name);
+    let checked_in = fs::read_to_string(&path).unwrap_or_default();
+    assert!(
+      checked_in == serde_json::to_string_pretty(&schema).unwrap() + "\n",
+      "docs/protocol-schema/{} is out of date, run `bun run protocol-schema`",
+      name
+    );
+  }
+}
+
+#[test]
+fn validator_points_at_the_offending_field() {
+  let validator = ProtocolValidator::new();
+
+  assert!(validator.validate(r#"{"type":"getConfig","id":7}"#).is_ok());
+  assert!(validator
+    .validate(r#"{"type":"startThrusterTest","payload":3}"#)
+    .is_ok());
+
+  let error = validator
+    .validate(r#"{"type":"startThrusterTest","payload":300}"#)
+    .unwrap_err();
+  assert!(error.contains("/payload"), "{}", error);
+  let error = validator
+    .validate(r#"{"type":"hello","payload":{"protocolVersion":1}}"#)
+    .unwrap_err();
+  assert!(error.contains("softwareVersion"), "{}", error);
+  let error = validator
+    .validate(r#"{"type":"selfDestruct"}"#)
+    .unwrap_err();
+  assert!(error.contains("unknown type"), "{}", error);
+  assert!(validator.validate("not json").is_err());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn traffic_between_app_and_mock_rov_follows_the_schema() {
+  let directory = temp_directory("protocol-schema", "traffic");
+  let rov = MockRov::start_with_args(r#"{ "events": [] }"#, &["--validate"]);
+  let client = Client::connect_with(rov.port, |config| {
+    config.capture_traffic = true;
+    config.capture_directory = directory.to_string_lossy().into_owned();
+  });
+
+  let Ok(WebsocketMessage::Config(config)) = send_request(
+    &client.messages,
+    WebsocketMessage::GetConfig,
+    REQUEST_TIMEOUT,
+  )
+  .await
+  else {
+    panic!("no config from ROV");
+  };
+  for message in [
+    WebsocketMessage::SetConfig(config),
+    WebsocketMessage::StartThrusterTest(0),
+    WebsocketMessage::TogglePitchStabilization,
+    WebsocketMessage::CustomAction("lights".to_string()),
+  ] {
+    send_request(&client.messages, message, REQUEST_TIMEOUT)
+      .await
+      .expect("request was not answered");
+  }
+  send_message(
+    &client.messages,
+    WebsocketMessage::DirectionVector([0.1, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0]),
+  )
+  .unwrap();
+  client
+    .wait_for_events(Duration::from_secs(3), "rov_telemetry", |telemetry| {
+      telemetry.len() >= 5
+    })
+    .await;
+  assert_eq!(
+    disconnected_count(&client.statuses()),
+    0,
+    "mock ROV rejected the app's traffic"
+  );
+  drop(client);
+
+  let mut files: Vec<_> = fs::read_dir(&directory)
+    .expect("no capture directory")
+    .map(|entry| entry.unwrap().path())
+    .collect();
+  files.sort();
+  let frames = read_capture(&files).expect("unreadable capture");
+  let validator = ProtocolValidator::new();
+  for direction in [FrameDirection::Outbound, FrameDirection::Inbound] {
+    let texts: Vec<_> = frames
+      .iter()
+      .filter(|frame| frame.direction == direction && frame.kind == FrameKind::Text)
+      .collect();
+    assert!(!texts.is_empty(), "no {:?} frames captured", direction);
+    for frame in texts {
+      if let Err(e) = validator.validate(&frame.text) {
+        panic!("{:?}: {}", direction, e);
+      }
+    }
+  }
+  let _ = fs::remove_dir_all(&directory);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn mock_rov_closes_connections_that_violate_the_schema() {
+  let rov = MockRov::start_with_args(r#"{ "events": [] }"#, &["--validate"]);
+  let (mut socket, _) = connect_async(format!("ws://127.0.0.1:{}", rov.port))
+    .await
+    .expect("failed to connect to mock ROV");
+
+  socket
+    .send(Message::Text(
+      r#"{"type":"startThrusterTest","payload":300}"#.into(),
+    ))
+    .await
+    .unwrap();
+
+  let close = timeout(Duration::from_secs(3), async {
+    while let Some(Ok(message)) = socket.next().await {
+      if let Message::Close(frame) = message {
+        return frame;
+      }
+    }
+    None
+  })
+  .await
+  .expect("mock ROV kept the connection open");
+  assert_eq!(close.map(|frame| frame.code), Some(CloseCode::Policy));
+}
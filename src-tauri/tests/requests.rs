@@ -0,0 +1,169 @@
+mod common;
+
+use common::{Client, MockRov};
+use manafish_lib::websocket::message::WebsocketMessage;
+use manafish_lib::websocket::request::{send_request, REQUEST_TIMEOUT};
+use manafish_lib::websocket::send::config::{
+  handle_request_rov_config, handle_set_rov_config, handle_start_regulator_auto_tuning,
+  handle_start_thruster_test,
+};
+use std::time::{Duration, Instant};
+use tokio::time::sleep;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn get_config_resolves_with_config() {
+  let rov = MockRov::start(r#"{ "events": [] }"#);
+  let client = Client::connect(rov.port);
+
+  let reply = send_request(
+    &client.messages,
+    WebsocketMessage::GetConfig,
+    REQUEST_TIMEOUT,
+  )
+  .await;
+
+  assert!(
+    matches!(reply, Ok(WebsocketMessage::Config(_))),
+    "{:?}",
+    reply
+  );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn set_config_resolves_with_applied_config() {
+  let rov = MockRov::start(r#"{ "events": [] }"#);
+  let client = Client::connect(rov.port);
+
+  let Ok(WebsocketMessage::Config(mut config)) = send_request(
+    &client.messages,
+    WebsocketMessage::GetConfig,
+    REQUEST_TIMEOUT,
+  )
+  .await
+  else {
+    panic!("no config from ROV");
+  };
+  config.power.user_max_power = 0.25;
+
+  let reply = send_request(
+    &client.messages,
+    WebsocketMessage::SetConfig(config),
+    REQUEST_TIMEOUT,
+  )
+  .await;
+
+  match reply {
+    Ok(WebsocketMessage::Config(applied)) => assert_eq!(applied.power.user_max_power, 0.25),
+    other => panic!("unexpected reply {:?}", other),
+  }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn commands_are_acknowledged() {
+  let rov = MockRov::start(r#"{ "events": [] }"#);
+  let client = Client::connect(rov.port);
+
+  let start = send_request(
+    &client.messages,
+    WebsocketMessage::StartThrusterTest(2),
+    REQUEST_TIMEOUT,
+  )
+  .await;
+  let cancel = send_request(
+    &client.messages,
+    WebsocketMessage::CancelThrusterTest(2),
+    REQUEST_TIMEOUT,
+  )
+  .await;
+
+  assert!(matches!(start, Ok(WebsocketMessage::Ack)), "{:?}", start);
+  assert!(matches!(cancel, Ok(WebsocketMessage::Ack)), "{:?}", cancel);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn rejected_commands_fail_with_reason() {
+  let rov = MockRov::start(r#"{ "events": [] }"#);
+  let client = Client::connect(rov.port);
+
+  let reply = send_request(
+    &client.messages,
+    WebsocketMessage::CancelRegulatorAutoTuning,
+    REQUEST_TIMEOUT,
+  )
+  .await;
+
+  let error = reply.expect_err("cancel without auto-tuning was accepted");
+  assert!(error.contains("not running"), "unexpected error {}", error);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn unanswered_request_times_out() {
+  let rov = MockRov::start(r#"{ "events": [{ "at": 1000, "fault": "unansweredPings" }] }"#);
+  let client = Client::connect(rov.port);
+  client.wait_connected().await;
+  sleep(Duration::from_millis(1500)).await;
+
+  let started = Instant::now();
+  let reply = send_request(
+    &client.messages,
+    WebsocketMessage::GetConfig,
+    Duration::from_secs(2),
+  )
+  .await;
+
+  let error = reply.expect_err("request without reply resolved");
+  assert!(
+    error.contains("did not reply"),
+    "unexpected error {}",
+    error
+  );
+  assert!(started.elapsed() < Duration::from_secs(4));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn disconnect_fails_pending_requests() {
+  let rov = MockRov::start(
+    r#"{ "events": [
+      { "at": 1000, "connection": 1, "fault": "unansweredPings" },
+      { "at": 3000, "connection": 1, "fault": "disconnect" }
+    ] }"#,
+  );
+  let client = Client::connect(rov.port);
+  client.wait_connected().await;
+  sleep(Duration::from_millis(1500)).await;
+
+  let started = Instant::now();
+  let reply = send_request(
+    &client.messages,
+    WebsocketMessage::GetConfig,
+    Duration::from_secs(15),
+  )
+  .await;
+
+  let error = reply.expect_err("request resolved on a dropped connection");
+  assert!(error.contains("Disconnected"), "unexpected error {}", error);
+  assert!(started.elapsed() < Duration::from_secs(10));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn legacy_rov_answers_without_request_ids() {
+  let rov = MockRov::start_with_args(r#"{ "events": [] }"#, &["--legacy"]);
+  let client = Client::connect(rov.port);
+  client.wait_connected().await;
+
+  let config = handle_request_rov_config(&client.messages).await.unwrap();
+  let mut changed = config.clone();
+  changed.power.user_max_power = 0.25;
+  let applied = handle_set_rov_config(&client.messages, changed)
+    .await
+    .unwrap();
+  assert_eq!(applied.power.user_max_power, 0.25);
+
+  // Nothing acknowledges these, they are done once sent.
+  handle_start_thruster_test(&client.messages, 2)
+    .await
+    .unwrap();
+  handle_start_regulator_auto_tuning(&client.messages)
+    .await
+    .unwrap();
+}
@@ -0,0 +1,120 @@
+mod common;
+
+use common::{Client, MockRov};
+use manafish_lib::models::config::{Config, RovProfile};
+use manafish_lib::models::rov_config::MicrocontrollerFirmwareVariant;
+use std::time::Duration;
+
+fn profile(name: &str, port: u16) -> RovProfile {
+  RovProfile {
+    name: name.to_string(),
+    ip_address: "127.0.0.1".to_string(),
+    web_socket_port: port,
+    webrtc_signaling_api_port: 1984,
+    webrtc_signaling_api_path: "/api/webrtc?src=cam".to_string(),
+    use_tls: false,
+    certificate_fingerprint: None,
+    auth_token: None,
+    expected_firmware_variant: Some(MicrocontrollerFirmwareVariant::Dshot),
+    last_known_rov_config: None,
+    notes: String::new(),
+  }
+}
+
+fn fleet() -> Config {
+  let mut config = Config::default();
+  config.create_rov_profile(profile("Alpha", 9100)).unwrap();
+  config.create_rov_profile(profile("Bravo", 9200)).unwrap();
+  config
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn switching_profile_reconnects_to_its_rov() {
+  let alpha = MockRov::start_with_args(r#"{ "events": [] }"#, &["--firmware-version", "alpha"]);
+  let bravo = MockRov::start_with_args(r#"{ "events": [] }"#, &["--firmware-version", "bravo"]);
+  let mut config = Config::default();
+  config
+    .create_rov_profile(profile("Alpha", alpha.port))
+    .unwrap();
+  config
+    .create_rov_profile(profile("Bravo", bravo.port))
+    .unwrap();
+  config.switch_rov_profile("Alpha").unwrap();
+  let initial = config.clone();
+  let client = Client::connect_with(alpha.port, |client_config| *client_config = initial);
+  client.wait_connected().await;
+
+  config.switch_rov_profile("Bravo").unwrap();
+  client.config_tx.send(config).await.unwrap();
+
+  let handshakes = client
+    .wait_for_events(
+      Duration::from_secs(5),
+      "rov_handshake_completed",
+      |handshakes| handshakes.len() == 2,
+    )
+    .await;
+  assert_eq!(handshakes[0]["firmwareVersion"], "alpha");
+  assert_eq!(handshakes[1]["firmwareVersion"], "bravo");
+}
+
+#[test]
+fn switching_profile_copies_its_endpoint() {
+  let mut config = fleet();
+
+  config.switch_rov_profile("Bravo").unwrap();
+
+  assert_eq!(config.active_rov_profile.as_deref(), Some("Bravo"));
+  assert_eq!(config.web_socket_port, 9200);
+  assert!(config.switch_rov_profile("Charlie").is_err());
+  assert_eq!(config.active_rov_profile.as_deref(), Some("Bravo"));
+}
+
+#[test]
+fn profile_names_are_unique() {
+  let mut config = fleet();
+
+  assert!(config.create_rov_profile(profile("Alpha", 9300)).is_err());
+  assert!(config.create_rov_profile(profile(" ", 9300)).is_err());
+  assert!(config
+    .update_rov_profile("Alpha", profile("Bravo", 9100))
+    .is_err());
+  assert_eq!(config.rov_profiles.len(), 2);
+}
+
+#[test]
+fn editing_active_profile_applies_its_endpoint() {
+  let mut config = fleet();
+  config.switch_rov_profile("Alpha").unwrap();
+
+  config
+    .update_rov_profile("Alpha", profile("Alpha Prime", 9150))
+    .unwrap();
+
+  assert_eq!(config.active_rov_profile.as_deref(), Some("Alpha Prime"));
+  assert_eq!(config.web_socket_port, 9150);
+}
+
+#[test]
+fn deleting_active_profile_keeps_its_endpoint() {
+  let mut config = fleet();
+  config.switch_rov_profile("Bravo").unwrap();
+
+  config.delete_rov_profile("Bravo").unwrap();
+
+  assert_eq!(config.active_rov_profile, None);
+  assert_eq!(config.web_socket_port, 9200);
+  assert_eq!(config.rov_profiles.len(), 1);
+}
+
+#[test]
+fn config_without_profiles_still_loads() {
+  let mut saved = serde_json::to_value(Config::default()).unwrap();
+  let fields = saved.as_object_mut().unwrap();
+  fields.remove("rovProfiles");
+  fields.remove("activeRovProfile");
+
+  let config: Config = serde_json::from_value(saved).unwrap();
+
+  assert!(config.rov_profiles.is_empty());
+}
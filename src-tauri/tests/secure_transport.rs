@@ -0,0 +1,132 @@
+mod common;
+
+use common::{phases, Client, MockRov};
+use manafish_lib::models::connection::ConnectionPhase;
+use manafish_lib::websocket::manager::PRIMARY_VEHICLE;
+use std::time::Duration;
+use tokio::time::sleep;
+
+const EVENT: &str = "rov_connection_state_changed";
+const CERTIFICATE: &str = concat!(
+  env!("CARGO_MANIFEST_DIR"),
+  "/tests/fixtures/rov-certificate.pem"
+);
+const KEY: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/rov-key.pem");
+// As printed by `openssl x509 -fingerprint -sha256` for the fixtures.
+const FINGERPRINT: &str = concat!(
+  "59:BD:1E:66:5A:FA:BE:88:42:00:CD:2C:40:B6:83:83:",
+  "33:2D:08:65:97:4F:0C:1B:22:EA:C1:C1:91:C9:64:97"
+);
+const OTHER_FINGERPRINT: &str = concat!(
+  "F5:5B:44:9C:B0:E5:9F:F0:3D:EB:EF:26:4C:36:FB:D3:",
+  "2A:D3:4C:FA:21:A0:5E:5D:02:EF:73:22:6E:58:47:4A"
+);
+
+fn secure_rov(extra_args: &[&str]) -> MockRov {
+  let mut args = vec!["--tls-certificate", CERTIFICATE, "--tls-key", KEY];
+  args.extend_from_slice(extra_args);
+  MockRov::start_with_args(r#"{ "events": [] }"#, &args)
+}
+
+async fn wait_for_reason(client: &Client, text: &str) -> String {
+  let states = client
+    .wait_for_events(Duration::from_secs(5), EVENT, |states| {
+      states
+        .iter()
+        .any(|state| state["reason"].as_str().unwrap_or("").contains(text))
+    })
+    .await;
+  assert!(!phases(&states).contains(&"connected"));
+  client.control.state.get().reason
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn certificate_is_trusted_on_first_use() {
+  let rov = secure_rov(&[]);
+  let client = Client::connect_with(rov.port, |config| config.use_tls = true);
+
+  let states = client
+    .wait_for_events(Duration::from_secs(3), EVENT, |states| {
+      phases(states).contains(&"connected")
+    })
+    .await;
+  assert!(states[0]["reason"]
+    .as_str()
+    .unwrap()
+    .starts_with("Connecting to wss://"));
+
+  let trusted = client.trusted_certificates();
+  assert_eq!(trusted.len(), 1);
+  assert_eq!(trusted[0].vehicle_id, PRIMARY_VEHICLE);
+  assert_eq!(trusted[0].fingerprint, FINGERPRINT);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn pinned_certificate_connects_without_asking_again() {
+  let rov = secure_rov(&[]);
+  let client = Client::connect_with(rov.port, |config| {
+    config.use_tls = true;
+    config.certificate_fingerprint = Some(FINGERPRINT.replace(':', "").to_lowercase());
+  });
+
+  client
+    .wait_for_events(Duration::from_secs(3), EVENT, |states| {
+      phases(states).contains(&"connected")
+    })
+    .await;
+  assert!(client.trusted_certificates().is_empty());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn other_certificate_is_refused() {
+  let rov = secure_rov(&[]);
+  let client = Client::connect_with(rov.port, |config| {
+    config.use_tls = true;
+    config.certificate_fingerprint = Some(OTHER_FINGERPRINT.to_string());
+  });
+
+  let reason = wait_for_reason(&client, "is pinned").await;
+  assert!(reason.contains(FINGERPRINT));
+  assert!(client.trusted_certificates().is_empty());
+  assert!(client.handshake.get().is_none());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn token_is_sent_when_connecting() {
+  let rov = secure_rov(&["--token", "s3cret"]);
+  let client = Client::connect_with(rov.port, |config| {
+    config.use_tls = true;
+    config.auth_token = Some("s3cret".to_string());
+  });
+
+  client
+    .wait_for_events(Duration::from_secs(3), EVENT, |states| {
+      phases(states).contains(&"connected")
+    })
+    .await;
+  assert_eq!(client.control.state.get().phase, ConnectionPhase::Connected);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn wrong_token_is_refused() {
+  let rov = MockRov::start_with_args(r#"{ "events": [] }"#, &["--token", "s3cret"]);
+  let without = Client::connect(rov.port);
+  let wrong = Client::connect_with(rov.port, |config| {
+    config.auth_token = Some("guess".to_string());
+  });
+
+  wait_for_reason(&without, "authentication token").await;
+  wait_for_reason(&wrong, "authentication token").await;
+  sleep(Duration::from_millis(500)).await;
+  assert!(without.statuses().iter().all(|status| !status.is_connected));
+  assert!(wrong.statuses().iter().all(|status| !status.is_connected));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn plain_client_can_not_talk_to_tls_rov() {
+  let rov = secure_rov(&[]);
+  let client = Client::connect(rov.port);
+
+  wait_for_reason(&client, "WebSocket connect error").await;
+  assert!(client.trusted_certificates().is_empty());
+}
@@ -0,0 +1,86 @@
+mod common;
+
+use common::{Client, MockRov};
+use manafish_lib::models::telemetry_history::TelemetryField;
+use manafish_lib::websocket::manager::PRIMARY_VEHICLE;
+use manafish_lib::websocket::telemetry_history::TelemetryHistory;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::sleep;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn history_downsamples_telemetry_and_status() {
+  let rov = MockRov::start_with_args(r#"{ "events": [] }"#, &["--status-rate", "10"]);
+  let client = Client::connect(rov.port);
+  client.wait_connected().await;
+  sleep(Duration::from_millis(1000)).await;
+
+  let fields = [
+    TelemetryField::Depth,
+    TelemetryField::ThrusterRpm8,
+    TelemetryField::BatteryPercentage,
+  ];
+  let series = client
+    .state::<Arc<TelemetryHistory>>()
+    .query(PRIMARY_VEHICLE, &fields, None, None, 5)
+    .unwrap();
+  assert_eq!(series.len(), 3);
+  for (series, field) in series.iter().zip(fields) {
+    assert_eq!(series.field, field);
+    assert!(!series.buckets.is_empty(), "no {:?} readings", field);
+    assert!(series.buckets.len() <= 5);
+    for bucket in &series.buckets {
+      assert!(
+        bucket.min <= bucket.mean && bucket.mean <= bucket.max,
+        "{:?}",
+        bucket
+      );
+      assert!(bucket.from_ms <= bucket.to_ms);
+    }
+    assert!(series
+      .buckets
+      .windows(2)
+      .all(|pair| pair[0].to_ms < pair[1].from_ms));
+  }
+
+  // Every reading in its own bucket, within the bounds asked for.
+  let depth = &series[0].buckets;
+  let (from_ms, to_ms) = (depth[0].from_ms, depth.last().unwrap().to_ms);
+  let count: u32 = depth.iter().map(|bucket| bucket.count).sum();
+  let all = client
+    .state::<Arc<TelemetryHistory>>()
+    .query(
+      PRIMARY_VEHICLE,
+      &[TelemetryField::Depth],
+      Some(from_ms),
+      Some(to_ms),
+      10_000,
+    )
+    .unwrap();
+  assert_eq!(all[0].buckets.len() as u32, count);
+  assert!(all[0].buckets.iter().all(|bucket| bucket.count == 1));
+
+  assert!(client
+    .state::<Arc<TelemetryHistory>>()
+    .query(PRIMARY_VEHICLE, &fields, Some(to_ms), Some(from_ms), 5)
+    .is_err());
+  assert!(client
+    .state::<Arc<TelemetryHistory>>()
+    .query(PRIMARY_VEHICLE, &fields, None, None, 0)
+    .is_err());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn no_history_is_kept_when_disabled() {
+  let rov = MockRov::start(r#"{ "events": [] }"#);
+  let client = Client::connect_with(rov.port, |config| config.telemetry_history_minutes = 0);
+  client
+    .wait_for_event(Duration::from_secs(5), "rov_telemetry")
+    .await;
+
+  let series = client
+    .state::<Arc<TelemetryHistory>>()
+    .query(PRIMARY_VEHICLE, &[TelemetryField::Pitch], None, None, 10)
+    .unwrap();
+  assert!(series[0].buckets.is_empty());
+}
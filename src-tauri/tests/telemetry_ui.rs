@@ -0,0 +1,136 @@
+mod common;
+
+use common::{Client, MockRov};
+use manafish_lib::models::capture::{CapturedFrame, FrameDirection, FrameKind};
+use manafish_lib::models::telemetry_ui::TelemetryUiMode;
+use manafish_lib::websocket::handler::{HandlerResult, MessageHandler, FRONTEND_ORDER};
+use manafish_lib::websocket::manager::PRIMARY_VEHICLE;
+use manafish_lib::websocket::message::{WebsocketEnvelope, WebsocketMessage};
+use manafish_lib::websocket::replay::replay_capture;
+use manafish_lib::websocket::telemetry_ui::TelemetryUi;
+use manafish_lib::websocket::vehicle::VehicleEmitter;
+use std::net::TcpListener;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::test::MockRuntime;
+use tokio::time::sleep;
+
+// Counts every telemetry frame, as a recorder would see them.
+struct Counter(Arc<AtomicU64>);
+
+impl MessageHandler<MockRuntime> for Counter {
+  fn subscribes_to(&self, message: &WebsocketMessage) -> bool {
+    matches!(message, WebsocketMessage::Telemetry(_))
+  }
+
+  fn handle(
+    &self,
+    _vehicle: &VehicleEmitter<MockRuntime>,
+    _envelope: &WebsocketEnvelope,
+  ) -> HandlerResult {
+    self.0.fetch_add(1, Ordering::Relaxed);
+    Ok(Vec::new())
+  }
+}
+
+fn fast_rov() -> MockRov {
+  MockRov::start_with_args(r#"{ "events": [] }"#, &["--telemetry-rate", "100"])
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn frontend_gets_telemetry_at_the_configured_rate() {
+  let rov = fast_rov();
+  let client = Client::connect_with(rov.port, |config| {
+    config.telemetry_ui_rate_hz = 10;
+    config.telemetry_ui_mode = TelemetryUiMode::Latest;
+  });
+  let counted = Arc::new(AtomicU64::new(0));
+  // Ahead of the frontend, so it has always seen at least as many frames.
+  client
+    .manager
+    .handlers()
+    .register("counter", FRONTEND_ORDER - 1, Counter(counted.clone()));
+
+  client
+    .wait_for_events(Duration::from_secs(3), "rov_telemetry", |telemetry| {
+      !telemetry.is_empty()
+    })
+    .await;
+  sleep(Duration::from_secs(2)).await;
+
+  let events = client.events("rov_telemetry").len() as u64;
+  let stats = client.state::<Arc<TelemetryUi>>().stats(PRIMARY_VEHICLE);
+  assert!((10..=30).contains(&events), "{} telemetry events", events);
+  assert!(stats.emitted >= events);
+  assert!(stats.received >= events * 3, "{:?}", stats);
+  assert!(stats.dropped > 0, "{:?}", stats);
+  assert!(counted.load(Ordering::Relaxed) >= stats.received);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn batches_carry_every_frame() {
+  let rov = fast_rov();
+  let client = Client::connect_with(rov.port, |config| {
+    config.telemetry_ui_rate_hz = 10;
+    config.telemetry_ui_mode = TelemetryUiMode::Batch;
+  });
+
+  let batches = client
+    .wait_for_events(Duration::from_secs(5), "rov_telemetry_batch", |batches| {
+      batches.len() >= 10
+    })
+    .await;
+  let stats = client.state::<Arc<TelemetryUi>>().stats(PRIMARY_VEHICLE);
+
+  let frames: usize = batches
+    .iter()
+    .map(|batch| batch.as_array().unwrap().len())
+    .sum();
+  assert!(frames >= batches.len() * 3, "{} frames", frames);
+  assert!(frames as u64 <= stats.received);
+  assert_eq!(stats.dropped, 0);
+  assert!(client.events("rov_telemetry").is_empty());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn average_mode_passes_on_the_mean_of_an_interval() {
+  // A client without a ROV to talk to, so all telemetry comes from the replay.
+  let port = TcpListener::bind("127.0.0.1:0")
+    .unwrap()
+    .local_addr()
+    .unwrap()
+    .port();
+  let client = Client::connect_with(port, |config| {
+    config.telemetry_ui_rate_hz = 4;
+    config.telemetry_ui_mode = TelemetryUiMode::Average;
+  });
+  let frames: Vec<_> = (1..=5)
+    .map(|depth| CapturedFrame {
+      direction: FrameDirection::Inbound,
+      elapsed_ms: depth,
+      kind: FrameKind::Text,
+      text: format!(
+        r#"{{"type":"telemetry","payload":{{"pitch":0.0,"roll":0.0,"desiredPitch":0.0,"desiredRoll":0.0,"depth":{}.0,"thrusterRpms":[0.0,0.0,0.0,0.0,0.0,0.0,0.0,0.0]}}}}"#,
+        depth
+      ),
+    })
+    .collect();
+
+  let telemetry_ui = Arc::new(TelemetryUi::default());
+  telemetry_ui.configure(&client.config);
+  let emitter = client.manager.emitter(None).unwrap();
+  replay_capture(&emitter, telemetry_ui.clone(), &frames, 1.0).await;
+  sleep(Duration::from_millis(500)).await;
+
+  // The first frame goes out at once, the others within its interval are
+  // averaged.
+  let depths: Vec<_> = client
+    .events("rov_telemetry")
+    .iter()
+    .map(|telemetry| telemetry["depth"].as_f64().unwrap())
+    .collect();
+  assert_eq!(depths, [1.0, 3.5]);
+  let stats = telemetry_ui.stats(PRIMARY_VEHICLE);
+  assert_eq!((stats.received, stats.emitted, stats.dropped), (5, 2, 3));
+}
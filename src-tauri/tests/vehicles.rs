@@ -0,0 +1,186 @@
+mod common;
+
+use common::{Client, MockRov};
+use manafish_lib::models::config::RovProfile;
+use manafish_lib::websocket::direction_vector::NEUTRAL_DIRECTION_VECTOR;
+use manafish_lib::websocket::manager::{VehicleConnection, PRIMARY_VEHICLE};
+use manafish_lib::websocket::message::WebsocketMessage;
+use manafish_lib::websocket::request::{send_request, REQUEST_TIMEOUT};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::{sleep, Instant};
+
+fn profile(name: &str, port: u16) -> RovProfile {
+  RovProfile {
+    name: name.to_string(),
+    ip_address: "127.0.0.1".to_string(),
+    web_socket_port: port,
+    webrtc_signaling_api_port: 1984,
+    webrtc_signaling_api_path: "/api/webrtc?src=cam".to_string(),
+    use_tls: false,
+    certificate_fingerprint: None,
+    auth_token: None,
+    expected_firmware_variant: None,
+    last_known_rov_config: None,
+    notes: String::new(),
+  }
+}
+
+// Connects the primary vehicle to `alpha` and a second vehicle to `bravo`.
+async fn fleet(alpha: &MockRov, bravo: &MockRov) -> (Client, Arc<VehicleConnection>) {
+  let bravo_port = bravo.port;
+  let client = Client::connect_with(alpha.port, |config| {
+    config
+      .create_rov_profile(profile("Bravo", bravo_port))
+      .unwrap();
+  });
+  let second = client
+    .manager
+    .open(
+      "Bravo".to_string(),
+      Some("Bravo".to_string()),
+      &client.config,
+    )
+    .unwrap();
+
+  let deadline = Instant::now() + Duration::from_secs(5);
+  while client.vehicle_events("rov_handshake_completed").len() < 2 {
+    assert!(Instant::now() < deadline, "vehicles did not connect");
+    sleep(Duration::from_millis(50)).await;
+  }
+  (client, second)
+}
+
+async fn get_power(vehicle: &VehicleConnection) -> f32 {
+  match send_request(
+    &vehicle.messages,
+    WebsocketMessage::GetConfig,
+    REQUEST_TIMEOUT,
+  )
+  .await
+  {
+    Ok(WebsocketMessage::Config(config)) => config.power.user_max_power,
+    other => panic!("unexpected reply {:?}", other),
+  }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn events_are_tagged_with_their_vehicle() {
+  let alpha = MockRov::start_with_args(r#"{ "events": [] }"#, &["--firmware-version", "alpha"]);
+  let bravo = MockRov::start_with_args(r#"{ "events": [] }"#, &["--firmware-version", "bravo"]);
+  let (client, _) = fleet(&alpha, &bravo).await;
+
+  let mut handshakes = client.vehicle_events("rov_handshake_completed");
+  handshakes.sort_by_key(|event| event.vehicle_id.clone());
+
+  assert_eq!(handshakes[0].vehicle_id.as_deref(), Some("Bravo"));
+  assert_eq!(handshakes[0].payload["firmwareVersion"], "bravo");
+  assert!(!handshakes[0].pilot);
+  assert_eq!(handshakes[1].vehicle_id.as_deref(), Some(PRIMARY_VEHICLE));
+  assert_eq!(handshakes[1].payload["firmwareVersion"], "alpha");
+  assert!(handshakes[1].pilot);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn commands_reach_only_their_vehicle() {
+  let alpha = MockRov::start(r#"{ "events": [] }"#);
+  let bravo = MockRov::start(r#"{ "events": [] }"#);
+  let (client, second) = fleet(&alpha, &bravo).await;
+  let Ok(WebsocketMessage::Config(mut config)) = send_request(
+    &second.messages,
+    WebsocketMessage::GetConfig,
+    REQUEST_TIMEOUT,
+  )
+  .await
+  else {
+    panic!("no config from second vehicle");
+  };
+
+  config.power.user_max_power = 0.5;
+  send_request(
+    &second.messages,
+    WebsocketMessage::SetConfig(config),
+    REQUEST_TIMEOUT,
+  )
+  .await
+  .unwrap();
+
+  assert_eq!(get_power(&second).await, 0.5);
+  assert_ne!(get_power(&client).await, 0.5);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn pilot_input_moves_to_the_selected_vehicle() {
+  let alpha = MockRov::start(r#"{ "events": [] }"#);
+  let bravo = MockRov::start(r#"{ "events": [] }"#);
+  let (client, _) = fleet(&alpha, &bravo).await;
+  client
+    .direction_vectors
+    .tx
+    .send([0.5, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0])
+    .unwrap();
+
+  client.manager.set_pilot("Bravo").unwrap();
+
+  assert_eq!(client.manager.get(None).unwrap().id, "Bravo");
+  assert_eq!(
+    *client.direction_vectors.tx.borrow(),
+    NEUTRAL_DIRECTION_VECTOR
+  );
+  let switched = client.vehicle_events("rov_telemetry").len();
+  sleep(Duration::from_millis(500)).await;
+  let telemetry = &client.vehicle_events("rov_telemetry")[switched..];
+  assert!(!telemetry.is_empty());
+  for event in telemetry {
+    assert_eq!(event.pilot, event.vehicle_id.as_deref() == Some("Bravo"));
+  }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn closed_vehicle_hands_pilot_input_back() {
+  let alpha = MockRov::start(r#"{ "events": [] }"#);
+  let bravo = MockRov::start(r#"{ "events": [] }"#);
+  let (client, second) = fleet(&alpha, &bravo).await;
+  client.manager.set_pilot("Bravo").unwrap();
+
+  client.manager.close("Bravo").await.unwrap();
+
+  let vehicles = client.manager.list();
+  assert_eq!(vehicles.len(), 1);
+  assert!(vehicles[0].pilot);
+  assert!(client.manager.get(Some("Bravo")).is_err());
+  assert!(client.manager.close(PRIMARY_VEHICLE).await.is_err());
+  assert!(send_request(
+    &second.messages,
+    WebsocketMessage::GetConfig,
+    REQUEST_TIMEOUT
+  )
+  .await
+  .is_err());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn profiles_clashing_with_open_vehicles_are_refused() {
+  let alpha = MockRov::start(r#"{ "events": [] }"#);
+  let bravo = MockRov::start(r#"{ "events": [] }"#);
+  let (alpha_port, bravo_port) = (alpha.port, bravo.port);
+  let client = Client::connect_with(alpha.port, |config| {
+    config
+      .create_rov_profile(profile(PRIMARY_VEHICLE, bravo_port))
+      .unwrap();
+    config
+      .create_rov_profile(profile("Alpha", alpha_port))
+      .unwrap();
+  });
+  let open = |name: &str| {
+    client
+      .manager
+      .open(name.to_string(), Some(name.to_string()), &client.config)
+  };
+
+  let reserved = open(PRIMARY_VEHICLE).err().unwrap();
+  assert!(reserved.contains("reserved"), "{}", reserved);
+  let shared = open("Alpha").err().unwrap();
+  assert!(shared.contains(PRIMARY_VEHICLE), "{}", shared);
+  assert_eq!(client.manager.list().len(), 1);
+}
@@ -0,0 +1,11 @@
+use manafish_protocol::bindings::{typescript, BINDINGS_PATH};
+use std::fs;
+use std::process::exit;
+
+fn main() {
+  if let Err(e) = fs::write(BINDINGS_PATH, typescript()) {
+    eprintln!("Failed to write {}: {}", BINDINGS_PATH, e);
+    exit(1);
+  }
+  println!("Wrote {}", BINDINGS_PATH);
+}
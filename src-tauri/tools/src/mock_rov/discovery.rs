@@ -0,0 +1,26 @@
+use manafish_protocol::models::discovery::{Announcement, DiscoveryMessage};
+use tokio::net::UdpSocket;
+
+pub async fn answer_probes(socket: UdpSocket, announcement: Announcement) {
+  let answer = serde_json::to_vec(&DiscoveryMessage::Announce(announcement)).unwrap();
+  let mut buffer = [0u8; 2048];
+
+  loop {
+    let (len, from) = match socket.recv_from(&mut buffer).await {
+      Ok(received) => received,
+      Err(e) => {
+        eprintln!("Failed to receive discovery probe: {}", e);
+        continue;
+      }
+    };
+    if !matches!(
+      serde_json::from_slice(&buffer[..len]),
+      Ok(DiscoveryMessage::Discover)
+    ) {
+      continue;
+    }
+    if let Err(e) = socket.send_to(&answer, from).await {
+      eprintln!("Failed to answer discovery probe from {}: {}", from, e);
+    }
+  }
+}
@@ -0,0 +1,122 @@
+use std::io;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::task::{Context, Poll};
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncWrite, AsyncWriteExt, ReadBuf};
+use tokio::net::tcp::OwnedReadHalf;
+use tokio::net::TcpStream;
+use tokio::sync::mpsc::{unbounded_channel, UnboundedSender};
+use tokio::time::{sleep_until, Instant};
+
+pub struct LinkConditions {
+  pub latency: Duration,
+  pub jitter: Duration,
+  pub drop_ratio: f64,
+  random_state: u64,
+}
+
+impl LinkConditions {
+  pub fn new(seed: u64) -> Self {
+    LinkConditions {
+      latency: Duration::ZERO,
+      jitter: Duration::ZERO,
+      drop_ratio: 0.0,
+      random_state: seed | 1,
+    }
+  }
+
+  pub fn reset(&mut self) {
+    self.latency = Duration::ZERO;
+    self.jitter = Duration::ZERO;
+    self.drop_ratio = 0.0;
+  }
+
+  pub fn should_drop(&mut self) -> bool {
+    self.drop_ratio > 0.0 && self.random() < self.drop_ratio
+  }
+
+  fn delay(&mut self) -> Duration {
+    self.latency + self.jitter.mul_f64(self.random())
+  }
+
+  fn random(&mut self) -> f64 {
+    // xorshift64, good enough to spread jitter and drops without a rand dependency.
+    self.random_state ^= self.random_state << 13;
+    self.random_state ^= self.random_state >> 7;
+    self.random_state ^= self.random_state << 17;
+    (self.random_state >> 11) as f64 / (1u64 << 53) as f64
+  }
+}
+
+pub fn lock(conditions: &Mutex<LinkConditions>) -> MutexGuard<'_, LinkConditions> {
+  conditions.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+// Delays every byte written towards the app by the current latency and jitter,
+// pongs and close frames included, while keeping the stream in order.
+pub struct LinkStream {
+  read: OwnedReadHalf,
+  tx: UnboundedSender<(Instant, Vec<u8>)>,
+  conditions: Arc<Mutex<LinkConditions>>,
+  last_due: Instant,
+}
+
+impl LinkStream {
+  pub fn new(stream: TcpStream, conditions: Arc<Mutex<LinkConditions>>) -> Self {
+    let (read, mut write) = stream.into_split();
+    let (tx, mut rx) = unbounded_channel::<(Instant, Vec<u8>)>();
+
+    tokio::spawn(async move {
+      while let Some((due, bytes)) = rx.recv().await {
+        sleep_until(due).await;
+        if write.write_all(&bytes).await.is_err() {
+          return;
+        }
+      }
+      let _ = write.shutdown().await;
+    });
+
+    LinkStream {
+      read,
+      tx,
+      conditions,
+      last_due: Instant::now(),
+    }
+  }
+}
+
+impl AsyncRead for LinkStream {
+  fn poll_read(
+    mut self: Pin<&mut Self>,
+    cx: &mut Context<'_>,
+    buf: &mut ReadBuf<'_>,
+  ) -> Poll<io::Result<()>> {
+    Pin::new(&mut self.read).poll_read(cx, buf)
+  }
+}
+
+impl AsyncWrite for LinkStream {
+  fn poll_write(
+    mut self: Pin<&mut Self>,
+    _cx: &mut Context<'_>,
+    buf: &[u8],
+  ) -> Poll<io::Result<usize>> {
+    let delay = lock(&self.conditions).delay();
+    let due = (Instant::now() + delay).max(self.last_due);
+    self.last_due = due;
+
+    match self.tx.send((due, buf.to_vec())) {
+      Ok(()) => Poll::Ready(Ok(buf.len())),
+      Err(_) => Poll::Ready(Err(io::ErrorKind::BrokenPipe.into())),
+    }
+  }
+
+  fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+    Poll::Ready(Ok(()))
+  }
+
+  fn poll_shutdown(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+    Poll::Ready(Ok(()))
+  }
+}
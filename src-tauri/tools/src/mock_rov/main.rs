@@ -0,0 +1,115 @@
+mod discovery;
+mod link;
+mod options;
+mod scenario;
+mod session;
+mod simulation;
+mod tls;
+
+use discovery::answer_probes;
+use options::Options;
+use scenario::Scenario;
+use session::handle_connection;
+use simulation::Simulation;
+use std::process::exit;
+use std::sync::{Arc, Mutex};
+use tokio::net::{TcpListener, UdpSocket};
+
+#[tokio::main]
+async fn main() {
+  let options = match Options::from_args(std::env::args().skip(1)) {
+    Ok(options) => options,
+    Err(e) => {
+      eprintln!("{}\n\n{}", e, Options::USAGE);
+      exit(2);
+    }
+  };
+
+  let scenario = match &options.scenario {
+    Some(path) => match Scenario::from_file(path) {
+      Ok(scenario) => scenario,
+      Err(e) => {
+        eprintln!("{}", e);
+        exit(2);
+      }
+    },
+    None => Scenario::default(),
+  };
+
+  let acceptor = match (&options.tls_certificate, &options.tls_key) {
+    (Some(certificate), Some(key)) => match tls::acceptor(certificate, key) {
+      Ok(acceptor) => Some(acceptor),
+      Err(e) => {
+        eprintln!("{}", e);
+        exit(2);
+      }
+    },
+    _ => None,
+  };
+
+  let listener = match TcpListener::bind((options.host.as_str(), options.port)).await {
+    Ok(listener) => listener,
+    Err(e) => {
+      eprintln!("Failed to bind {}:{}: {}", options.host, options.port, e);
+      exit(1);
+    }
+  };
+
+  // The first stdout line is the bound address so scripts can use `--port 0`.
+  let web_socket_port = match listener.local_addr() {
+    Ok(addr) => {
+      let scheme = if acceptor.is_some() { "wss" } else { "ws" };
+      println!("Mock ROV listening on {}://{}", scheme, addr);
+      addr.port()
+    }
+    Err(e) => {
+      eprintln!("Failed to read listener address: {}", e);
+      exit(1);
+    }
+  };
+
+  // Followed by the discovery address when discovery is on.
+  if let Some(discovery_port) = options.discovery_port {
+    let socket = match UdpSocket::bind((options.host.as_str(), discovery_port)).await {
+      Ok(socket) => socket,
+      Err(e) => {
+        eprintln!("Failed to bind {}:{}: {}", options.host, discovery_port, e);
+        exit(1);
+      }
+    };
+    match socket.local_addr() {
+      Ok(addr) => println!("Mock ROV answering discovery on udp://{}", addr),
+      Err(e) => {
+        eprintln!("Failed to read discovery socket address: {}", e);
+        exit(1);
+      }
+    }
+    tokio::spawn(answer_probes(socket, options.announcement(web_socket_port)));
+  }
+
+  let simulation = Arc::new(Mutex::new(Simulation::new(options.battery_drain)));
+  let options = Arc::new(options);
+  let scenario = Arc::new(scenario);
+  let mut connection = 0;
+
+  loop {
+    match listener.accept().await {
+      Ok((stream, addr)) => {
+        connection += 1;
+        let simulation = simulation.clone();
+        let options = options.clone();
+        let timeline = scenario.timeline(connection);
+        let acceptor = acceptor.clone();
+        tokio::spawn(async move {
+          handle_connection(
+            stream, addr, connection, simulation, options, timeline, acceptor,
+          )
+          .await;
+        });
+      }
+      Err(e) => {
+        eprintln!("Failed to accept connection: {}", e);
+      }
+    }
+  }
+}
@@ -0,0 +1,186 @@
+use manafish_protocol::models::discovery::Announcement;
+use manafish_protocol::models::handshake::{
+  Capability, Hello, MIN_PROTOCOL_VERSION, PROTOCOL_VERSION,
+};
+use manafish_protocol::models::unknown::UnknownFields;
+use std::path::PathBuf;
+use std::time::Duration;
+
+pub struct Options {
+  pub host: String,
+  pub port: u16,
+  pub name: String,
+  pub discovery_port: Option<u16>,
+  pub tls_certificate: Option<PathBuf>,
+  pub tls_key: Option<PathBuf>,
+  pub token: Option<String>,
+  pub telemetry_rate: f64,
+  pub status_rate: f64,
+  pub battery_drain: f32,
+  pub firmware_version: String,
+  pub scenario: Option<PathBuf>,
+  pub protocol_version: u32,
+  pub min_protocol_version: u32,
+  pub capabilities: Vec<Capability>,
+  pub legacy: bool,
+  pub validate: bool,
+}
+
+impl Default for Options {
+  fn default() -> Self {
+    Options {
+      host: "127.0.0.1".to_string(),
+      port: 9000,
+      name: "Mock ROV".to_string(),
+      discovery_port: None,
+      tls_certificate: None,
+      tls_key: None,
+      token: None,
+      telemetry_rate: 20.0,
+      status_rate: 1.0,
+      battery_drain: 1.0,
+      firmware_version: format!("mock-{}", env!("CARGO_PKG_VERSION")),
+      scenario: None,
+      protocol_version: PROTOCOL_VERSION,
+      min_protocol_version: MIN_PROTOCOL_VERSION,
+      capabilities: Capability::ALL.to_vec(),
+      legacy: false,
+      validate: false,
+    }
+  }
+}
+
+impl Options {
+  pub const USAGE: &'static str = "Usage: manafish-mock-rov [OPTIONS]
+
+Options:
+  --host <HOST>               Address to listen on [default: 127.0.0.1]
+  --port <PORT>               Port to listen on, 0 picks a free port [default: 9000]
+  --name <NAME>               Name announced to discovery probes [default: Mock ROV]
+  --discovery-port <PORT>     UDP port to answer discovery probes on, 0 picks a free port
+  --tls-certificate <FILE>    PEM certificate to serve wss:// with, needs --tls-key
+  --tls-key <FILE>            PEM private key of the certificate
+  --token <TOKEN>             Bearer token clients must send when connecting
+  --telemetry-rate <HZ>       Telemetry messages per second [default: 20]
+  --status-rate <HZ>          Status updates per second [default: 1]
+  --battery-drain <FACTOR>    How many times faster than normal the battery drains [default: 1]
+  --firmware-version <NAME>   Firmware version reported on connect
+  --scenario <FILE>           JSON timeline of faults to inject on each connection
+  --protocol-version <N>      Protocol version sent in the handshake
+  --min-protocol-version <N>  Oldest app protocol version accepted in the handshake
+  --capabilities <LIST>       Comma separated capabilities, e.g. depthHold,dshot [default: all]
+  --legacy                    Ignore the handshake like firmware that predates it
+  --validate                  Close connections that send messages violating the protocol schema
+  -h, --help                  Print this help";
+
+  pub fn from_args(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+    let mut options = Options::default();
+
+    while let Some(arg) = args.next() {
+      let mut value = |name: &str| {
+        args
+          .next()
+          .ok_or_else(|| format!("Missing value for {}", name))
+      };
+
+      match arg.as_str() {
+        "--host" => options.host = value("--host")?,
+        "--port" => options.port = parse(&value("--port")?, "--port")?,
+        "--name" => options.name = value("--name")?,
+        "--discovery-port" => {
+          options.discovery_port = Some(parse(&value("--discovery-port")?, "--discovery-port")?)
+        }
+        "--tls-certificate" => {
+          options.tls_certificate = Some(PathBuf::from(value("--tls-certificate")?))
+        }
+        "--tls-key" => options.tls_key = Some(PathBuf::from(value("--tls-key")?)),
+        "--token" => options.token = Some(value("--token")?),
+        "--telemetry-rate" => {
+          options.telemetry_rate = parse_rate(&value("--telemetry-rate")?, "--telemetry-rate")?
+        }
+        "--status-rate" => {
+          options.status_rate = parse_rate(&value("--status-rate")?, "--status-rate")?
+        }
+        "--battery-drain" => {
+          options.battery_drain = parse_rate(&value("--battery-drain")?, "--battery-drain")? as f32
+        }
+        "--firmware-version" => options.firmware_version = value("--firmware-version")?,
+        "--scenario" => options.scenario = Some(PathBuf::from(value("--scenario")?)),
+        "--protocol-version" => {
+          options.protocol_version = parse(&value("--protocol-version")?, "--protocol-version")?
+        }
+        "--min-protocol-version" => {
+          options.min_protocol_version =
+            parse(&value("--min-protocol-version")?, "--min-protocol-version")?
+        }
+        "--capabilities" => options.capabilities = parse_capabilities(&value("--capabilities")?)?,
+        "--legacy" => options.legacy = true,
+        "--validate" => options.validate = true,
+        "-h" | "--help" => {
+          println!("{}", Self::USAGE);
+          std::process::exit(0);
+        }
+        other => return Err(format!("Unknown argument: {}", other)),
+      }
+    }
+
+    if options.tls_certificate.is_some() != options.tls_key.is_some() {
+      return Err("--tls-certificate and --tls-key must be given together".to_string());
+    }
+
+    Ok(options)
+  }
+
+  pub fn hello(&self) -> Hello {
+    Hello {
+      protocol_version: self.protocol_version,
+      min_protocol_version: self.min_protocol_version,
+      software_version: self.firmware_version.clone(),
+      capabilities: self.capabilities.clone(),
+      unknown_fields: UnknownFields::default(),
+    }
+  }
+
+  pub fn announcement(&self, web_socket_port: u16) -> Announcement {
+    Announcement {
+      name: self.name.clone(),
+      firmware_version: self.firmware_version.clone(),
+      web_socket_port,
+      webrtc_signaling_api_port: 1984,
+    }
+  }
+
+  pub fn telemetry_interval(&self) -> Duration {
+    Duration::from_secs_f64(1.0 / self.telemetry_rate)
+  }
+
+  pub fn status_interval(&self) -> Duration {
+    Duration::from_secs_f64(1.0 / self.status_rate)
+  }
+}
+
+fn parse<T: std::str::FromStr>(value: &str, name: &str) -> Result<T, String> {
+  value
+    .parse()
+    .map_err(|_| format!("Invalid value for {}: {}", name, value))
+}
+
+fn parse_rate(value: &str, name: &str) -> Result<f64, String> {
+  let rate: f64 = parse(value, name)?;
+  if !rate.is_finite() || rate <= 0.0 {
+    return Err(format!("{} must be a positive number", name));
+  }
+  Ok(rate)
+}
+
+fn parse_capabilities(value: &str) -> Result<Vec<Capability>, String> {
+  value
+    .split(',')
+    .map(str::trim)
+    .filter(|name| !name.is_empty())
+    .map(|name| {
+      serde_json::from_value(serde_json::Value::String(name.to_string()))
+        .map_err(|_| format!("Invalid capability: {}", name))
+    })
+    .collect()
+}
@@ -0,0 +1,89 @@
+use serde::Deserialize;
+use std::fs;
+use std::path::Path;
+
+// A scenario is a timeline of faults, each injected `at` milliseconds after a
+// connection is accepted. Events without a `connection` number apply to every
+// connection, otherwise only to the nth (1-based) connection of the process.
+//
+// {
+//   "events": [
+//     { "at": 0, "fault": "latency", "millis": 250 },
+//     { "at": 0, "fault": "jitter", "millis": 100 },
+//     { "at": 5000, "connection": 1, "fault": "close", "code": 1011, "reason": "overheated" }
+//   ]
+// }
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct Scenario {
+  #[serde(default)]
+  pub events: Vec<ScenarioEvent>,
+}
+
+#[derive(Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ScenarioEvent {
+  pub at: u64,
+  #[serde(default)]
+  pub connection: Option<u32>,
+  #[serde(flatten)]
+  pub fault: Fault,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(tag = "fault", rename_all = "camelCase")]
+pub enum Fault {
+  // Extra one-way delay on everything the mock sends, including pongs.
+  Latency {
+    millis: u64,
+  },
+  // Random extra delay between zero and `millis` on top of the latency.
+  Jitter {
+    millis: u64,
+  },
+  // Fraction of protocol messages that are silently discarded.
+  DropFrames {
+    ratio: f64,
+  },
+  // Stops reading from the socket, so pings are never answered while
+  // telemetry keeps flowing.
+  UnansweredPings,
+  // Stops reading and writing but keeps the socket open.
+  HalfOpen,
+  MalformedJson,
+  UnknownMessage,
+  Close {
+    #[serde(default = "default_close_code")]
+    code: u16,
+    #[serde(default)]
+    reason: String,
+  },
+  // Drops the TCP connection without a close frame.
+  Disconnect,
+  // Clears latency, jitter, dropped frames and unanswered pings.
+  Recover,
+}
+
+fn default_close_code() -> u16 {
+  1000
+}
+
+impl Scenario {
+  pub fn from_file(path: &Path) -> Result<Self, String> {
+    let content = fs::read_to_string(path)
+      .map_err(|e| format!("Failed to read scenario {}: {}", path.display(), e))?;
+    serde_json::from_str(&content)
+      .map_err(|e| format!("Failed to parse scenario {}: {}", path.display(), e))
+  }
+
+  pub fn timeline(&self, connection: u32) -> Vec<ScenarioEvent> {
+    let mut events: Vec<ScenarioEvent> = self
+      .events
+      .iter()
+      .filter(|event| event.connection.map_or(true, |n| n == connection))
+      .cloned()
+      .collect();
+    events.sort_by_key(|event| event.at);
+    events
+  }
+}
@@ -0,0 +1,351 @@
+use crate::link::{lock as lock_link, LinkConditions, LinkStream};
+use crate::options::Options;
+use crate::scenario::{Fault, ScenarioEvent};
+use crate::simulation::{log, Simulation};
+use crate::tls::Authorize;
+use futures_util::{SinkExt, StreamExt};
+use manafish_protocol::message::{WebsocketEnvelope, WebsocketMessage};
+use manafish_protocol::models::toast::{Toast, ToastType};
+use manafish_protocol::models::unknown::UnknownFields;
+use manafish_protocol::schema::ProtocolValidator;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::net::TcpStream;
+use tokio::time::{interval, sleep_until, Instant, MissedTickBehavior};
+use tokio_rustls::TlsAcceptor;
+use tokio_tungstenite::tungstenite::protocol::frame::coding::CloseCode;
+use tokio_tungstenite::tungstenite::protocol::CloseFrame;
+use tokio_tungstenite::{accept_hdr_async, tungstenite::Message};
+
+const MALFORMED_JSON: &str = r#"{"type":"telemetry","payload":{"pitch":"#;
+const UNKNOWN_MESSAGE: &str = r#"{"type":"mockUnknownMessage","payload":{"value":42}}"#;
+
+pub async fn handle_connection(
+  stream: TcpStream,
+  addr: SocketAddr,
+  connection: u32,
+  simulation: Arc<Mutex<Simulation>>,
+  options: Arc<Options>,
+  timeline: Vec<ScenarioEvent>,
+  acceptor: Option<TlsAcceptor>,
+) {
+  let seed = SystemTime::now()
+    .duration_since(UNIX_EPOCH)
+    .unwrap_or_default()
+    .as_nanos() as u64
+    ^ u64::from(connection);
+  let conditions = Arc::new(Mutex::new(LinkConditions::new(seed)));
+  let stream = LinkStream::new(stream, conditions.clone());
+
+  match acceptor {
+    Some(acceptor) => match acceptor.accept(stream).await {
+      Ok(stream) => {
+        serve(
+          stream, addr, connection, simulation, options, timeline, conditions,
+        )
+        .await
+      }
+      Err(e) => eprintln!("TLS handshake with {} failed: {}", addr, e),
+    },
+    None => {
+      serve(
+        stream, addr, connection, simulation, options, timeline, conditions,
+      )
+      .await
+    }
+  }
+}
+
+async fn serve<S: AsyncRead + AsyncWrite + Unpin>(
+  stream: S,
+  addr: SocketAddr,
+  connection: u32,
+  simulation: Arc<Mutex<Simulation>>,
+  options: Arc<Options>,
+  timeline: Vec<ScenarioEvent>,
+  conditions: Arc<Mutex<LinkConditions>>,
+) {
+  let authorize = Authorize {
+    token: options.token.clone(),
+  };
+  let ws_stream = match accept_hdr_async(stream, authorize).await {
+    Ok(ws_stream) => ws_stream,
+    Err(e) => {
+      eprintln!("Websocket handshake with {} failed: {}", addr, e);
+      return;
+    }
+  };
+  eprintln!("Client connected from {} (connection {})", addr, connection);
+
+  let (mut write, mut read) = ws_stream.split();
+
+  let mut telemetry_timer = interval(options.telemetry_interval());
+  telemetry_timer.set_missed_tick_behavior(MissedTickBehavior::Skip);
+  let mut status_timer = interval(options.status_interval());
+  status_timer.set_missed_tick_behavior(MissedTickBehavior::Skip);
+
+  let validator = options.validate.then(ProtocolValidator::new);
+  let started = Instant::now();
+  let mut timeline = timeline.into_iter().peekable();
+  let mut reading = true;
+
+  let mut outgoing = Vec::new();
+  queue(
+    &mut outgoing,
+    &conditions,
+    [
+      WebsocketMessage::FirmwareVersion(options.firmware_version.clone()),
+      log(format!("Mock ROV accepted connection from {}", addr)),
+      WebsocketMessage::StatusUpdate(lock(&simulation).status()),
+    ],
+  );
+
+  loop {
+    for message in outgoing.drain(..) {
+      if let Err(e) = write.send(message).await {
+        eprintln!("Failed to send to {}: {}", addr, e);
+        return;
+      }
+    }
+
+    let next_fault_at = timeline
+      .peek()
+      .map(|event| started + Duration::from_millis(event.at));
+
+    tokio::select! {
+      _ = telemetry_timer.tick() => {
+        let mut simulation = lock(&simulation);
+        let mut messages = simulation.step(std::time::Instant::now());
+        messages.push(WebsocketMessage::Telemetry(simulation.telemetry()));
+        queue(&mut outgoing, &conditions, messages);
+      }
+      _ = status_timer.tick() => {
+        let status = WebsocketMessage::StatusUpdate(lock(&simulation).status());
+        queue(&mut outgoing, &conditions, [status]);
+      }
+      message = read.next(), if reading => match message {
+        Some(Ok(Message::Text(text))) => {
+          if let Some(Err(e)) = validator.as_ref().map(|validator| validator.validate(&text)) {
+            eprintln!("{} from {}", e, addr);
+            let frame = CloseFrame {
+              code: CloseCode::Policy,
+              reason: "Message violates the protocol schema".into(),
+            };
+            if let Err(e) = write.send(Message::Close(Some(frame))).await {
+              eprintln!("Failed to send close frame to {}: {}", addr, e);
+            }
+            return;
+          }
+          match serde_json::from_str::<WebsocketEnvelope>(&text) {
+            Ok(WebsocketEnvelope { id, message: WebsocketMessage::Hello(hello) }) => {
+              eprintln!("Hello from {}, protocol version {}", addr, hello.protocol_version);
+              if !options.legacy {
+                let reply = WebsocketEnvelope {
+                  id,
+                  message: WebsocketMessage::Hello(options.hello()),
+                };
+                queue(&mut outgoing, &conditions, [reply]);
+              }
+            }
+            Ok(mut envelope) => {
+              // Firmware from before the handshake knows nothing of request ids.
+              if options.legacy {
+                envelope.id = None;
+              }
+              queue(&mut outgoing, &conditions, respond(&simulation, envelope))
+            }
+            Err(e) => eprintln!("Failed to deserialize message from {}: {}", addr, e),
+          }
+        }
+        Some(Ok(Message::Close(_))) | None => break,
+        Some(Ok(_)) => {}
+        Some(Err(e)) => {
+          eprintln!("Websocket read error from {}: {}", addr, e);
+          break;
+        }
+      },
+      _ = sleep_until(next_fault_at.unwrap_or(started)), if next_fault_at.is_some() => {
+        let Some(event) = timeline.next() else {
+          continue;
+        };
+        eprintln!("Injecting {:?} on connection {}", event.fault, connection);
+
+        match event.fault {
+          Fault::Latency { millis } => {
+            lock_link(&conditions).latency = Duration::from_millis(millis);
+          }
+          Fault::Jitter { millis } => {
+            lock_link(&conditions).jitter = Duration::from_millis(millis);
+          }
+          Fault::DropFrames { ratio } => {
+            lock_link(&conditions).drop_ratio = ratio.clamp(0.0, 1.0);
+          }
+          Fault::UnansweredPings => reading = false,
+          Fault::HalfOpen => {
+            // Hold on to the socket without ever touching it again.
+            std::future::pending::<()>().await;
+          }
+          Fault::MalformedJson => outgoing.push(Message::Text(MALFORMED_JSON.into())),
+          Fault::UnknownMessage => outgoing.push(Message::Text(UNKNOWN_MESSAGE.into())),
+          Fault::Close { code, reason } => {
+            let frame = CloseFrame {
+              code: code.into(),
+              reason: reason.into(),
+            };
+            if let Err(e) = write.send(Message::Close(Some(frame))).await {
+              eprintln!("Failed to send close frame to {}: {}", addr, e);
+            }
+            return;
+          }
+          Fault::Disconnect => return,
+          Fault::Recover => {
+            lock_link(&conditions).reset();
+            reading = true;
+          }
+        }
+      }
+    }
+  }
+
+  eprintln!("Client {} disconnected", addr);
+}
+
+fn queue(
+  outgoing: &mut Vec<Message>,
+  conditions: &Mutex<LinkConditions>,
+  messages: impl IntoIterator<Item = impl Into<WebsocketEnvelope>>,
+) {
+  for message in messages {
+    let message = message.into();
+    if lock_link(conditions).should_drop() {
+      continue;
+    }
+    match serde_json::to_string(&message) {
+      Ok(text) => outgoing.push(Message::Text(text.into())),
+      Err(e) => eprintln!("Failed to serialize message: {}", e),
+    }
+  }
+}
+
+// Requests that carry an `id` always get exactly one reply with the same id,
+// an `ack` unless the message has a natural answer or is rejected.
+fn respond(simulation: &Mutex<Simulation>, envelope: WebsocketEnvelope) -> Vec<WebsocketEnvelope> {
+  let (reply, messages) = handle(&mut lock(simulation), envelope.message);
+  let reply = match envelope.id {
+    Some(id) => Some(WebsocketEnvelope {
+      id: Some(id),
+      message: reply.unwrap_or(WebsocketMessage::Ack),
+    }),
+    // Fire-and-forget messages never see a nack, only natural answers.
+    None => reply
+      .filter(|reply| !matches!(reply, WebsocketMessage::Nack(_)))
+      .map(WebsocketEnvelope::from),
+  };
+  reply
+    .into_iter()
+    .chain(messages.into_iter().map(WebsocketEnvelope::from))
+    .collect()
+}
+
+fn handle(
+  simulation: &mut Simulation,
+  message: WebsocketMessage,
+) -> (Option<WebsocketMessage>, Vec<WebsocketMessage>) {
+  match message {
+    WebsocketMessage::DirectionVector(direction_vector) => {
+      simulation.set_direction_vector(direction_vector);
+      (None, Vec::new())
+    }
+    WebsocketMessage::GetConfig => (
+      Some(WebsocketMessage::Config(simulation.config())),
+      Vec::new(),
+    ),
+    WebsocketMessage::SetConfig(config) => {
+      simulation.set_config(config);
+      (
+        Some(WebsocketMessage::Config(simulation.config())),
+        vec![toast(ToastType::Success, "ROV config set successfully")],
+      )
+    }
+    WebsocketMessage::StartThrusterTest(thruster) => {
+      (None, simulation.start_thruster_test(thruster))
+    }
+    WebsocketMessage::CancelThrusterTest(thruster) => {
+      rejectable(simulation.cancel_thruster_test(thruster))
+    }
+    WebsocketMessage::StartRegulatorAutoTuning => (None, simulation.start_regulator_auto_tuning()),
+    WebsocketMessage::CancelRegulatorAutoTuning => {
+      rejectable(simulation.cancel_regulator_auto_tuning())
+    }
+    WebsocketMessage::TogglePitchStabilization => {
+      simulation.toggle_pitch_stabilization();
+      (
+        None,
+        vec![WebsocketMessage::StatusUpdate(simulation.status())],
+      )
+    }
+    WebsocketMessage::ToggleRollStabilization => {
+      simulation.toggle_roll_stabilization();
+      (
+        None,
+        vec![WebsocketMessage::StatusUpdate(simulation.status())],
+      )
+    }
+    WebsocketMessage::ToggleDepthHold => {
+      simulation.toggle_depth_hold();
+      (
+        None,
+        vec![WebsocketMessage::StatusUpdate(simulation.status())],
+      )
+    }
+    WebsocketMessage::CustomAction(action) => (
+      None,
+      vec![log(format!("Received custom action: {}", action))],
+    ),
+    WebsocketMessage::FlashMicrocontrollerFirmware(variant) => {
+      let message = format!("Flashed {:?} microcontroller firmware", variant);
+      simulation.flash_firmware(variant);
+      (
+        None,
+        vec![
+          log(message),
+          WebsocketMessage::Config(simulation.config()),
+          toast(ToastType::Success, "Microcontroller firmware flashed"),
+        ],
+      )
+    }
+    other => {
+      eprintln!("Ignoring message the ROV does not handle: {:?}", other);
+      (
+        Some(WebsocketMessage::Nack("Unsupported request".to_string())),
+        Vec::new(),
+      )
+    }
+  }
+}
+
+fn rejectable(
+  result: Result<Vec<WebsocketMessage>, String>,
+) -> (Option<WebsocketMessage>, Vec<WebsocketMessage>) {
+  match result {
+    Ok(messages) => (None, messages),
+    Err(reason) => (Some(WebsocketMessage::Nack(reason)), Vec::new()),
+  }
+}
+
+fn toast(toast_type: ToastType, message: &str) -> WebsocketMessage {
+  WebsocketMessage::ShowToast(Toast {
+    id: None,
+    toast_type: Some(toast_type),
+    message: message.to_string(),
+    description: None,
+    cancel: None,
+    unknown_fields: UnknownFields::default(),
+  })
+}
+
+fn lock(simulation: &Mutex<Simulation>) -> MutexGuard<'_, Simulation> {
+  simulation.lock().unwrap_or_else(PoisonError::into_inner)
+}
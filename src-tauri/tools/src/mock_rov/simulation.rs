@@ -0,0 +1,389 @@
+use manafish_protocol::message::WebsocketMessage;
+use manafish_protocol::models::{
+  actions::DirectionVector,
+  log::{LogEntry, LogLevel, LogOrigin},
+  rov_config::{
+    DirectionCoefficients, FluidType, MicrocontrollerFirmwareVariant, Pid, Power, Regulator,
+    RegulatorSuggestions, RovConfig, ThrusterAllocation, ThrusterPinSetup, ThrusterTest,
+  },
+  rov_status::{RovStatus, SystemHealth},
+  rov_telemetry::RovTelemetry,
+  toast::{Toast, ToastCancel, ToastType},
+  unknown::UnknownFields,
+};
+use std::time::{Duration, Instant};
+
+const THRUSTER_TEST_DURATION: Duration = Duration::from_secs(5);
+const AUTO_TUNING_DURATION: Duration = Duration::from_secs(10);
+const MAX_RPM: f32 = 3000.0;
+const MAX_TILT_DEGREES: f32 = 30.0;
+const MAX_HEAVE_SPEED: f32 = 0.5;
+const THRUSTER_TEST_TOAST_ID: &str = "thruster-test";
+const AUTO_TUNING_TOAST_ID: &str = "regulator-auto-tuning";
+
+pub struct Simulation {
+  config: RovConfig,
+  status: RovStatus,
+  direction_vector: DirectionVector,
+  pitch: f32,
+  roll: f32,
+  depth: f32,
+  battery: f32,
+  battery_drain: f32,
+  started_at: Instant,
+  last_step: Instant,
+  thruster_test: Option<(ThrusterTest, Instant)>,
+  auto_tuning: Option<Instant>,
+}
+
+impl Simulation {
+  pub fn new(battery_drain: f32) -> Self {
+    let now = Instant::now();
+    Simulation {
+      config: default_rov_config(),
+      status: RovStatus {
+        pitch_stabilization: false,
+        roll_stabilization: false,
+        depth_hold: false,
+        battery_percentage: 100,
+        health: SystemHealth {
+          imu_ok: true,
+          pressure_sensor_ok: true,
+          microcontroller_ok: true,
+          unknown_fields: UnknownFields::default(),
+        },
+        unknown_fields: UnknownFields::default(),
+      },
+      direction_vector: [0.0; 8],
+      pitch: 0.0,
+      roll: 0.0,
+      depth: 0.0,
+      battery: 100.0,
+      battery_drain,
+      started_at: now,
+      last_step: now,
+      thruster_test: None,
+      auto_tuning: None,
+    }
+  }
+
+  pub fn config(&self) -> RovConfig {
+    self.config.clone()
+  }
+
+  pub fn set_config(&mut self, config: RovConfig) {
+    self.config = config;
+  }
+
+  pub fn status(&self) -> RovStatus {
+    self.status.clone()
+  }
+
+  pub fn set_direction_vector(&mut self, direction_vector: DirectionVector) {
+    self.direction_vector = direction_vector;
+  }
+
+  pub fn toggle_pitch_stabilization(&mut self) {
+    self.status.pitch_stabilization = !self.status.pitch_stabilization;
+  }
+
+  pub fn toggle_roll_stabilization(&mut self) {
+    self.status.roll_stabilization = !self.status.roll_stabilization;
+  }
+
+  pub fn toggle_depth_hold(&mut self) {
+    self.status.depth_hold = !self.status.depth_hold;
+  }
+
+  pub fn flash_firmware(&mut self, variant: MicrocontrollerFirmwareVariant) {
+    self.config.microcontroller_firmware_variant = variant;
+  }
+
+  pub fn start_thruster_test(&mut self, thruster: ThrusterTest) -> Vec<WebsocketMessage> {
+    self.thruster_test = Some((thruster, Instant::now()));
+    vec![toast(
+      THRUSTER_TEST_TOAST_ID,
+      ToastType::Loading,
+      format!("Testing thruster {}", thruster),
+      Some(ToastCancel::CancelThrusterTest(thruster)),
+    )]
+  }
+
+  pub fn cancel_thruster_test(
+    &mut self,
+    thruster: ThrusterTest,
+  ) -> Result<Vec<WebsocketMessage>, String> {
+    match self.thruster_test {
+      Some((active, _)) if active == thruster => {
+        self.thruster_test = None;
+        Ok(vec![toast(
+          THRUSTER_TEST_TOAST_ID,
+          ToastType::Info,
+          format!("Thruster {} test cancelled", thruster),
+          None,
+        )])
+      }
+      _ => Err(format!("Thruster {} is not being tested", thruster)),
+    }
+  }
+
+  pub fn start_regulator_auto_tuning(&mut self) -> Vec<WebsocketMessage> {
+    self.auto_tuning = Some(Instant::now());
+    vec![toast(
+      AUTO_TUNING_TOAST_ID,
+      ToastType::Loading,
+      "Auto-tuning regulator".to_string(),
+      Some(ToastCancel::CancelRegulatorAutoTuning),
+    )]
+  }
+
+  pub fn cancel_regulator_auto_tuning(&mut self) -> Result<Vec<WebsocketMessage>, String> {
+    if self.auto_tuning.take().is_none() {
+      return Err("Regulator auto-tuning is not running".to_string());
+    }
+    Ok(vec![toast(
+      AUTO_TUNING_TOAST_ID,
+      ToastType::Info,
+      "Regulator auto-tuning cancelled".to_string(),
+      None,
+    )])
+  }
+
+  pub fn step(&mut self, now: Instant) -> Vec<WebsocketMessage> {
+    let dt = now.saturating_duration_since(self.last_step).as_secs_f32();
+    self.last_step = now;
+
+    let [_, _, heave, pitch, _, roll, _, _] = self.direction_vector;
+    let desired_pitch = self.desired_pitch();
+    let desired_roll = self.desired_roll();
+    let response = (dt * 2.0).min(1.0);
+    self.pitch += (desired_pitch - self.pitch) * response;
+    self.roll += (desired_roll - self.roll) * response;
+    if !self.status.pitch_stabilization {
+      self.pitch += pitch * MAX_TILT_DEGREES * dt;
+    }
+    if !self.status.roll_stabilization {
+      self.roll += roll * MAX_TILT_DEGREES * dt;
+    }
+    self.pitch = self.pitch.clamp(-90.0, 90.0);
+    self.roll = self.roll.clamp(-180.0, 180.0);
+
+    if !self.status.depth_hold || heave != 0.0 {
+      self.depth = (self.depth - heave * MAX_HEAVE_SPEED * dt).max(0.0);
+    }
+
+    let drain_per_second = (0.005 + 0.05 * self.load()) * self.battery_drain;
+    self.battery = (self.battery - drain_per_second * dt).max(0.0);
+    self.status.battery_percentage = self.battery.round() as u8;
+
+    let mut messages = Vec::new();
+
+    if let Some((thruster, started)) = self.thruster_test {
+      if now.saturating_duration_since(started) >= THRUSTER_TEST_DURATION {
+        self.thruster_test = None;
+        messages.push(toast(
+          THRUSTER_TEST_TOAST_ID,
+          ToastType::Success,
+          format!("Thruster {} test completed", thruster),
+          None,
+        ));
+      }
+    }
+
+    if let Some(started) = self.auto_tuning {
+      if now.saturating_duration_since(started) >= AUTO_TUNING_DURATION {
+        self.auto_tuning = None;
+        messages.push(WebsocketMessage::RegulatorSuggestions(
+          self.regulator_suggestions(),
+        ));
+        messages.push(toast(
+          AUTO_TUNING_TOAST_ID,
+          ToastType::Success,
+          "Regulator auto-tuning completed".to_string(),
+          None,
+        ));
+      }
+    }
+
+    messages
+  }
+
+  pub fn telemetry(&self) -> RovTelemetry {
+    let t = self.started_at.elapsed().as_secs_f32();
+    let thruster_rpms = self.thruster_rpms();
+    let load = self.load();
+
+    RovTelemetry {
+      pitch: self.pitch + 0.4 * (t * 1.3).sin(),
+      roll: self.roll + 0.3 * (t * 0.9).cos(),
+      desired_pitch: self.desired_pitch(),
+      desired_roll: self.desired_roll(),
+      depth: self.depth + 0.02 * (t * 0.7).sin(),
+      water_temperature: Some(12.0 - self.depth * 0.05 + 0.05 * (t * 0.05).sin()),
+      electronics_temperature: Some(35.0 + 15.0 * load + 0.2 * (t * 0.1).sin()),
+      thruster_rpms,
+      work_indicator_percentage: (load * 100.0).round() as u8,
+      unknown_fields: UnknownFields::default(),
+    }
+  }
+
+  fn desired_pitch(&self) -> f32 {
+    if self.status.pitch_stabilization {
+      self.direction_vector[3] * MAX_TILT_DEGREES
+    } else {
+      self.pitch
+    }
+  }
+
+  fn desired_roll(&self) -> f32 {
+    if self.status.roll_stabilization {
+      self.direction_vector[5] * MAX_TILT_DEGREES
+    } else {
+      self.roll
+    }
+  }
+
+  fn thruster_rpms(&self) -> [f32; 8] {
+    let mut rpms = [0.0; 8];
+
+    if let Some((thruster, _)) = self.thruster_test {
+      if let Some(rpm) = rpms.get_mut(thruster as usize) {
+        *rpm = 0.2 * MAX_RPM;
+      }
+      return rpms;
+    }
+
+    let max_power = self.config.power.user_max_power.clamp(0.0, 100.0) / 100.0;
+    for (rpm, (row, direction)) in rpms.iter_mut().zip(
+      self
+        .config
+        .thruster_allocation
+        .iter()
+        .zip(self.config.thruster_pin_setup.spin_directions),
+    ) {
+      let thrust: f32 = row
+        .iter()
+        .zip(self.direction_vector)
+        .map(|(coefficient, input)| coefficient * input)
+        .sum();
+      *rpm = thrust.clamp(-1.0, 1.0) * max_power * MAX_RPM * f32::from(direction);
+    }
+    rpms
+  }
+
+  fn load(&self) -> f32 {
+    self
+      .thruster_rpms()
+      .iter()
+      .map(|rpm| rpm.abs() / MAX_RPM)
+      .sum::<f32>()
+      / 8.0
+  }
+
+  fn regulator_suggestions(&self) -> RegulatorSuggestions {
+    let regulator = &self.config.regulator;
+    RegulatorSuggestions {
+      pitch: tuned(&regulator.pitch),
+      roll: tuned(&regulator.roll),
+      depth: tuned(&regulator.depth),
+      unknown_fields: UnknownFields::default(),
+    }
+  }
+}
+
+fn tuned(pid: &Pid) -> Pid {
+  Pid {
+    kp: pid.kp * 1.1,
+    ki: pid.ki * 0.9,
+    kd: pid.kd * 1.05,
+    unknown_fields: pid.unknown_fields.clone(),
+  }
+}
+
+pub fn log(message: String) -> WebsocketMessage {
+  WebsocketMessage::LogMessage(LogEntry {
+    level: LogLevel::Info,
+    origin: LogOrigin::Firmware,
+    message,
+    unknown_fields: UnknownFields::default(),
+  })
+}
+
+fn toast(
+  id: &str,
+  toast_type: ToastType,
+  message: String,
+  cancel: Option<ToastCancel>,
+) -> WebsocketMessage {
+  WebsocketMessage::ShowToast(Toast {
+    id: Some(id.to_string()),
+    toast_type: Some(toast_type),
+    message,
+    description: None,
+    cancel,
+    unknown_fields: UnknownFields::default(),
+  })
+}
+
+fn default_rov_config() -> RovConfig {
+  let thruster_allocation: ThrusterAllocation = [
+    [1.0, 1.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0],
+    [1.0, -1.0, 0.0, 0.0, -1.0, 0.0, 0.0, 0.0],
+    [1.0, -1.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0],
+    [1.0, 1.0, 0.0, 0.0, -1.0, 0.0, 0.0, 0.0],
+    [0.0, 0.0, 1.0, 1.0, 0.0, 1.0, 0.0, 0.0],
+    [0.0, 0.0, 1.0, 1.0, 0.0, -1.0, 0.0, 0.0],
+    [0.0, 0.0, 1.0, -1.0, 0.0, 1.0, 0.0, 0.0],
+    [0.0, 0.0, 1.0, -1.0, 0.0, -1.0, 0.0, 0.0],
+  ];
+
+  RovConfig {
+    microcontroller_firmware_variant: MicrocontrollerFirmwareVariant::Dshot,
+    fluid_type: FluidType::Saltwater,
+    thruster_pin_setup: ThrusterPinSetup {
+      identifiers: [0, 1, 2, 3, 4, 5, 6, 7],
+      spin_directions: [1, -1, 1, -1, 1, -1, 1, -1],
+      unknown_fields: UnknownFields::default(),
+    },
+    thruster_allocation,
+    regulator: Regulator {
+      turn_speed: 40,
+      pitch: Pid {
+        kp: 5.0,
+        ki: 0.5,
+        kd: 1.0,
+        unknown_fields: UnknownFields::default(),
+      },
+      roll: Pid {
+        kp: 5.0,
+        ki: 0.5,
+        kd: 1.0,
+        unknown_fields: UnknownFields::default(),
+      },
+      depth: Pid {
+        kp: 10.0,
+        ki: 1.0,
+        kd: 2.0,
+        unknown_fields: UnknownFields::default(),
+      },
+      unknown_fields: UnknownFields::default(),
+    },
+    direction_coefficients: DirectionCoefficients {
+      surge: 1.0,
+      sway: 1.0,
+      heave: 1.0,
+      pitch: 1.0,
+      yaw: 1.0,
+      roll: 1.0,
+      unknown_fields: UnknownFields::default(),
+    },
+    power: Power {
+      user_max_power: 30.0,
+      regulator_max_power: 30.0,
+      battery_min_voltage: 9.8,
+      battery_max_voltage: 12.6,
+      unknown_fields: UnknownFields::default(),
+    },
+    unknown_fields: UnknownFields::default(),
+  }
+}
@@ -0,0 +1,53 @@
+use rustls::crypto::ring;
+use rustls::pki_types::pem::PemObject;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer};
+use rustls::ServerConfig;
+use std::path::Path;
+use std::sync::Arc;
+use tokio_rustls::TlsAcceptor;
+use tokio_tungstenite::tungstenite::handshake::server::{
+  Callback, ErrorResponse, Request, Response,
+};
+use tokio_tungstenite::tungstenite::http::{header::AUTHORIZATION, StatusCode};
+
+pub fn acceptor(certificate: &Path, key: &Path) -> Result<TlsAcceptor, String> {
+  let certificates = CertificateDer::pem_file_iter(certificate)
+    .and_then(|certificates| certificates.collect::<Result<Vec<_>, _>>())
+    .map_err(|e| format!("Failed to read {}: {}", certificate.display(), e))?;
+  let key = PrivateKeyDer::from_pem_file(key)
+    .map_err(|e| format!("Failed to read {}: {}", key.display(), e))?;
+
+  let config = ServerConfig::builder_with_provider(Arc::new(ring::default_provider()))
+    .with_safe_default_protocol_versions()
+    .and_then(|builder| {
+      builder
+        .with_no_client_auth()
+        .with_single_cert(certificates, key)
+    })
+    .map_err(|e| format!("Invalid TLS certificate: {}", e))?;
+  Ok(TlsAcceptor::from(Arc::new(config)))
+}
+
+// Refuses the websocket upgrade unless it carries the expected bearer token.
+pub struct Authorize {
+  pub token: Option<String>,
+}
+
+impl Callback for Authorize {
+  fn on_request(self, request: &Request, response: Response) -> Result<Response, ErrorResponse> {
+    let Some(token) = self.token else {
+      return Ok(response);
+    };
+    let presented = request
+      .headers()
+      .get(AUTHORIZATION)
+      .and_then(|value| value.to_str().ok());
+    if presented == Some(format!("Bearer {}", token).as_str()) {
+      return Ok(response);
+    }
+
+    let mut refusal = ErrorResponse::new(Some("Invalid token".to_string()));
+    *refusal.status_mut() = StatusCode::UNAUTHORIZED;
+    Err(refusal)
+  }
+}
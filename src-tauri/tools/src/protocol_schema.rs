@@ -0,0 +1,16 @@
+use manafish_protocol::schema::{write_schema_bundle, PROTOCOL_SCHEMA_PATH};
+use std::path::PathBuf;
+use std::process::exit;
+
+// Writes to `docs/protocol-schema`, or to the directory given as the only argument.
+fn main() {
+  let directory = std::env::args()
+    .nth(1)
+    .map(PathBuf::from)
+    .unwrap_or_else(|| PathBuf::from(PROTOCOL_SCHEMA_PATH));
+  if let Err(e) = write_schema_bundle(&directory) {
+    eprintln!("Failed to write {}: {}", directory.display(), e);
+    exit(1);
+  }
+  println!("Wrote {}", directory.display());
+}
@@ -27,13 +27,17 @@ const TemperatureIndicator = memo(function TemperatureIndicator() {
           <CircuitBoardIcon className='h-4 w-4' />
           <ThermometerIcon className='absolute top-0 -left-2.5 h-4 w-4' />
         </span>
-        <span className='text-xs'>{electronicsTemperature.toFixed(1)}°C</span>
+        <span className='text-xs'>
+          {electronicsTemperature?.toFixed(1) ?? '--'}°C
+        </span>
       </div>
       <div className='flex w-17 items-center gap-1 drop-shadow-[0_1.2px_1.2px_rgba(0,0,0,0.8)]'>
         <span className='relative'>
           <WavesIcon className='h-4 w-4' />
           <ThermometerIcon className='absolute top-0 -left-2.5 h-4 w-4' />
-          <span className='text-xs'>{waterTemperature.toFixed(1)}°C</span>
+          <span className='text-xs'>
+            {waterTemperature?.toFixed(1) ?? '--'}°C
+          </span>
         </span>
       </div>
     </>
@@ -1,19 +1,16 @@
-import { listen } from '@tauri-apps/api/event';
 import { useEffect } from 'react';
 
 import { logWarn } from '@/lib/log';
+import { listenPilot } from '@/lib/vehicleEvent';
 
-import {
-  type ConnectionStatus,
-  connectionStatusStore,
-} from '@/stores/connectionStatus';
+import { connectionStatusStore } from '@/stores/connectionStatus';
 
 function useConnectionStatusListener() {
   useEffect(() => {
     let unlisten: (() => void) | undefined;
     void (async () => {
       try {
-        unlisten = await listen<ConnectionStatus>(
+        unlisten = await listenPilot(
           'rov_connection_status_updated',
           ({ payload }) => {
             connectionStatusStore.setState(() => payload);
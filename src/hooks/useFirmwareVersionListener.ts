@@ -1,9 +1,9 @@
-import { listen } from '@tauri-apps/api/event';
 import { useEffect } from 'react';
 
 import { toast } from '@/components/ui/Toaster';
 
 import { logError } from '@/lib/log';
+import { listenPilot } from '@/lib/vehicleEvent';
 
 import { firmwareVersionStore } from '@/stores/firmwareVersion';
 
@@ -13,7 +13,7 @@ function useFirmwareVersionListener() {
 
     void (async () => {
       try {
-        unlisten = await listen<string>(
+        unlisten = await listenPilot(
           'firmware_version_recieved',
           ({ payload }) => {
             firmwareVersionStore.setState(() => payload);
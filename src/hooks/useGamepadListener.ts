@@ -1,34 +1,11 @@
 import { invoke } from '@tauri-apps/api/core';
-import { listen } from '@tauri-apps/api/event';
 import { useEffect } from 'react';
 
 import { toast } from '@/components/ui/Toaster';
 
+import { type GamepadData, listenEvent } from '@/lib/bindings';
 import { logError } from '@/lib/log';
 
-type GamepadEventType =
-  | 'connected'
-  | 'disconnected'
-  | 'buttonPressed'
-  | 'buttonReleased'
-  | 'buttonChanged'
-  | 'axisChanged'
-  | 'dropped';
-
-type GamepadData = {
-  id: number;
-  uuid: string;
-  connected: boolean;
-  vibration: boolean;
-  event: GamepadEventType;
-  timestamp: number;
-  name: string;
-  buttons: ReadonlyArray<number>;
-  axes: ReadonlyArray<number>;
-  mapping: string;
-  powerInfo: string;
-};
-
 const gamepads: (Gamepad | null)[] = [null, null, null, null];
 
 function getGamepads() {
@@ -81,10 +58,7 @@ function useGamepadListener() {
         toast.error('Failed to start gamepad stream');
       });
       try {
-        unlisten = await listen<GamepadData>(
-          'gamepad_event',
-          handleGamepadEvent,
-        );
+        unlisten = await listenEvent('gamepad_event', handleGamepadEvent);
       } catch (error) {
         logError('Failed to listen for gamepad events:', error);
         toast.error('Failed to listen for gamepad events');
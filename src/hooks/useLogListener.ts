@@ -1,17 +1,17 @@
-import { listen } from '@tauri-apps/api/event';
 import { useEffect } from 'react';
 
 import { toast } from '@/components/ui/Toaster';
 
-import { type LogEntry, createLogRecord, logError } from '@/lib/log';
+import { listenEvent } from '@/lib/bindings';
+import { createLogRecord, logError } from '@/lib/log';
 
 function useLogListener() {
   useEffect(() => {
     let unlisten: (() => void) | undefined;
     void (async () => {
       try {
-        unlisten = await listen<LogEntry>('log_message', ({ payload }) => {
-          void createLogRecord(payload);
+        unlisten = await listenEvent('log_message', ({ payload }) => {
+          void createLogRecord(payload.payload);
         });
       } catch (error) {
         logError('Failed to listen to log messages:', error);
@@ -1,13 +1,10 @@
-import { listen } from '@tauri-apps/api/event';
 import { useEffect, useState } from 'react';
 
 import { toast } from '@/components/ui/Toaster';
 
+import { type RegulatorSuggestions } from '@/lib/bindings';
 import { logError } from '@/lib/log';
-
-import { type Regulator } from '@/stores/rovConfig';
-
-type RegulatorSuggestions = Omit<Regulator, 'turnSpeed'>;
+import { listenPilot } from '@/lib/vehicleEvent';
 
 function useRegulatorSuggestionsListener() {
   const [suggestions, setSuggestions] = useState<RegulatorSuggestions | null>(
@@ -18,7 +15,7 @@ function useRegulatorSuggestionsListener() {
     let unlisten: (() => void) | undefined;
     void (async () => {
       try {
-        unlisten = await listen<RegulatorSuggestions>(
+        unlisten = await listenPilot(
           'regulator_suggestions_received',
           ({payload}) => {
             setSuggestions(payload);
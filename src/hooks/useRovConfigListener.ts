@@ -1,11 +1,11 @@
-import { listen } from '@tauri-apps/api/event';
 import { useEffect } from 'react';
 
 import { toast } from '@/components/ui/Toaster';
 
 import { logError } from '@/lib/log';
+import { listenPilot } from '@/lib/vehicleEvent';
 
-import { type RovConfig, rovConfigStore } from '@/stores/rovConfig';
+import { rovConfigStore } from '@/stores/rovConfig';
 
 function useRovConfigListener() {
   useEffect(() => {
@@ -13,7 +13,7 @@ function useRovConfigListener() {
 
     void (async () => {
       try {
-        unlisten = await listen<RovConfig>(
+        unlisten = await listenPilot(
           'rov_config_received',
           ({ payload }) => {
             rovConfigStore.setState(() => payload);
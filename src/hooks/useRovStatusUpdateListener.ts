@@ -1,16 +1,16 @@
-import { listen } from '@tauri-apps/api/event';
 import { useEffect } from 'react';
 
 import { logWarn } from '@/lib/log';
+import { listenPilot } from '@/lib/vehicleEvent';
 
-import { type RovStatus, rovStatusStore } from '@/stores/rovStatus';
+import { rovStatusStore } from '@/stores/rovStatus';
 
 function useRovStatusUpdateListener() {
   useEffect(() => {
     let unlisten: (() => void) | undefined;
     void (async () => {
       try {
-        unlisten = await listen<RovStatus>('rov_status_update', ({payload}) => {
+        unlisten = await listenPilot('rov_status_update', ({payload}) => {
           rovStatusStore.setState(() => payload);
         });
       } catch (error) {
@@ -1,16 +1,16 @@
-import { listen } from '@tauri-apps/api/event';
 import { useEffect } from 'react';
 
 import { logWarn } from '@/lib/log';
+import { listenPilot } from '@/lib/vehicleEvent';
 
-import { type RovTelemetry, rovTelemetryStore } from '@/stores/rovTelemetry';
+import { rovTelemetryStore } from '@/stores/rovTelemetry';
 
 function useRovTelemetryListener() {
   useEffect(() => {
     let unlisten: (() => void) | undefined;
     void (async () => {
       try {
-        unlisten = await listen<RovTelemetry>('rov_telemetry', ({payload}) => {
+        unlisten = await listenPilot('rov_telemetry', ({payload}) => {
           rovTelemetryStore.setState(() => payload);
         });
       } catch (error) {
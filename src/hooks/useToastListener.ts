@@ -5,6 +5,7 @@ import { useEffect } from 'react';
 import { toast } from '@/components/ui/Toaster';
 
 import { logError } from '@/lib/log';
+import { type VehicleEvent } from '@/lib/vehicleEvent';
 
 function camelToSnake(str: string): string {
   return str.replace(/[A-Z]/g, (letter) => `_${letter.toLowerCase()}`);
@@ -30,7 +31,8 @@ function useToastListener() {
     let unlisten: (() => void) | undefined;
     void (async () => {
       try {
-        unlisten = await listen<Toast>('show_toast', ({ payload }) => {
+        unlisten = await listen<VehicleEvent<Toast>>('show_toast', (event) => {
+          const { vehicleId, payload } = event.payload;
           const typeMethodMap = {
             success: toast.success,
             info: toast.info,
@@ -55,7 +57,10 @@ function useToastListener() {
                               if (payload.cancel?.type) {
                                 await invoke(
                                   camelToSnake(payload.cancel.type),
-                                  { payload: payload.cancel.payload },
+                                  {
+                                    payload: payload.cancel.payload,
+                                    vehicle: vehicleId,
+                                  },
                                 ).catch((error) => {
                                   logError(
                                     'Failed to invoke cancel command:',
@@ -0,0 +1,272 @@
+// Generated from the Rust models by `bun run bindings`, do not edit.
+import { invoke } from '@tauri-apps/api/core';
+import { type EventCallback, listen } from '@tauri-apps/api/event';
+
+export type VehicleId = string;
+
+export type DirectionVector = [number, number, number, number, number, number, number, number];
+
+export type CustomAction = string;
+
+export type ThrusterAllocation = [[number, number, number, number, number, number, number, number], [number, number, number, number, number, number, number, number], [number, number, number, number, number, number, number, number], [number, number, number, number, number, number, number, number], [number, number, number, number, number, number, number, number], [number, number, number, number, number, number, number, number], [number, number, number, number, number, number, number, number], [number, number, number, number, number, number, number, number]];
+
+export type ThrusterTest = number;
+
+export type FirmwareVersion = string;
+
+export type VehicleEvent<T> = { vehicleId: string | null, pilot: boolean, replay: boolean, payload: T, };
+
+export type VehicleInfo = { id: string, profile: string | null, ipAddress: string, webSocketPort: number, pilot: boolean, connection: ConnectionState, };
+
+export type ConnectionPhase = "disconnected" | "connecting" | "handshaking" | "connected" | "degraded" | "reconnecting";
+
+export type ConnectionState = { phase: ConnectionPhase, reason: string, attempt: number, retryInMs: number | null, };
+
+export type ConnectionStatus = { isConnected: boolean, delay: number | null, };
+
+export type Capability = "pitchStabilization" | "rollStabilization" | "depthHold" | "dshot" | "thrusterTest" | "regulatorAutoTuning" | "firmwareFlashing" | "unknown";
+
+export type Handshake = { protocolVersion: number | null, firmwareVersion: string | null, capabilities: Array<Capability>, };
+
+export type RttBucket = { upToMs: number | null, count: number, };
+
+export type LinkQuality = { rttHistogram: Array<RttBucket>, rttLastMs: number | null, rttMinMs: number | null, rttMeanMs: number | null, rttMaxMs: number | null, jitterMs: number | null, missedPongRatio: number, telemetryRate: number, bytesInPerSecond: number, bytesOutPerSecond: number, reconnectCount: number, };
+
+export type OutboundPriority = "emergency" | "control" | "configuration";
+
+export type DeliveryPolicy = { "type": "dropWhenOffline" } | { "type": "holdUntilReconnect" } | { "type": "expireAfter", seconds: number, };
+
+export type QueuedMessage = { id: number, messageType: string, priority: OutboundPriority, policy: DeliveryPolicy, queuedForMs: number, };
+
+export type DirectionVectorStats = { received: number, sent: number, coalesced: number, };
+
+export type MicrocontrollerFirmwareVariant = "pwm" | "dshot";
+
+export type FluidType = "saltwater" | "freshwater";
+
+export type ThrusterPinSetup = { identifiers: [number, number, number, number, number, number, number, number], spinDirections: [number, number, number, number, number, number, number, number], };
+
+export type Pid = { kp: number, ki: number, kd: number, };
+
+export type Regulator = { turnSpeed: number, pitch: Pid, roll: Pid, depth: Pid, };
+
+export type RegulatorSuggestions = { pitch: Pid, roll: Pid, depth: Pid, };
+
+export type DirectionCoefficients = { surge: number, sway: number, heave: number, pitch: number, yaw: number, roll: number, };
+
+export type Power = { userMaxPower: number, regulatorMaxPower: number, batteryMinVoltage: number, batteryMaxVoltage: number, };
+
+export type RovConfig = { microcontrollerFirmwareVariant: MicrocontrollerFirmwareVariant, fluidType: FluidType, thrusterPinSetup: ThrusterPinSetup, thrusterAllocation: [[number, number, number, number, number, number, number, number], [number, number, number, number, number, number, number, number], [number, number, number, number, number, number, number, number], [number, number, number, number, number, number, number, number], [number, number, number, number, number, number, number, number], [number, number, number, number, number, number, number, number], [number, number, number, number, number, number, number, number], [number, number, number, number, number, number, number, number]], regulator: Regulator, directionCoefficients: DirectionCoefficients, power: Power, };
+
+export type SystemHealth = { imuOk: boolean, pressureSensorOk: boolean, microcontrollerOk: boolean, };
+
+export type RovStatus = { pitchStabilization: boolean, rollStabilization: boolean, depthHold: boolean, batteryPercentage: number, health: SystemHealth, };
+
+export type RovTelemetry = { pitch: number, roll: number, desiredPitch: number, desiredRoll: number, depth: number, waterTemperature?: number, electronicsTemperature?: number, thrusterRpms: [number, number, number, number, number, number, number, number], workIndicatorPercentage: number, };
+
+export type TelemetryUiStats = { received: number, emitted: number, dropped: number, };
+
+export type TelemetryField = "pitch" | "roll" | "desiredPitch" | "desiredRoll" | "depth" | "waterTemperature" | "electronicsTemperature" | "thrusterRpm1" | "thrusterRpm2" | "thrusterRpm3" | "thrusterRpm4" | "thrusterRpm5" | "thrusterRpm6" | "thrusterRpm7" | "thrusterRpm8" | "workIndicatorPercentage" | "batteryPercentage";
+
+export type TelemetryBucket = { fromMs: number, toMs: number, min: number, max: number, mean: number, count: number, };
+
+export type TelemetrySeries = { field: TelemetryField, buckets: Array<TelemetryBucket>, };
+
+export type HealthFlag = "imuOk" | "pressureSensorOk" | "microcontrollerOk";
+
+export type BatteryForecast = "remainingDiveMinutes" | "minimumVoltageMinutes";
+
+export type AlarmField = TelemetryField | HealthFlag | BatteryForecast;
+
+export type AlarmCondition = "above" | "below" | "risingFaster" | "fallingFaster";
+
+export type AlarmSeverity = "info" | "warning" | "critical";
+
+export type AlarmRule = { name: string, field: AlarmField, condition: AlarmCondition, threshold: number, durationMs: number, hysteresis: number, rateWindowMs: number, severity: AlarmSeverity, };
+
+export type Alarm = { id: number, vehicleId: string, rule: string, severity: AlarmSeverity, message: string, value: number, raisedMs: number, clearedMs: number | null, acknowledgedMs: number | null, };
+
+export type BatteryEstimate = { batteryPercentage: number, voltage: number | null, load: number, drainPerMinute: number | null, minimumVoltageInMs: number | null, returnToSurfaceMs: number, remainingDiveMs: number | null, };
+
+export type LogLevel = "info" | "warn" | "error";
+
+export type LogOrigin = "firmware" | "backend" | "frontend";
+
+export type LogEntry = { level: LogLevel, origin: LogOrigin, message: string, };
+
+export type ToastType = "success" | "info" | "warn" | "error" | "loading";
+
+export type ToastCancel = { "type": "cancelThrusterTest", "payload": number } | { "type": "cancelRegulatorAutoTuning" };
+
+export type Toast = { id: string | null, toastType: ToastType | null, message: string, description: string | null, cancel: ToastCancel | null, };
+
+export type UnknownMessage = { messageType: string, raw: unknown, };
+
+export type DiscoveredRov = { name: string, ipAddress: string, firmwareVersion: string, webSocketPort: number, webrtcSignalingApiPort: number, };
+
+export type GamepadEventType = "connected" | "disconnected" | "buttonPressed" | "buttonReleased" | "buttonChanged" | "axisChanged" | "dropped";
+
+export type GamepadData = { id: number, uuid: string, connected: boolean, vibration: boolean, event: GamepadEventType, timestamp: number, name: string, buttons: Array<number>, axes: Array<number>, mapping: string, powerInfo: string, };
+
+export type KeyboardBindings = { surgeForward: string, surgeBackward: string, swayLeft: string, swayRight: string, heaveUp: string, heaveDown: string, yawLeft: string, yawRight: string, pitchUp: string, pitchDown: string, rollLeft: string, rollRight: string, action1Positive: string, action1Negative: string, action2Positive: string, action2Negative: string, pitchStabilization: string, rollStabilization: string, depthHold: string, record: string, };
+
+export type ControlSource = "leftStick" | "rightStick" | "dPad" | "faceButtons";
+
+export type AttitudeIndicator = "scientific" | "dimensional3D" | "disabled";
+
+export type GamepadBindings = { surgeSway: ControlSource, heaveUp: string, heaveDown: string, pitchYaw: ControlSource, rollLeft: string, rollRight: string, action1Positive: string, action1Negative: string, action2Positive: string, action2Negative: string, pitchStabilization: string, rollStabilization: string, depthHold: string, record: string, };
+
+export type RovProfile = { name: string, ipAddress: string, webSocketPort: number, webrtcSignalingApiPort: number, webrtcSignalingApiPath: string, useTls: boolean, certificateFingerprint: string | null, authToken: string | null, expectedFirmwareVariant: MicrocontrollerFirmwareVariant | null, lastKnownRovConfig: RovConfig | null, notes: string, };
+
+export type TelemetryUiMode = "latest" | "average" | "batch";
+
+export type Config = { autoUpdate: boolean, attitudeIndicator: AttitudeIndicator, workIndicator: boolean, thrusterRpmOverlay: boolean, videoDirectory: string, ipAddress: string, webrtcSignalingApiPort: number, webrtcSignalingApiPath: string, webSocketPort: number, useTls: boolean, certificateFingerprint: string | null, authToken: string | null, rovProfiles: Array<RovProfile>, activeRovProfile: string | null, controlWatchdogTimeoutMs: number, directionVectorRateHz: number, telemetryUiRateHz: number, telemetryUiMode: TelemetryUiMode, telemetryHistoryMinutes: number, alarmRules: Array<AlarmRule>, batteryReserveMinutes: number, ascentSpeed: number, captureTraffic: boolean, captureDirectory: string, diveSessionOnConnect: boolean, diveOperator: string, diveSite: string, infoLogging: boolean, keyboard: KeyboardBindings, gamepad: GamepadBindings, };
+
+export type FrameDirection = "inbound" | "outbound";
+
+export type FrameKind = "text" | "binary" | "ping" | "pong" | "close";
+
+export type CapturedFrame = { direction: FrameDirection, elapsedMs: number, kind: FrameKind, text: string, };
+
+export type DiveRecordingFormat = "csv" | "jsonl";
+
+export type DiveRecording = { path: string, vehicleId: string, format: DiveRecordingFormat, startedMs: number, bytes: number, recording: boolean, };
+
+export type DiveSample = { "kind": "telemetry", "data": RovTelemetry } | { "kind": "status", "data": RovStatus } | { "kind": "log", "data": LogEntry } | { "kind": "control", "data": unknown };
+
+export type DiveRecord = { timeMs: number, } & ({ "kind": "telemetry", "data": RovTelemetry } | { "kind": "status", "data": RovStatus } | { "kind": "log", "data": LogEntry } | { "kind": "control", "data": unknown });
+
+export type DiveSummary = { durationMs: number, maxDepth: number | null, averageDepth: number | null, minWaterTemperature: number | null, maxWaterTemperature: number | null, batteryUsed: number | null, pitchStabilizationMs: number, rollStabilizationMs: number, depthHoldMs: number, };
+
+export type DiveSession = { id: string, vehicleId: string, operator: string, site: string, firmwareVersion: string | null, rovConfig: RovConfig | null, startedMs: number, endedMs: number | null, recordings: Array<string>, dataFiles: Array<string>, summary: DiveSummary | null, };
+
+export type DiveReplayState = { path: string, vehicleId: string, speed: number, paused: boolean, positionMs: number, durationMs: number, finished: boolean, };
+
+export type EventPayloads = {
+  rov_telemetry: VehicleEvent<RovTelemetry>;
+  rov_telemetry_batch: VehicleEvent<Array<RovTelemetry>>;
+  rov_status_update: VehicleEvent<RovStatus>;
+  rov_config_received: VehicleEvent<RovConfig>;
+  regulator_suggestions_received: VehicleEvent<RegulatorSuggestions>;
+  firmware_version_recieved: VehicleEvent<string>;
+  rov_unknown_message: VehicleEvent<UnknownMessage>;
+  rov_handshake_completed: VehicleEvent<Handshake>;
+  rov_connection_state_changed: VehicleEvent<ConnectionState>;
+  rov_connection_status_updated: VehicleEvent<ConnectionStatus>;
+  link_quality: VehicleEvent<LinkQuality>;
+  log_message: VehicleEvent<LogEntry>;
+  dive_replay_state: VehicleEvent<DiveReplayState>;
+  alarm_changed: VehicleEvent<Alarm>;
+  battery_estimate: VehicleEvent<BatteryEstimate>;
+  show_toast: VehicleEvent<Toast>;
+  discovered_rovs: Array<DiscoveredRov>;
+  gamepad_event: GamepadData;
+};
+
+export function listenEvent<E extends keyof EventPayloads>(
+  event: E,
+  handler: EventCallback<EventPayloads[E]>,
+) {
+  return listen<EventPayloads[E]>(event, handler);
+}
+
+export const commands = {
+  getConfig: () => invoke<Config>('get_config'),
+  setConfig: (args: { payload: Config }) =>
+    invoke<null>('set_config', args),
+  createRovProfile: (args: { payload: RovProfile }) =>
+    invoke<Config>('create_rov_profile', args),
+  updateRovProfile: (args: { name: string; payload: RovProfile }) =>
+    invoke<Config>('update_rov_profile', args),
+  deleteRovProfile: (args: { name: string }) =>
+    invoke<Config>('delete_rov_profile', args),
+  switchRovProfile: (args: { name: string }) =>
+    invoke<Config>('switch_rov_profile', args),
+  listVehicles: () => invoke<Array<VehicleInfo>>('list_vehicles'),
+  openVehicle: (args: { profile: string }) =>
+    invoke<Array<VehicleInfo>>('open_vehicle', args),
+  closeVehicle: (args: { vehicle: string }) =>
+    invoke<Array<VehicleInfo>>('close_vehicle', args),
+  setPilotVehicle: (args: { vehicle: string }) =>
+    invoke<Array<VehicleInfo>>('set_pilot_vehicle', args),
+  discoverRovs: () => invoke<Array<DiscoveredRov>>('discover_rovs'),
+  startGamepadStream: () => invoke<null>('start_gamepad_stream'),
+  sendDirectionVector: (args: { payload: [number, number, number, number, number, number, number, number] }) =>
+    invoke<null>('send_direction_vector', args),
+  getDirectionVectorStats: (args: { vehicle?: string | null } = {}) =>
+    invoke<DirectionVectorStats>('get_direction_vector_stats', args),
+  sendCustomAction: (args: { vehicle?: string | null; payload: string }) =>
+    invoke<null>('send_custom_action', args),
+  togglePitchStabilization: (args: { vehicle?: string | null } = {}) =>
+    invoke<null>('toggle_pitch_stabilization', args),
+  toggleRollStabilization: (args: { vehicle?: string | null } = {}) =>
+    invoke<null>('toggle_roll_stabilization', args),
+  toggleDepthHold: (args: { vehicle?: string | null } = {}) =>
+    invoke<null>('toggle_depth_hold', args),
+  saveRecording: (args: { tempPath: string }) =>
+    invoke<null>('save_recording', args),
+  appendRecordingChunk: (args: { tempPath: string; chunk: Array<number> }) =>
+    invoke<null>('append_recording_chunk', args),
+  requestRovConfig: (args: { vehicle?: string | null } = {}) =>
+    invoke<RovConfig>('request_rov_config', args),
+  setRovConfig: (args: { vehicle?: string | null; payload: RovConfig }) =>
+    invoke<RovConfig>('set_rov_config', args),
+  startThrusterTest: (args: { vehicle?: string | null; payload: number }) =>
+    invoke<null>('start_thruster_test', args),
+  cancelThrusterTest: (args: { vehicle?: string | null; payload: number }) =>
+    invoke<null>('cancel_thruster_test', args),
+  startRegulatorAutoTuning: (args: { vehicle?: string | null } = {}) =>
+    invoke<null>('start_regulator_auto_tuning', args),
+  cancelRegulatorAutoTuning: (args: { vehicle?: string | null } = {}) =>
+    invoke<null>('cancel_regulator_auto_tuning', args),
+  flashMicrocontrollerFirmware: (args: { vehicle?: string | null; payload: MicrocontrollerFirmwareVariant }) =>
+    invoke<null>('flash_microcontroller_firmware', args),
+  getRovHandshake: (args: { vehicle?: string | null } = {}) =>
+    invoke<Handshake | null>('get_rov_handshake', args),
+  getConnectionState: (args: { vehicle?: string | null } = {}) =>
+    invoke<ConnectionState>('get_connection_state', args),
+  connect: (args: { vehicle?: string | null } = {}) =>
+    invoke<null>('connect', args),
+  disconnect: (args: { vehicle?: string | null } = {}) =>
+    invoke<null>('disconnect', args),
+  forgetRovCertificate: (args: { vehicle?: string | null } = {}) =>
+    invoke<Config>('forget_rov_certificate', args),
+  getLinkQuality: (args: { vehicle?: string | null } = {}) =>
+    invoke<LinkQuality>('get_link_quality', args),
+  getTelemetryUiStats: (args: { vehicle?: string | null } = {}) =>
+    invoke<TelemetryUiStats>('get_telemetry_ui_stats', args),
+  queryTelemetry: (args: { vehicle?: string | null; fields: Array<TelemetryField>; from?: number | null; to?: number | null; maxPoints: number }) =>
+    invoke<Array<TelemetrySeries>>('query_telemetry', args),
+  listAlarms: () => invoke<Array<Alarm>>('list_alarms'),
+  acknowledgeAlarm: (args: { id: number }) =>
+    invoke<Alarm>('acknowledge_alarm', args),
+  getBatteryEstimate: (args: { vehicle?: string | null } = {}) =>
+    invoke<BatteryEstimate | null>('get_battery_estimate', args),
+  startDiveRecording: (args: { vehicle?: string | null; format?: DiveRecordingFormat | null } = {}) =>
+    invoke<DiveRecording>('start_dive_recording', args),
+  stopDiveRecording: (args: { vehicle?: string | null } = {}) =>
+    invoke<DiveRecording>('stop_dive_recording', args),
+  listDiveRecordings: () => invoke<Array<DiveRecording>>('list_dive_recordings'),
+  startDiveSession: (args: { vehicle?: string | null; operator?: string | null; site?: string | null } = {}) =>
+    invoke<DiveSession>('start_dive_session', args),
+  stopDiveSession: (args: { vehicle?: string | null } = {}) =>
+    invoke<DiveSession>('stop_dive_session', args),
+  listDiveSessions: () => invoke<Array<DiveSession>>('list_dive_sessions'),
+  startDiveReplay: (args: { path: string; speed?: number | null; vehicle?: string | null }) =>
+    invoke<DiveReplayState>('start_dive_replay', args),
+  pauseDiveReplay: () => invoke<DiveReplayState>('pause_dive_replay'),
+  resumeDiveReplay: () => invoke<DiveReplayState>('resume_dive_replay'),
+  seekDiveReplay: (args: { positionMs: number }) =>
+    invoke<DiveReplayState>('seek_dive_replay', args),
+  setDiveReplaySpeed: (args: { speed: number }) =>
+    invoke<DiveReplayState>('set_dive_replay_speed', args),
+  stopDiveReplay: () => invoke<DiveReplayState>('stop_dive_replay'),
+  getDiveReplayState: () => invoke<DiveReplayState | null>('get_dive_replay_state'),
+  getOutboundQueue: (args: { vehicle?: string | null } = {}) =>
+    invoke<Array<QueuedMessage>>('get_outbound_queue', args),
+  cancelOutboundMessage: (args: { vehicle?: string | null; id: number }) =>
+    invoke<null>('cancel_outbound_message', args),
+  replayCapture: (args: { paths: Array<string>; speed?: number | null; vehicle?: string | null }) =>
+    invoke<null>('replay_capture', args),
+  stopCaptureReplay: () => invoke<boolean>('stop_capture_replay'),
+};
@@ -0,0 +1,21 @@
+import { invoke } from '@tauri-apps/api/core';
+
+import { toast } from '@/components/ui/Toaster';
+
+import { logError } from '@/lib/log';
+
+async function replayCapture(paths: string[], speed?: number, vehicle?: string) {
+  await invoke('replay_capture', { paths, speed, vehicle }).catch((error) => {
+    logError('Failed to replay capture:', error);
+    toast.error(String(error));
+  });
+}
+
+async function stopCaptureReplay() {
+  return await invoke<boolean>('stop_capture_replay').catch((error) => {
+    logError('Failed to stop capture replay:', error);
+    return false;
+  });
+}
+
+export { replayCapture, stopCaptureReplay };
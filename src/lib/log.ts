@@ -6,16 +6,9 @@ import {
   openDB,
 } from 'idb';
 
-import { configStore } from '@/stores/config';
-
-type LogLevel = 'info' | 'warn' | 'error';
-type LogOrigin = 'frontend' | 'backend' | 'firmware';
+import { type LogEntry, type LogLevel, type LogOrigin } from '@/lib/bindings';
 
-type LogEntry = {
-  origin: LogOrigin;
-  level: LogLevel;
-  message: string;
-};
+import { configStore } from '@/stores/config';
 
 type LogRecord = {
   id: number;
@@ -0,0 +1,58 @@
+import { type EventCallback } from '@tauri-apps/api/event';
+
+import {
+  type EventPayloads,
+  type VehicleEvent,
+  listenEvent,
+} from '@/lib/bindings';
+
+type VehicleEventName = {
+  [E in keyof EventPayloads]: EventPayloads[E] extends VehicleEvent<unknown>
+    ? E
+    : never;
+}[keyof EventPayloads];
+
+type VehicleEventPayload<E extends VehicleEventName> =
+  EventPayloads[E] extends VehicleEvent<infer T> ? T : never;
+
+// Vehicles a dive replay plays back as, from the replayed connection state.
+// The backend holds their own events back meanwhile.
+const replaying = new Set<string>();
+let trackingReplays: Promise<unknown> | null = null;
+
+function trackReplays() {
+  if (!trackingReplays) {
+    trackingReplays = listenEvent(
+      'rov_connection_state_changed',
+      ({ payload }) => {
+        if (!payload.replay || payload.vehicleId === null) return;
+        if (payload.payload.phase === 'disconnected') {
+          replaying.delete(payload.vehicleId);
+        } else {
+          replaying.add(payload.vehicleId);
+        }
+      },
+    );
+  }
+  return trackingReplays;
+}
+
+// Listens to an event of the vehicle receiving pilot input, events raised by
+// the app itself pass as well. While a replay plays back as the vehicle only
+// the replayed events pass, never one of its own still on the way.
+async function listenPilot<E extends VehicleEventName>(
+  event: E,
+  handler: EventCallback<VehicleEventPayload<E>>,
+) {
+  await trackReplays();
+  return listenEvent(event, ({ payload, ...rest }) => {
+    const vehicleEvent = payload as VehicleEvent<VehicleEventPayload<E>>;
+    if (vehicleEvent.vehicleId !== null) {
+      if (!vehicleEvent.pilot) return;
+      if (!vehicleEvent.replay && replaying.has(vehicleEvent.vehicleId)) return;
+    }
+    handler({ ...rest, payload: vehicleEvent.payload });
+  });
+}
+
+export { listenPilot, type VehicleEvent };
@@ -3,70 +3,22 @@ import { invoke } from '@tauri-apps/api/core';
 
 import { toast } from '@/components/ui/Toaster';
 
+import {
+  type AttitudeIndicator,
+  type Config,
+  type ControlSource,
+  type GamepadBindings,
+  type KeyboardBindings,
+  type RovProfile,
+  commands,
+} from '@/lib/bindings';
 import { logError } from '@/lib/log';
 
-type KeyboardBindings = {
-  surgeForward: string;
-  surgeBackward: string;
-  swayLeft: string;
-  swayRight: string;
-  heaveUp: string;
-  heaveDown: string;
-  pitchUp: string;
-  pitchDown: string;
-  yawLeft: string;
-  yawRight: string;
-  rollLeft: string;
-  rollRight: string;
-  action1Positive: string;
-  action1Negative: string;
-  action2Positive: string;
-  action2Negative: string;
-  pitchStabilization: string;
-  rollStabilization: string;
-  depthHold: string;
-  record: string;
-};
-
-type ControlSource = 'leftStick' | 'rightStick' | 'dPad' | 'faceButtons';
-type AttitudeIndicator = 'scientific' | 'dimensional3D' | 'disabled';
-
-type GamepadBindings = {
-  surgeSway: ControlSource;
-  heaveUp: string;
-  heaveDown: string;
-  pitchYaw: ControlSource;
-  rollLeft: string;
-  rollRight: string;
-  action1Positive: string;
-  action1Negative: string;
-  action2Positive: string;
-  action2Negative: string;
-  pitchStabilization: string;
-  rollStabilization: string;
-  depthHold: string;
-  record: string;
-};
-
-type Config = {
-  autoUpdate: boolean;
-  attitudeIndicator: AttitudeIndicator;
-  workIndicator: boolean;
-  thrusterRpmOverlay: boolean;
-  videoDirectory: string;
-  ipAddress: string;
-  webrtcSignalingApiPort: number;
-  webrtcSignalingApiPath: string;
-  webSocketPort: number;
-  infoLogging: boolean;
-  keyboard: KeyboardBindings;
-  gamepad: GamepadBindings;
-};
-
 const configStore = new Store<Config | null>(null);
 
 async function getConfig() {
-  await invoke<Config>('get_config')
+  await commands
+    .getConfig()
     .then((payload) => configStore.setState(() => payload))
     .catch((error) => {
       logError('Failed to get config:', error);
@@ -89,20 +41,55 @@ async function setConfig(newConfigOptions: Partial<Config>) {
 
   configStore.setState(() => newConfig);
 
-  await invoke('set_config', { payload: newConfig }).catch((error) => {
+  await commands.setConfig({ payload: newConfig }).catch((error) => {
     configStore.setState(() => currentConfig);
     logError('Failed to set config:', error);
     toast.error('Failed to set config. Changes reverted.');
   });
 }
 
+async function updateRovProfiles(command: string, args: Record<string, unknown>) {
+  await invoke<Config>(command, args)
+    .then((payload) => configStore.setState(() => payload))
+    .catch((error) => {
+      logError(`Failed to ${command.replaceAll('_', ' ')}:`, error);
+      toast.error(String(error));
+    });
+}
+
+async function createRovProfile(profile: RovProfile) {
+  await updateRovProfiles('create_rov_profile', { payload: profile });
+}
+
+async function updateRovProfile(name: string, profile: RovProfile) {
+  await updateRovProfiles('update_rov_profile', { name, payload: profile });
+}
+
+async function deleteRovProfile(name: string) {
+  await updateRovProfiles('delete_rov_profile', { name });
+}
+
+async function switchRovProfile(name: string) {
+  await updateRovProfiles('switch_rov_profile', { name });
+}
+
+async function forgetRovCertificate(vehicle?: string) {
+  await updateRovProfiles('forget_rov_certificate', { vehicle });
+}
+
 export {
   configStore,
   getConfig,
   setConfig,
+  createRovProfile,
+  updateRovProfile,
+  deleteRovProfile,
+  switchRovProfile,
+  forgetRovCertificate,
   type KeyboardBindings,
   type ControlSource,
   type GamepadBindings,
   type Config,
+  type RovProfile,
   type AttitudeIndicator,
 };
@@ -1,9 +1,6 @@
 import { Store } from '@tanstack/react-store';
 
-type ConnectionStatus = {
-  isConnected: boolean;
-  delay: number;
-};
+import { type ConnectionStatus } from '@/lib/bindings';
 
 const connectionStatusStore = new Store<ConnectionStatus>({
   isConnected: false,
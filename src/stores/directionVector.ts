@@ -1,15 +1,6 @@
 import { Store } from '@tanstack/react-store';
 
-type DirectionVector = [
-  number,
-  number,
-  number,
-  number,
-  number,
-  number,
-  number,
-  number,
-];
+import { type DirectionVector } from '@/lib/bindings';
 
 const directionVectorStore = new Store<DirectionVector>([
   0, 0, 0, 0, 0, 0, 0, 0,
@@ -1,66 +1,28 @@
 import { Store } from '@tanstack/react-store';
-import { invoke } from '@tauri-apps/api/core';
 
 import { toast } from '@/components/ui/Toaster';
 
+import {
+  type DirectionCoefficients,
+  type Power,
+  type Regulator,
+  type RovConfig,
+  type ThrusterAllocation,
+  type ThrusterPinSetup,
+  commands,
+} from '@/lib/bindings';
 import { logError } from '@/lib/log';
 
 import { connectionStatusStore } from '@/stores/connectionStatus';
 
-type Row = [number, number, number, number, number, number, number, number];
-
-type ThrusterPinSetup = {
-  identifiers: Row;
-  spinDirections: Row;
-};
-
-type ThrusterAllocation = [Row, Row, Row, Row, Row, Row, Row, Row];
-
-type pid = {
-  kp: number;
-  ki: number;
-  kd: number;
-};
-
-type Regulator = {
-  turnSpeed: number;
-  pitch: pid;
-  roll: pid;
-  depth: pid;
-};
-
-type DirectionCoefficients = {
-  surge: number;
-  sway: number;
-  heave: number;
-  pitch: number;
-  yaw: number;
-  roll: number;
-};
-
-type Power = {
-  userMaxPower: number;
-  regulatorMaxPower: number;
-  batteryMinVoltage: number;
-  batteryMaxVoltage: number;
-};
-
-type RovConfig = {
-  microcontrollerFirmwareVariant: 'pwm' | 'dshot';
-  fluidType: 'saltwater' | 'freshwater';
-  thrusterPinSetup: ThrusterPinSetup;
-  thrusterAllocation: ThrusterAllocation;
-  regulator: Regulator;
-  directionCoefficients: DirectionCoefficients;
-  power: Power;
-};
+type Row = ThrusterPinSetup['identifiers'];
 
 const rovConfigStore = new Store<RovConfig | null>(null);
 
 async function requestRovConfig() {
   if (!connectionStatusStore.state.isConnected) return;
 
-  await invoke('request_rov_config').catch((error) => {
+  await commands.requestRovConfig().catch((error) => {
     logError('Failed to request ROV config:', error);
     toast.error('Failed to request ROV config');
   });
@@ -81,7 +43,7 @@ async function setRovConfig(newConfigOptions: Partial<RovConfig>) {
 
   rovConfigStore.setState(() => newRovConfig);
 
-  await invoke('set_rov_config', { payload: newRovConfig }).catch((error) => {
+  await commands.setRovConfig({ payload: newRovConfig }).catch((error) => {
     rovConfigStore.setState(() => currentRovConfig);
     logError('Failed to set ROV config:', error);
     toast.error('Failed to set ROV config. Changes reverted.');
@@ -1,18 +1,6 @@
 import { Store } from '@tanstack/react-store';
 
-type SystemHealth = {
-  imuOk: boolean;
-  pressureSensorOk: boolean;
-  microcontrollerOk: boolean;
-};
-
-type RovStatus = {
-  pitchStabilization: boolean;
-  rollStabilization: boolean;
-  depthHold: boolean;
-  batteryPercentage: number;
-  health: SystemHealth;
-};
+import { type RovStatus } from '@/lib/bindings';
 
 const rovStatusStore = new Store<RovStatus>({
   pitchStabilization: false,
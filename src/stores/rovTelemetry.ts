@@ -1,25 +1,6 @@
 import { Store } from '@tanstack/react-store';
 
-type RovTelemetry = {
-  pitch: number;
-  roll: number;
-  desiredPitch: number;
-  desiredRoll: number;
-  depth: number;
-  waterTemperature: number;
-  electronicsTemperature: number;
-  thrusterRpms: [
-    number,
-    number,
-    number,
-    number,
-    number,
-    number,
-    number,
-    number,
-  ];
-  workIndicatorPercentage: number;
-};
+import { type RovTelemetry } from '@/lib/bindings';
 
 const rovTelemetryStore = new Store<RovTelemetry>({
   pitch: 0,
@@ -27,8 +8,6 @@ const rovTelemetryStore = new Store<RovTelemetry>({
   desiredPitch: 0,
   desiredRoll: 0,
   depth: 0,
-  waterTemperature: 0,
-  electronicsTemperature: 0,
   thrusterRpms: [0, 0, 0, 0, 0, 0, 0, 0],
   workIndicatorPercentage: 0,
 });
@@ -0,0 +1,47 @@
+import { Store } from '@tanstack/react-store';
+import { invoke } from '@tauri-apps/api/core';
+
+import { toast } from '@/components/ui/Toaster';
+
+import { type VehicleInfo } from '@/lib/bindings';
+import { logError } from '@/lib/log';
+
+const vehiclesStore = new Store<VehicleInfo[]>([]);
+
+async function listVehicles() {
+  await invoke<VehicleInfo[]>('list_vehicles')
+    .then((payload) => vehiclesStore.setState(() => payload))
+    .catch((error) => {
+      logError('Failed to list vehicles:', error);
+    });
+}
+
+async function updateVehicles(command: string, args: Record<string, unknown>) {
+  await invoke<VehicleInfo[]>(command, args)
+    .then((payload) => vehiclesStore.setState(() => payload))
+    .catch((error) => {
+      logError(`Failed to ${command.replaceAll('_', ' ')}:`, error);
+      toast.error(String(error));
+    });
+}
+
+async function openVehicle(profile: string) {
+  await updateVehicles('open_vehicle', { profile });
+}
+
+async function closeVehicle(vehicle: string) {
+  await updateVehicles('close_vehicle', { vehicle });
+}
+
+async function setPilotVehicle(vehicle: string) {
+  await updateVehicles('set_pilot_vehicle', { vehicle });
+}
+
+export {
+  vehiclesStore,
+  listVehicles,
+  openVehicle,
+  closeVehicle,
+  setPilotVehicle,
+  type VehicleInfo,
+};